@@ -0,0 +1,29 @@
+use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, config::HookBuilder, Section};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Build a report with a short chain and `sections` notes attached
+fn report(sections: usize) -> Error {
+    let mut result = Err::<(), _>(anyhow!("connection reset by peer")).context("request failed");
+    for n in 0..sections {
+        result = result.note(format!("attempt {} timed out", n));
+    }
+    result.unwrap_err()
+}
+
+fn construct_and_format(c: &mut Criterion) {
+    std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    HookBuilder::blank().install().unwrap();
+
+    let mut group = c.benchmark_group("construct_and_format");
+    for sections in [0, 1, 3].iter() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(sections),
+            sections,
+            |b, &sections| b.iter(|| format!("{:?}", report(sections))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, construct_and_format);
+criterion_main!(benches);
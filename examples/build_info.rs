@@ -0,0 +1,63 @@
+//! A build script collecting the information read by `color_anyhow::build_info!()`
+//!
+//! Copy this file to the `build.rs` of the application, then pass
+//! `color_anyhow::build_info!()` to `HookBuilder::build_info`.
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_owned())
+}
+
+/// The date of the build, honoring `SOURCE_DATE_EPOCH` for reproducible builds
+fn build_date() -> String {
+    let secs = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        });
+
+    // days since the epoch to a date in the proleptic Gregorian calendar
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn main() {
+    if let Some(commit) = git(&["rev-parse", "--short", "HEAD"]) {
+        println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_COMMIT={}", commit);
+    }
+    // the variable is only set for dirty builds, so it can be checked in a `const`
+    if matches!(git(&["status", "--porcelain"]), Some(status) if !status.is_empty()) {
+        println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_DIRTY=1");
+    }
+    // cargo only sets these for build scripts
+    if let Ok(profile) = env::var("PROFILE") {
+        println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_PROFILE={}", profile);
+    }
+    if let Ok(target) = env::var("TARGET") {
+        println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_TARGET={}", target);
+    }
+    println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_DATE={}", build_date());
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
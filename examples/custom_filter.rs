@@ -4,47 +4,29 @@ use tracing::{info, instrument};
 #[instrument]
 fn main() -> Result<(), Error> {
     std::env::set_var("RUST_BACKTRACE", "1");
-    #[cfg(feature = "capture-spantrace")]
-    install_tracing();
-
-    color_anyhow::config::HookBuilder::default()
-        .add_frame_filter(Box::new(|frames| {
-            let filters = &["custom_filter::main"];
-
-            frames.retain(|frame| {
-                !filters.iter().any(|f| {
-                    let name = if let Some(name) = frame.name.as_ref() {
-                        name.as_str()
-                    } else {
-                        return true;
-                    };
-
-                    name.starts_with(f)
-                })
-            });
-        }))
-        .install()
-        .unwrap();
 
-    Ok(read_config()?)
-}
+    let hook = color_anyhow::config::HookBuilder::default().add_frame_filter(Box::new(|frames| {
+        let filters = &["custom_filter::main"];
+
+        frames.retain(|frame| {
+            !filters.iter().any(|f| {
+                let name = if let Some(name) = frame.name.as_ref() {
+                    name.as_str()
+                } else {
+                    return true;
+                };
 
-#[cfg(feature = "capture-spantrace")]
-fn install_tracing() {
-    use tracing_error::ErrorLayer;
-    use tracing_subscriber::prelude::*;
-    use tracing_subscriber::{fmt, EnvFilter};
-
-    let fmt_layer = fmt::layer().with_target(false);
-    let filter_layer = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new("info"))
-        .unwrap();
-
-    tracing_subscriber::registry()
-        .with(filter_layer)
-        .with(fmt_layer)
-        .with(ErrorLayer::default())
-        .init();
+                name.starts_with(f)
+            })
+        });
+    }));
+
+    #[cfg(feature = "tracing-init")]
+    hook.install_with_tracing("info").unwrap();
+    #[cfg(not(feature = "tracing-init"))]
+    hook.install().unwrap();
+
+    Ok(read_config()?)
 }
 
 #[instrument]
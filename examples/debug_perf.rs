@@ -8,8 +8,9 @@ use color_anyhow::{
 use tracing::instrument;
 
 fn main() -> Result<(), Error> {
-    #[cfg(feature = "capture-spantrace")]
-    install_tracing();
+    #[cfg(feature = "tracing-init")]
+    color_anyhow::install_with_tracing()?;
+    #[cfg(not(feature = "tracing-init"))]
     color_anyhow::install()?;
 
     time_report();
@@ -36,21 +37,3 @@ fn time_report_inner() {
 
     dbg!(end - start);
 }
-
-#[cfg(feature = "capture-spantrace")]
-fn install_tracing() {
-    use tracing_error::ErrorLayer;
-    use tracing_subscriber::prelude::*;
-    use tracing_subscriber::{fmt, EnvFilter};
-
-    let fmt_layer = fmt::layer().with_target(false);
-    let filter_layer = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new("info"))
-        .unwrap();
-
-    tracing_subscriber::registry()
-        .with(filter_layer)
-        .with(fmt_layer)
-        .with(ErrorLayer::default())
-        .init();
-}
@@ -0,0 +1,275 @@
+//! Machine readable artifacts of panics, written by the panic hook
+use crate::config::PanicHook;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The format of the panic artifacts written with `HookBuilder::panic_artifact`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArtifactFormat {
+    /// The report printed by the panic hook, without colors, in a `.txt` file
+    Plain,
+    /// A JSON object in a `.json` file
+    ///
+    /// The object has the panic `message`, its `location` as an object with the `file`,
+    /// `line` and `column`, or `null` if it's unknown, the name of the panicking `thread`,
+    /// the `timestamp` of the panic in RFC 3339 format, the `report_id`, the `build` if
+    /// one was given to `HookBuilder::build_info`, the `backtrace` as an array of frames
+    /// with their `n`, `name`, `file`, `line` and whether the frame filters `hidden` them,
+    /// and the `report` printed by the panic hook, without colors.
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::HookBuilder, ArtifactFormat, ArtifactSpec};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("color-anyhow-json-{}", std::process::id()));
+    /// HookBuilder::default()
+    ///     .panic_artifact(ArtifactSpec::new(&dir).format(ArtifactFormat::Json))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let _ = std::thread::Builder::new()
+    ///     .name("indexer".into())
+    ///     .spawn(|| panic!("the index is corrupt"))
+    ///     .unwrap()
+    ///     .join();
+    ///
+    /// let artifact = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    /// assert_eq!(artifact.extension().unwrap(), "json");
+    ///
+    /// let artifact = std::fs::read_to_string(&artifact).unwrap();
+    /// let artifact: serde_json::Value = serde_json::from_str(&artifact).unwrap();
+    /// assert_eq!(artifact["message"], "the index is corrupt");
+    /// assert_eq!(artifact["thread"], "indexer");
+    /// assert_eq!(artifact["report_id"].as_str().unwrap().len(), 26);
+    /// assert!(artifact["location"]["line"].is_u64());
+    /// assert!(artifact["backtrace"].is_array());
+    /// assert!(artifact["report"].as_str().unwrap().starts_with("The application panicked"));
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    Json,
+}
+
+impl ArtifactFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ArtifactFormat::Plain => "txt",
+            #[cfg(feature = "json")]
+            ArtifactFormat::Json => "json",
+        }
+    }
+}
+
+/// Where and how the panic hook writes an artifact of each panic, for
+/// `HookBuilder::panic_artifact`
+///
+/// # Details
+///
+/// Artifacts are written to a directory, which is created if it doesn't exist, in files
+/// named after a template followed by the extension of their format. The template
+/// defaults to `panic-{timestamp}-{report_id}`, where
+///
+/// - `{timestamp}` is the time of the panic in UTC, e.g. `20240502T101500Z`
+/// - `{report_id}` is the identifier of the report, the one shown in the report with
+///   `HookBuilder::display_report_id`, or a new one otherwise
+/// - `{pid}` is the id of the process
+///
+/// Other text in the template is used as is. With a retention count, the oldest artifacts
+/// in the directory beyond that count are deleted after each one is written. Only the
+/// files whose names start with the text of the template before its first placeholder
+/// and end with the extension of the format count as artifacts, so the directory can be
+/// shared with other files.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{ArtifactFormat, ArtifactSpec};
+///
+/// let spec = ArtifactSpec::new(std::env::temp_dir().join("myapp-crashes"))
+///     .file_name("myapp-{pid}-{timestamp}")
+///     .format(ArtifactFormat::Plain)
+///     .retain(10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactSpec {
+    dir: PathBuf,
+    template: String,
+    format: ArtifactFormat,
+    retain: Option<usize>,
+}
+
+impl ArtifactSpec {
+    /// Write the artifacts to `dir`, as plain text, keeping all of them
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            template: "panic-{timestamp}-{report_id}".to_owned(),
+            format: ArtifactFormat::Plain,
+            retain: None,
+        }
+    }
+
+    /// Name the artifacts after `template`, without their extension
+    pub fn file_name(mut self, template: &str) -> Self {
+        self.template = template.to_owned();
+        self
+    }
+
+    /// Write the artifacts in `format`, defaults to `ArtifactFormat::Plain`
+    pub fn format(mut self, format: ArtifactFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Keep only the `count` most recent artifacts in the directory
+    pub fn retain(mut self, count: usize) -> Self {
+        self.retain = Some(count);
+        self
+    }
+
+    /// Returns the directory the artifacts are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The name of the artifact of a panic at `time`
+    fn expand(&self, time: SystemTime, report_id: &str) -> String {
+        let (date, secs_of_day) = utc(time);
+        let timestamp = format!(
+            "{}T{:02}{:02}{:02}Z",
+            date.replace('-', ""),
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        );
+
+        let name = self
+            .template
+            .replace("{timestamp}", &timestamp)
+            .replace("{report_id}", report_id)
+            .replace("{pid}", &std::process::id().to_string());
+        format!("{}.{}", name, self.format.extension())
+    }
+
+    /// Whether the file named `name` is an artifact written with this spec
+    fn is_artifact(&self, name: &str) -> bool {
+        let prefix = self.template.split('{').next().unwrap_or_default();
+        let extension = format!(".{}", self.format.extension());
+        name.starts_with(prefix) && name.ends_with(&extension)
+    }
+
+    /// Delete the oldest artifacts beyond the retention count
+    fn prune(&self) -> io::Result<()> {
+        let retain = match self.retain {
+            Some(retain) => retain,
+            None => return Ok(()),
+        };
+
+        let mut artifacts = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let name = entry.file_name();
+            if metadata.is_file() && self.is_artifact(&name.to_string_lossy()) {
+                artifacts.push((metadata.modified()?, name, entry.path()));
+            }
+        }
+
+        // oldest first, artifacts written within the same tick are ordered by name
+        artifacts.sort();
+        let excess = artifacts.len().saturating_sub(retain);
+        for (_, _, path) in artifacts.drain(..excess) {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write the artifact of the panic `pi` as specified by `spec`, returning its path
+#[allow(unused_variables)]
+pub(crate) fn write_panic_artifact(
+    spec: &ArtifactSpec,
+    printer: &PanicHook,
+    pi: &std::panic::PanicInfo<'_>,
+    report_id: &str,
+    report: &str,
+) -> io::Result<PathBuf> {
+    let time = crate::clock::now();
+    let report = printer
+        .line_ending()
+        .apply(crate::writers::strip_ansi(report));
+
+    let contents = match spec.format {
+        ArtifactFormat::Plain => report,
+        #[cfg(feature = "json")]
+        ArtifactFormat::Json => {
+            let (date, secs_of_day) = utc(time);
+            let millis = time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_millis();
+            let timestamp = format!(
+                "{}T{:02}:{:02}:{:02}.{:03}Z",
+                date,
+                secs_of_day / 3600,
+                secs_of_day / 60 % 60,
+                secs_of_day % 60,
+                millis
+            );
+
+            let location = pi.location().map(|location| {
+                serde_json::json!({
+                    "file": location.file(),
+                    "line": location.line(),
+                    "column": location.column(),
+                })
+            });
+            let backtrace: Vec<_> = printer
+                .frame_infos(&crate::config::capture_backtrace())
+                .into_iter()
+                .map(|frame| {
+                    serde_json::json!({
+                        "n": frame.n,
+                        "name": frame.name,
+                        "file": frame.filename.map(|file| file.display().to_string()),
+                        "line": frame.lineno,
+                        "hidden": frame.hidden,
+                    })
+                })
+                .collect();
+
+            let artifact = serde_json::json!({
+                "message": crate::config::panic_payload(pi),
+                "location": location,
+                "thread": std::thread::current().name(),
+                "timestamp": timestamp,
+                "report_id": report_id,
+                "build": printer.build_info().map(ToString::to_string),
+                "backtrace": backtrace,
+                "report": report,
+            });
+            serde_json::to_string_pretty(&artifact)?
+        }
+    };
+
+    fs::create_dir_all(&spec.dir)?;
+    let path = spec.dir.join(spec.expand(time, report_id));
+    fs::write(&path, contents)?;
+    spec.prune()?;
+
+    Ok(path)
+}
+
+/// The date of `time` in UTC, as `YYYY-MM-DD`, and the seconds elapsed on that day
+fn utc(time: SystemTime) -> (String, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (year, month, day) = crate::timestamps::civil_from_days((secs / 86400) as i64);
+    (format!("{:04}-{:02}-{:02}", year, month, day), secs % 86400)
+}
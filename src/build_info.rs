@@ -0,0 +1,180 @@
+//! Information identifying the build of the application, for panic and error reports
+use std::fmt;
+
+/// Information identifying the build of the application, shown in reports with
+/// `HookBuilder::build_info`
+///
+/// # Details
+///
+/// Every field is optional, and the fields that are set are rendered on a single
+/// line, e.g. `Build: 1a2b3c4 (dirty) release x86_64-unknown-linux-gnu 2024-05-02`.
+/// The values are rendered as given, so the commit is usually an abbreviated hash.
+/// A `BuildInfo` can be constructed in a `const`, either with all of its fields
+/// or from [`BuildInfo::new`] with struct update syntax. Applications without
+/// the data at hand can collect it in their build script, see [`build_info!`].
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::BuildInfo;
+///
+/// const BUILD: BuildInfo = BuildInfo {
+///     commit: Some("1a2b3c4"),
+///     dirty: true,
+///     profile: Some("release"),
+///     target: Some("x86_64-unknown-linux-gnu"),
+///     timestamp: Some("2024-05-02"),
+/// };
+/// assert_eq!(
+///     BUILD.to_string(),
+///     "1a2b3c4 (dirty) release x86_64-unknown-linux-gnu 2024-05-02"
+/// );
+///
+/// const COMMIT_ONLY: BuildInfo = BuildInfo {
+///     commit: Some("1a2b3c4"),
+///     ..BuildInfo::new()
+/// };
+/// assert_eq!(COMMIT_ONLY.to_string(), "1a2b3c4");
+/// ```
+///
+/// [`BuildInfo::new`]: #method.new
+/// [`build_info!`]: macro.build_info.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BuildInfo {
+    /// The commit the application was built from
+    pub commit: Option<&'static str>,
+    /// Whether the working tree had uncommitted changes
+    pub dirty: bool,
+    /// The cargo profile, e.g. `release`
+    pub profile: Option<&'static str>,
+    /// The target triple
+    pub target: Option<&'static str>,
+    /// When the application was built
+    pub timestamp: Option<&'static str>,
+}
+
+impl BuildInfo {
+    /// Construct a `BuildInfo` without any information
+    pub const fn new() -> Self {
+        Self {
+            commit: None,
+            dirty: false,
+            profile: None,
+            target: None,
+            timestamp: None,
+        }
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dirty = Some("(dirty)").filter(|_| self.dirty);
+        let parts = [
+            self.commit,
+            dirty,
+            self.profile,
+            self.target,
+            self.timestamp,
+        ];
+
+        let mut parts = parts.iter().flatten();
+        if let Some(first) = parts.next() {
+            f.write_str(first)?;
+        }
+        for part in parts {
+            write!(f, " {}", part)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Construct a [`BuildInfo`] from the environment variables set by the build script of the
+/// calling crate
+///
+/// # Details
+///
+/// The macro reads `COLOR_ANYHOW_BUILD_COMMIT`, `COLOR_ANYHOW_BUILD_PROFILE`,
+/// `COLOR_ANYHOW_BUILD_TARGET` and `COLOR_ANYHOW_BUILD_DATE` at compile time, fields
+/// whose variable isn't set are left empty. The build is dirty if
+/// `COLOR_ANYHOW_BUILD_DIRTY` is set, to any value. The variables are set by a
+/// build script like [`examples/build_info.rs`], which can be copied to the
+/// `build.rs` of the application as is:
+///
+/// ```rust,ignore
+/// // build.rs
+/// use std::process::Command;
+///
+/// fn git(args: &[&str]) -> Option<String> {
+///     let output = Command::new("git").args(args).output().ok()?;
+///     if !output.status.success() {
+///         return None;
+///     }
+///     Some(String::from_utf8(output.stdout).ok()?.trim().to_owned())
+/// }
+///
+/// fn main() {
+///     if let Some(commit) = git(&["rev-parse", "--short", "HEAD"]) {
+///         println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_COMMIT={}", commit);
+///     }
+///     if matches!(git(&["status", "--porcelain"]), Some(status) if !status.is_empty()) {
+///         println!("cargo:rustc-env=COLOR_ANYHOW_BUILD_DIRTY=1");
+///     }
+///     // ... the profile, target and date, see examples/build_info.rs
+///     println!("cargo:rerun-if-changed=.git/HEAD");
+///     println!("cargo:rerun-if-changed=.git/index");
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{config::HookBuilder, BuildInfo};
+///
+/// const BUILD: BuildInfo = color_anyhow::build_info!();
+///
+/// // this crate has no build script setting the variables
+/// assert_eq!(BUILD, BuildInfo::new());
+///
+/// HookBuilder::default().build_info(BUILD).install().unwrap();
+/// ```
+///
+/// The fields the build script didn't set can be filled in by hand, the build is
+/// identified once per report:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::HookBuilder, BuildInfo};
+///
+/// const BUILD: BuildInfo = BuildInfo {
+///     commit: Some("1a2b3c4"),
+///     ..color_anyhow::build_info!()
+/// };
+///
+/// HookBuilder::default()
+///     .build_info(BUILD)
+///     .display_build_info_in_error_reports(true)
+///     .install()
+///     .unwrap();
+///
+/// let report = format!("{:?}", anyhow!("config could not be loaded"));
+/// let builds: Vec<_> = report.lines().filter(|line| line.contains("Build:")).collect();
+/// assert_eq!(builds.len(), 1);
+/// assert!(builds[0].contains("1a2b3c4"));
+/// ```
+///
+/// [`BuildInfo`]: struct.BuildInfo.html
+/// [`examples/build_info.rs`]: https://github.com/yaahc/color-anyhow/blob/master/examples/build_info.rs
+#[cfg(feature = "build-info")]
+#[cfg_attr(docsrs, doc(cfg(feature = "build-info")))]
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::BuildInfo {
+            commit: option_env!("COLOR_ANYHOW_BUILD_COMMIT"),
+            dirty: option_env!("COLOR_ANYHOW_BUILD_DIRTY").is_some(),
+            profile: option_env!("COLOR_ANYHOW_BUILD_PROFILE"),
+            target: option_env!("COLOR_ANYHOW_BUILD_TARGET"),
+            timestamp: option_env!("COLOR_ANYHOW_BUILD_DATE"),
+        }
+    };
+}
@@ -0,0 +1,116 @@
+//! The source of the current time for everything time dependent in reports
+use crate::config::installed_printer;
+use std::time::{Instant, SystemTime};
+#[cfg(feature = "test-util")]
+use std::{sync::Mutex, time::Duration};
+
+/// A source of the current time, installed with [`HookBuilder::clock`]
+///
+/// # Details
+///
+/// Every time read by the hooks goes through the installed clock, such as the
+/// span entry timestamps recorded by [`SpanTimestampLayer`], the process uptime
+/// and the render time budget. The default clock reads the system's clocks.
+///
+/// [`HookBuilder::clock`]: config/struct.HookBuilder.html#method.clock
+/// [`SpanTimestampLayer`]: struct.SpanTimestampLayer.html
+pub trait ReportClock: Send + Sync + 'static {
+    /// The current wall-clock time
+    fn now(&self) -> SystemTime;
+
+    /// The current monotonic time
+    fn instant(&self) -> Instant;
+}
+
+/// The clock used when no other clock was installed
+pub(crate) struct SystemClock;
+
+impl ReportClock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The current wall-clock time of the installed clock
+pub(crate) fn now() -> SystemTime {
+    installed_printer().clock().now()
+}
+
+/// The current monotonic time of the installed clock
+pub(crate) fn instant() -> Instant {
+    installed_printer().clock().instant()
+}
+
+/// A clock that only moves when advanced, for deterministic tests of time
+/// dependent reports
+///
+/// # Details
+///
+/// The wall-clock time starts at the given time, the monotonic time at the
+/// moment the clock was created, and both move forward together when the
+/// clock is advanced.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::HookBuilder, ManualClock};
+/// use std::sync::Arc;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let clock = Arc::new(ManualClock::new(UNIX_EPOCH));
+/// HookBuilder::default()
+///     .clock(clock.clone())
+///     .display_uptime_in_error_reports(true)
+///     .install()
+///     .unwrap();
+///
+/// assert_eq!(color_anyhow::uptime(), Some(Duration::ZERO));
+///
+/// clock.advance(Duration::from_secs(90));
+/// assert_eq!(color_anyhow::uptime(), Some(Duration::from_secs(90)));
+///
+/// let report = format!("{:?}", anyhow!("the job timed out"));
+/// assert!(report.contains("process uptime: 1m 30s"));
+/// ```
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+#[derive(Debug)]
+pub struct ManualClock {
+    start: (SystemTime, Instant),
+    elapsed: Mutex<Duration>,
+}
+
+#[cfg(feature = "test-util")]
+impl ManualClock {
+    /// Create a clock whose wall-clock time starts at `start`
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start: (start, Instant::now()),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward by `by`
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl ReportClock for ManualClock {
+    fn now(&self) -> SystemTime {
+        self.start.0 + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start.1 + self.elapsed()
+    }
+}
@@ -1,22 +1,294 @@
 //! Configuration options for customizing the behavior of the provided panic
 //! and error reporting hooks
+use crate::clock::{ReportClock, SystemClock};
+#[cfg(feature = "capture-spantrace")]
+use crate::locale::DateFormat;
+use crate::locale::NumberFormat;
+use crate::redact::UserPaths;
+use crate::section::help::{HelpInfo, Sections};
 use crate::Backtrace;
 use crate::ColorExt;
+use crate::HumanDuration;
 use ansi_term::Color::*;
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
-use std::{fmt, path::PathBuf, sync::Arc};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-#[derive(Debug)]
-struct InstallError;
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static INSTALL_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static DEFAULT_PRINTER: Lazy<PanicHook> = Lazy::new(default_printer);
+
+/// Returns true once the hooks have been installed by any `HookBuilder`
+pub(crate) fn is_installed() -> bool {
+    INSTALLED.load(Ordering::Acquire)
+}
+
+/// Error returned by [`HookBuilder::try_install`] when the hooks could not be installed
+///
+/// [`HookBuilder::try_install`]: struct.HookBuilder.html#method.try_install
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstallError {
+    /// color-anyhow's hooks, or just its panic hook after a conflict, were already installed,
+    /// possibly by another `HookBuilder`
+    AlreadyInstalled,
+    /// Another crate has already installed its own report handler
+    ForeignHandler,
+}
 
 impl fmt::Display for InstallError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("could not install the BacktracePrinter as another was already installed")
+        match self {
+            InstallError::AlreadyInstalled => {
+                f.write_str("could not install the color-anyhow hooks as they were already installed")
+            }
+            InstallError::ForeignHandler => f.write_str(
+                "could not install the color-anyhow hooks as another report handler was already installed",
+            ),
+        }
     }
 }
 
 impl std::error::Error for InstallError {}
 
+/// What [`HookBuilder::try_install`] installs when another crate has already installed its own
+/// report handler
+///
+/// In every case the other crate's report handler stays in place, as a report handler can only
+/// be installed once.
+///
+/// [`HookBuilder::try_install`]: struct.HookBuilder.html#method.try_install
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Install neither hook, so panics and errors are rendered consistently by the other crate's
+    /// handler and the default panic hook, and return `InstallError::ForeignHandler`
+    RollBack,
+    /// Install the panic hook and report success, for applications that only care about
+    /// consistently styled panics
+    PanicHookOnly,
+    /// Install the panic hook and return `InstallError::ForeignHandler`
+    Error,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Conflict {
+    fn default() -> Self {
+        Conflict::RollBack
+    }
+}
+
+/// Controls whether error and panic reports are rendered with colors
+///
+/// # Details
+///
+/// The variants mirror `termcolor::ColorChoice` so the value CLI frameworks
+/// already parse from a `--color` flag can be passed straight through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ColorChoice {
+    /// Use colors only if the output is a terminal and `NO_COLOR` is not set
+    ///
+    /// Error reports check `stderr`, where `fn main` prints returned errors, and
+    /// panic reports check `stdout`, where the panic hook prints them.
+    Auto,
+    /// Always use colors
+    Always,
+    /// Always use colors, emitted as ANSI escape codes
+    ///
+    /// color-anyhow only ever emits ANSI escape codes so this is equivalent to
+    /// `Always`, it exists for parity with `termcolor`.
+    AlwaysAnsi,
+    /// Never use colors
+    Never,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ColorChoice {
+    fn default() -> Self {
+        ColorChoice::Always
+    }
+}
+
+impl ColorChoice {
+    fn use_colors(self, stream: atty::Stream) -> bool {
+        match self {
+            ColorChoice::Auto => env::var_os("NO_COLOR").is_none() && atty::is(stream),
+            ColorChoice::Always | ColorChoice::AlwaysAnsi => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The markers in front of the messages of the chain of errors in error reports
+///
+/// Messages spanning multiple lines stay aligned with the first character of
+/// the message in every style.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error};
+/// use color_anyhow::config::{ChainStyle, ColorChoice, HookBuilder};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// HookBuilder::blank()
+///     .color_choice(ColorChoice::Never)
+///     .chain_style(ChainStyle::Numbered)
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("expected `=`"))
+///     .context("could not parse the config\nline 3: `port 8080`")
+///     .context("could not start the server")
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     format!("{:?}", report),
+///     "
+///    0: could not start the server
+///    1: could not parse the config
+///       line 3: `port 8080`
+///    2: expected `=`"
+/// );
+/// ```
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error};
+/// use color_anyhow::config::{ChainStyle, ColorChoice, HookBuilder};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// HookBuilder::blank()
+///     .color_choice(ColorChoice::Never)
+///     .chain_style(ChainStyle::Plain)
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("expected `=`"))
+///     .context("could not parse the config\nline 3: `port 8080`")
+///     .context("could not start the server")
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     format!("{:?}", report),
+///     "
+///     could not start the server
+///     could not parse the config
+///     line 3: `port 8080`
+///     expected `=`"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainStyle {
+    /// Number each message with its index in the chain, e.g. `   0: message`
+    Numbered,
+    /// Put the given bullet in front of each message, e.g. `   - message`
+    ///
+    /// The bullet is expected to be a single column wide.
+    Bulleted(char),
+    /// Indent the messages without any marker
+    Plain,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ChainStyle {
+    fn default() -> Self {
+        ChainStyle::Numbered
+    }
+}
+
+/// The line ending used between the lines of rendered reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix style `\n` line endings
+    Lf,
+    /// Windows style `\r\n` line endings
+    CrLf,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for LineEnding {
+    fn default() -> Self {
+        LineEnding::Lf
+    }
+}
+
+impl LineEnding {
+    /// Convert the `\n` line endings in `text` to this line ending
+    pub(crate) fn apply(self, text: String) -> String {
+        match self {
+            LineEnding::Lf => text,
+            LineEnding::CrLf => {
+                let mut out = String::with_capacity(text.len() + text.len() / 16);
+                let mut prev = None;
+                for c in text.chars() {
+                    if c == '\n' && prev != Some('\r') {
+                        out.push('\r');
+                    }
+                    out.push(c);
+                    prev = Some(c);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Controls how the `self` field of spans, recorded for the receiver of
+/// instrumented methods, is rendered in `SpanTrace`s
+#[cfg(feature = "capture-spantrace")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpanTraceSelfField {
+    /// Render the field as-is, e.g. `self="cat" "fake_file"`
+    Show,
+    /// Leave the field out
+    Hide,
+    /// Render the field under another name, e.g. `cmd="cat" "fake_file"`
+    Rename(String),
+    /// Cap the value of the field at this many characters, ending it with `…`
+    /// if anything was cut off
+    Truncate(usize),
+}
+
+#[cfg(feature = "capture-spantrace")]
+#[allow(clippy::derivable_impls)]
+impl Default for SpanTraceSelfField {
+    fn default() -> Self {
+        SpanTraceSelfField::Show
+    }
+}
+
+/// Controls how backtrace frames of closures are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosureDisplay {
+    /// Render closure frames as-is, e.g. `app::main::{{closure}}`
+    Raw,
+    /// Render closure frames as the function enclosing them, e.g. `app::main`
+    ///
+    /// Nested closures are simplified to the outermost enclosing function.
+    Simplified,
+    /// Hide closure frames, as if they were removed by a frame filter
+    Hidden,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ClosureDisplay {
+    fn default() -> Self {
+        ClosureDisplay::Raw
+    }
+}
+
 /// A representation of a Frame from a Backtrace or a SpanTrace
 #[derive(Debug)]
 #[non_exhaustive]
@@ -31,8 +303,161 @@ pub struct Frame {
     pub filename: Option<PathBuf>,
 }
 
+impl Frame {
+    /// Create a frame with the index `n` and no symbol name or source location, e.g. to
+    /// render frames that weren't captured by a `Backtrace`
+    ///
+    /// # Details
+    ///
+    /// Symbol names and paths don't have to be valid UTF-8: names decoded lossily render
+    /// with replacement characters, and the bytes of paths that aren't valid UTF-8 render
+    /// escaped as `\xNN`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::Frame;
+    ///
+    /// /// A xorshift generator, so that every run renders the same frames
+    /// fn next(state: &mut u64) -> u64 {
+    ///     *state ^= *state << 13;
+    ///     *state ^= *state >> 7;
+    ///     *state ^= *state << 17;
+    ///     *state
+    /// }
+    ///
+    /// // render the source snippets of the frames as well
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    ///
+    /// let mut state = 0x2545_f491_4f6c_dd1d;
+    /// for n in 0..2000 {
+    ///     let len = next(&mut state) as usize % 40;
+    ///     let mut bytes: Vec<u8> = (0..len).map(|_| next(&mut state) as u8).collect();
+    ///     // give some names a hash suffix, so multibyte characters straddle where it starts
+    ///     if n % 2 == 0 {
+    ///         bytes.extend_from_slice(b"::h0123456789abcdef");
+    ///     }
+    ///
+    ///     let mut frame = Frame::new(n);
+    ///     frame.name = Some(String::from_utf8_lossy(&bytes).into_owned());
+    ///     frame.lineno = Some(next(&mut state) as u32);
+    ///     #[cfg(unix)]
+    ///     {
+    ///         use std::os::unix::ffi::OsStrExt;
+    ///         let path = std::ffi::OsStr::from_bytes(&bytes);
+    ///         frame.filename = Some(path.into());
+    ///     }
+    ///
+    ///     let rendered = frame.to_string();
+    ///     assert!(rendered.contains(&format!("{:>2}: ", n)));
+    /// }
+    ///
+    /// #[cfg(unix)]
+    /// {
+    ///     use std::os::unix::ffi::OsStrExt;
+    ///
+    ///     let mut frame = Frame::new(7);
+    ///     frame.name = Some(String::from_utf8_lossy(b"app::caf\xe9::main").into_owned());
+    ///     frame.filename = Some(std::ffi::OsStr::from_bytes(b"src/caf\xe9.rs").into());
+    ///     frame.lineno = Some(12);
+    ///
+    ///     let rendered = frame.to_string();
+    ///     assert!(rendered.contains("app::caf\u{fffd}::main"));
+    ///     assert!(rendered.contains("src/caf\\xe9.rs"));
+    /// }
+    /// ```
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            name: None,
+            lineno: None,
+            filename: None,
+        }
+    }
+}
+
 impl fmt::Display for Frame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_deadline(f, None, &SourceFiles::default())
+    }
+}
+
+/// A `Frame` rendered as part of a render call with a time budget
+struct BudgetedFrame<'a> {
+    frame: &'a Frame,
+    deadline: Option<Instant>,
+    files: &'a SourceFiles,
+}
+
+impl fmt::Display for BudgetedFrame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.frame.fmt_with_deadline(f, self.deadline, self.files)
+    }
+}
+
+/// A source file as rendered in the backtrace frames pointing into it
+pub(crate) struct SourceFile {
+    /// The path, shortened to the configured `max_path_width`
+    display: String,
+    /// The name and version of the registry package the file belongs to
+    package: Option<(String, String)>,
+    /// The contents of the file, read the first time a snippet of it is shown
+    source: once_cell::unsync::OnceCell<Option<String>>,
+}
+
+impl SourceFile {
+    fn resolve(path: &Path) -> Self {
+        let display = crate::writers::display_path(path);
+        let display = match installed_printer().max_path_width {
+            Some(max) => crate::writers::ellipsize_path(&display, max).into_owned(),
+            None => display.into_owned(),
+        };
+
+        Self {
+            display,
+            package: registry_package(path),
+            source: Default::default(),
+        }
+    }
+
+    /// The contents of the file at `path`, or `None` if it can't be read
+    pub(crate) fn source(&self, path: &Path) -> Option<&str> {
+        self.source
+            // a file that can't be read, e.g. because its path isn't valid on this system,
+            // just isn't shown
+            .get_or_init(|| installed_printer().read_source(path).ok())
+            .as_deref()
+    }
+}
+
+/// The source files of a backtrace, resolved once each while rendering it
+///
+/// Deep backtraces mostly point into a handful of files, so this bounds the
+/// paths and sources held while rendering by the number of distinct files
+/// rather than the number of frames.
+#[derive(Default)]
+pub(crate) struct SourceFiles(RefCell<HashMap<PathBuf, Rc<SourceFile>>>);
+
+impl SourceFiles {
+    pub(crate) fn get(&self, path: &Path) -> Rc<SourceFile> {
+        if let Some(file) = self.0.borrow().get(path) {
+            return file.clone();
+        }
+
+        let file = Rc::new(SourceFile::resolve(path));
+        self.0.borrow_mut().insert(path.to_owned(), file.clone());
+        file
+    }
+}
+
+impl Frame {
+    /// Render the frame, skipping its source snippet once `deadline` has passed
+    fn fmt_with_deadline(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        deadline: Option<Instant>,
+        files: &SourceFiles,
+    ) -> fmt::Result {
         let is_dependency_code = self.is_dependency_code();
 
         // Print frame index.
@@ -42,8 +467,11 @@ impl fmt::Display for Frame {
         // (dodging a dep on the regex crate here)
         let name = self.name.as_deref().unwrap_or("<unknown>");
         let has_hash_suffix = name.len() > 19
-            && &name[name.len() - 19..name.len() - 16] == "::h"
-            && name[name.len() - 16..].chars().all(|x| x.is_digit(16));
+            && matches!(
+                name.get(name.len() - 19..),
+                Some(suffix) if suffix.starts_with("::h")
+                    && suffix[3..].chars().all(|x| x.is_digit(16))
+            );
 
         // Print function name.
 
@@ -55,26 +483,37 @@ impl fmt::Display for Frame {
             };
             write!(f, "{}", color.paint(&name[..name.len() - 19]))?;
             let color = Black.make_intense();
-            writeln!(f, "{}", color.paint(&name[name.len() - 19..]))?;
+            write!(f, "{}", color.paint(&name[name.len() - 19..]))?;
         } else {
             let color = if is_dependency_code {
                 Green
             } else {
                 Red.make_intense()
             };
-            writeln!(f, "{}", color.paint(name))?;
+            write!(f, "{}", color.paint(name))?;
+        }
+
+        if installed_printer().annotate_frame_crate_versions {
+            if let Some((krate, version)) = self.crate_version(files) {
+                let annotation = match version {
+                    Some(version) => format!("[{} v{}]", krate, version),
+                    None => format!("[{}]", krate),
+                };
+                write!(f, " {}", ansi_term::Style::new().dimmed().paint(annotation))?;
+            }
         }
+        writeln!(f)?;
 
         // Print source location, if known.
         if let Some(ref file) = self.filename {
-            let filestr = file.to_str().unwrap_or("<bad utf8>");
+            let file = files.get(file);
             let lineno = self
                 .lineno
                 .map_or("<unknown line>".to_owned(), |x| x.to_string());
             writeln!(
                 f,
                 "    at {}:{}",
-                Purple.paint(filestr),
+                Purple.paint(file.display.as_str()),
                 Purple.paint(lineno)
             )?;
         } else {
@@ -89,15 +528,172 @@ impl fmt::Display for Frame {
 
         // Maybe print source.
         if v >= Verbosity::Full {
-            self.print_source_if_avail(f)?;
+            if !past(deadline) {
+                self.print_source_if_avail(f, files)?;
+            } else if self.lineno.is_some() && self.filename.is_some() {
+                crate::stats::truncated();
+                let note = "    (skipped: render budget exceeded)";
+                writeln!(f, "{}", ansi_term::Style::new().dimmed().paint(note))?;
+            }
         }
 
         Ok(())
     }
 }
 
+/// The name and version of the cargo registry package containing `path`
+fn registry_package(path: &Path) -> Option<(String, String)> {
+    let mut components = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy());
+    while let Some(component) = components.next() {
+        if component != "registry" || components.next().as_deref() != Some("src") {
+            continue;
+        }
+
+        // skip the directory of the registry index
+        let package = components.nth(1)?;
+        let split = package.match_indices('-').map(|(i, _)| i).find(|&i| {
+            let version = &package[i + 1..];
+            version.starts_with(|c: char| c.is_ascii_digit()) && version.contains('.')
+        });
+        if let Some(split) = split {
+            let version = package[split + 1..].to_owned();
+            return Some((package[..split].to_owned(), version));
+        }
+    }
+
+    None
+}
+
+/// `name` without its trailing `::{closure#N}` segment, as closures are named
+/// in symbols mangled with the v0 scheme
+fn strip_closure_segment(name: &str) -> Option<&str> {
+    let start = name.rfind("::{closure#")?;
+    name[start..].ends_with('}').then(|| &name[..start])
+}
+
+/// Whether the render `deadline`, if any, has passed
+fn past(deadline: Option<Instant>) -> bool {
+    matches!(deadline, Some(deadline) if crate::clock::instant() >= deadline)
+}
+
 impl Frame {
-    fn is_dependency_code(&self) -> bool {
+    fn is_closure(&self) -> bool {
+        match self.name.as_deref() {
+            Some(name) => name.contains("{{closure}}") || name.contains("{closure#"),
+            None => false,
+        }
+    }
+
+    /// Remove the closure path segments from this frame's name, leaving the
+    /// name of the enclosing function
+    fn simplify_closure_name(&mut self) {
+        let name = match self.name.as_mut() {
+            Some(name) => name,
+            None => return,
+        };
+
+        *name = name.replace("::{{closure}}", "");
+        while let Some(start) = name.find("::{closure#") {
+            let end = name[start..]
+                .find('}')
+                .map_or(name.len(), |end| start + end + 1);
+            name.replace_range(start..end, "");
+        }
+    }
+
+    /// The name of the `async fn` whose state machine this frame polls, if the
+    /// frame's name or its source tell it apart from a closure
+    fn async_fn_name(&self, files: &SourceFiles) -> Option<String> {
+        let name = self.name.as_deref()?;
+        let (name, hash) = match name.len().checked_sub(19) {
+            Some(split) if name.get(split..).unwrap_or("").starts_with("::h") => {
+                name.split_at(split)
+            }
+            _ => (name, ""),
+        };
+
+        // before Rust 1.67 the state machines were polled through a `GenFuture` wrapper
+        for wrapper in &["core", "std"] {
+            let prefix = format!("<{}::future::from_generator::GenFuture<", wrapper);
+            let suffix = format!("> as {}::future::future::Future>::poll", wrapper);
+            if let Some(inner) = name
+                .strip_prefix(&prefix)
+                .and_then(|n| n.strip_suffix(&suffix))
+            {
+                let function = inner
+                    .strip_suffix("::{{closure}}")
+                    .or_else(|| strip_closure_segment(inner))?;
+                return Some(format!("{}{}", function, hash));
+            }
+        }
+
+        // otherwise async fns are named like closures, which only their source tells apart
+        let function = name
+            .strip_suffix("::{{closure}}")
+            .or_else(|| strip_closure_segment(name))?;
+        let ident = function.rsplit("::").next()?;
+        let (lineno, filename) = (self.lineno?, self.filename.as_ref()?);
+        let file = files.get(filename);
+        let source = file.source(filename)?;
+
+        let needle = format!("fn {}", ident);
+        // the closest definition of the function before the frame's line
+        let definition = source
+            .lines()
+            .take(lineno as usize)
+            .filter_map(|line| {
+                let start = line.find(&needle)?;
+                let rest = &line[start + needle.len()..];
+                rest.starts_with(|c: char| c == '(' || c == '<' || c.is_whitespace())
+                    .then(|| &line[..start])
+            })
+            .last()?;
+
+        if definition.trim_end().ends_with("async") {
+            Some(format!("{}{}", function, hash))
+        } else {
+            None
+        }
+    }
+
+    /// Replace the name of this frame with the name of its `async fn`, if it
+    /// polls the state machine of one
+    fn simplify_async_name(&mut self, files: &SourceFiles) {
+        if let Some(name) = self.async_fn_name(files) {
+            self.name = Some(name);
+        }
+    }
+
+    /// The crate the frame belongs to and its version, if they can be told
+    ///
+    /// The version is only known for crates built from the cargo registry, whose
+    /// sources are in directories named after the crate and its version. The
+    /// name of other crates is taken from the first segment of the frame's
+    /// symbol name.
+    fn crate_version(&self, files: &SourceFiles) -> Option<(String, Option<String>)> {
+        if let Some(filename) = &self.filename {
+            if let Some((krate, version)) = &files.get(filename).package {
+                return Some((krate.clone(), Some(version.clone())));
+            }
+        }
+
+        let mut name = self.name.as_deref()?;
+        for prefix in &["<", "&", "mut ", "dyn "] {
+            name = name.strip_prefix(prefix).unwrap_or(name);
+        }
+        let krate = name.split("::").next()?;
+        // symbols mangled with the v0 scheme carry a disambiguator, e.g. `std[5f0ab3c2]`
+        let krate = krate.split('[').next()?;
+        if krate.is_empty() || !krate.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+
+        Some((krate.to_owned(), None))
+    }
+
+    pub(crate) fn is_dependency_code(&self) -> bool {
         const SYM_PREFIXES: &[&str] = &[
             "std::",
             "core::",
@@ -194,36 +790,28 @@ impl Frame {
         false
     }
 
-    fn print_source_if_avail(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn print_source_if_avail(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        files: &SourceFiles,
+    ) -> fmt::Result {
         let (lineno, filename) = match (self.lineno, self.filename.as_ref()) {
             (Some(a), Some(b)) => (a, b),
             // Without a line number and file name, we can't sensibly proceed.
             _ => return Ok(()),
         };
 
-        let file = match std::fs::File::open(filename) {
-            Ok(file) => file,
-            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
-            e @ Err(_) => e.unwrap(),
+        let file = files.get(filename);
+        let source = match file.source(filename) {
+            Some(source) => source,
+            None => return Ok(()),
         };
 
-        use std::fmt::Write;
-        use std::io::BufRead;
-
-        // Extract relevant lines.
-        let reader = std::io::BufReader::new(file);
-        let start_line = lineno - 2.min(lineno - 1);
-        let surrounding_src = reader.lines().skip(start_line as usize - 1).take(5);
-        let mut buf = String::new();
-        for (line, cur_line_no) in surrounding_src.zip(start_line..) {
-            let line = line.unwrap();
-            if cur_line_no == lineno {
-                let color = White.bold();
-                write!(&mut buf, "{:>8} > {}", cur_line_no, line)?;
-                writeln!(f, "{}", color.paint(&buf))?;
-                buf.clear();
+        for (line, is_lineno) in source_snippet(source, lineno) {
+            if is_lineno {
+                writeln!(f, "{}", White.bold().paint(line))?;
             } else {
-                writeln!(f, "{:>8} │ {}", cur_line_no, line)?;
+                writeln!(f, "{}", line)?;
             }
         }
 
@@ -231,108 +819,3981 @@ impl Frame {
     }
 }
 
-/// Builder for customizing the behavior of the global panic and error report hooks
-pub struct HookBuilder {
-    filters: Vec<Box<FilterCallback>>,
-    capture_span_trace_by_default: bool,
+/// The numbered lines of `source` around `lineno`, each along with whether it is `lineno`
+pub(crate) fn source_snippet(
+    source: &str,
+    lineno: u32,
+) -> impl Iterator<Item = (String, bool)> + '_ {
+    // lines are numbered from 1, but nothing stops a location from claiming line 0
+    let start_line = lineno.saturating_sub(2).max(1);
+    source
+        .lines()
+        .skip(start_line as usize - 1)
+        .take(5)
+        .zip(start_line..)
+        .map(move |(line, cur_line_no)| {
+            if cur_line_no == lineno {
+                (format!("{:>8} > {}", cur_line_no, line), true)
+            } else {
+                (format!("{:>8} │ {}", cur_line_no, line), false)
+            }
+        })
 }
 
-impl HookBuilder {
-    /// Construct a HookBuilder
-    ///
-    /// # Details
-    ///
-    /// By default this function calls `add_default_filters()` and
-    /// `capture_span_trace_by_default(true)`. To get a `HookBuilder` with all
-    /// features disabled by default call `HookBuilder::blank()`.
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use color_anyhow::config::HookBuilder;
-    ///
-    /// HookBuilder::new()
-    ///     .install()
-    ///     .unwrap();
-    /// ```
-    pub fn new() -> Self {
-        Self::blank()
-            .add_default_filters()
-            .capture_span_trace_by_default(true)
+/// A snapshot of a backtrace frame along with whether the frame filters hid it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FrameInfo {
+    /// Frame index, as shown in the rendered backtrace
+    pub n: usize,
+    /// frame symbol name
+    pub name: Option<String>,
+    /// source line number
+    pub lineno: Option<u32>,
+    /// source file path
+    pub filename: Option<PathBuf>,
+    /// whether the frame is hidden by the installed frame filters
+    pub hidden: bool,
+}
+
+/// A set of the blocks an error report is made of, for rendering only some of
+/// them with [`ReportOptions::include`]
+///
+/// # Details
+///
+/// Sets are combined with `|`, e.g. `BlockSet::CHAIN | BlockSet::HELP`. The
+/// blocks are, in the order they are rendered in:
+///
+/// - `CHAIN`: the severity banner, the chain of errors and the lines that
+///   describe it, like where the context was attached and the process uptime
+/// - `SECTIONS`: the attached errors and reports, and the custom sections
+/// - `SPAN_TRACE`: the `SpanTrace`
+/// - `BACKTRACE`: the backtraces of the report and of its causes, and the
+///   hints for the environment variables that add missing traces
+/// - `HELP`: the breadcrumbs, the notes, warnings, suggestions and other help
+///   text, the status code and the fingerprint
+///
+/// [`ReportOptions::include`]: struct.ReportOptions.html#method.include
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockSet(u8);
+
+impl BlockSet {
+    /// The chain of errors
+    pub const CHAIN: Self = Self(1);
+    /// The attached errors, reports and custom sections
+    pub const SECTIONS: Self = Self(1 << 1);
+    /// The `SpanTrace`
+    pub const SPAN_TRACE: Self = Self(1 << 2);
+    /// The backtraces
+    pub const BACKTRACE: Self = Self(1 << 3);
+    /// The help text
+    pub const HELP: Self = Self(1 << 4);
+
+    /// The set without any blocks
+    pub const fn empty() -> Self {
+        Self(0)
     }
 
-    /// Construct a HookBuilder with minimal features enabled
-    pub fn blank() -> Self {
-        HookBuilder {
-            filters: vec![],
-            capture_span_trace_by_default: false,
-        }
+    /// The set of all the blocks
+    pub const fn all() -> Self {
+        Self(0b1_1111)
     }
 
-    /// Configures the default capture mode for `SpanTraces` in error reports and panics
-    pub fn capture_span_trace_by_default(mut self, cond: bool) -> Self {
-        self.capture_span_trace_by_default = cond;
+    /// Returns whether all the blocks of `other` are in this set
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for BlockSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOrAssign for BlockSet {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// Options for rendering a single error report with [`format_report`]
+///
+/// [`format_report`]: ../fn.format_report.html
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    expand_hidden_frames: bool,
+    show_filter_provenance: Option<bool>,
+    show_all_backtraces: bool,
+    mark_since: Option<u64>,
+    width: Option<usize>,
+    include: Option<BlockSet>,
+    prefer_localized: bool,
+}
+
+impl ReportOptions {
+    /// Construct a `ReportOptions` that renders reports the same way as their `Debug` output
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print every backtrace frame, dimming the frames hidden by the frame
+    /// filters instead of collapsing them into "N frames hidden" markers
+    pub fn expand_hidden_frames(mut self, cond: bool) -> Self {
+        self.expand_hidden_frames = cond;
         self
     }
 
-    /// Add a custom filter to the set of frame filters
+    pub(crate) fn expands_hidden_frames(&self) -> bool {
+        self.expand_hidden_frames
+    }
+
+    /// Name the frame filters that hid the frames of each "N frames hidden" marker, defaults
+    /// to `HookBuilder::show_filter_provenance`
     ///
     /// # Examples
     ///
     /// ```rust
-    /// color_anyhow::config::HookBuilder::default()
-    ///     .add_frame_filter(Box::new(|frames| {
-    ///         let filters = &[
-    ///             "uninteresting_function",
-    ///         ];
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{HookBuilder, ReportOptions}};
     ///
-    ///         frames.retain(|frame| {
-    ///             !filters.iter().any(|f| {
-    ///                 let name = if let Some(name) = frame.name.as_ref() {
-    ///                     name.as_str()
-    ///                 } else {
-    ///                     return true;
-    ///                 };
+    /// #[inline(never)]
+    /// fn warm_cache() -> Error {
+    ///     anyhow!("the cache could not be warmed")
+    /// }
     ///
-    ///                 name.starts_with(f)
-    ///             })
-    ///         });
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .add_named_frame_filter("cache", Box::new(|frames| {
+    ///         frames.retain(|frame| !matches!(&frame.name, Some(name) if name.contains("warm_cache")))
     ///     }))
     ///     .install()
     ///     .unwrap();
+    ///
+    /// let report = warm_cache();
+    /// let plain = color_anyhow::format_report(&report, &ReportOptions::new());
+    /// assert!(plain.contains("⋮ 1 frame hidden ⋮"));
+    ///
+    /// let options = ReportOptions::new().show_filter_provenance(true);
+    /// let annotated = color_anyhow::format_report(&report, &options);
+    /// assert!(annotated.contains("⋮ 1 frame hidden by cache ⋮"));
     /// ```
-    pub fn add_frame_filter(mut self, filter: Box<FilterCallback>) -> Self {
-        self.filters.push(filter);
+    pub fn show_filter_provenance(mut self, cond: bool) -> Self {
+        self.show_filter_provenance = Some(cond);
         self
     }
 
-    /// Install the given Hook as the global error report hook
-    pub fn install(self) -> Result<(), crate::anyhow::Error> {
-        let (panic_hook, report_hook) = self.into_hooks();
-        crate::anyhow::set_hook(Box::new(move |e| Box::new(report_hook.default(e))))?;
-        install_panic_hook();
-
-        if crate::CONFIG.set(panic_hook).is_err() {
-            Err(InstallError)?
-        }
-
-        Ok(())
-    }
-
-    /// Add the default set of filters to this `HookBuilder`'s configuration
-    pub fn add_default_filters(self) -> Self {
-        self.add_frame_filter(Box::new(default_frame_filter))
-            .add_frame_filter(Box::new(anyhow_frame_filters))
+    pub(crate) fn filter_provenance(&self) -> Option<bool> {
+        self.show_filter_provenance
+    }
+
+    /// Render the backtraces provided by errors in the chain after the report's own backtrace
+    ///
+    /// # Details
+    ///
+    /// Errors in the chain that provide their own backtrace, through
+    /// `std::error::Error::backtrace` on toolchains that support it, are always
+    /// tagged with a dimmed `(has backtrace)`. With this option each distinct
+    /// provided backtrace is also rendered under a `BACKTRACE (cause #N)` banner,
+    /// where `N` is the index of the error in the chain. Backtraces identical to
+    /// the report's own or to an earlier cause's are only rendered once. On
+    /// toolchains without backtrace support in `std::error::Error` no error
+    /// provides a backtrace and only the report's own is rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, config::ReportOptions};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("connection reset"))
+    ///     .context("could not sync")
+    ///     .unwrap_err();
+    /// let rendered = color_anyhow::format_report(
+    ///     &report,
+    ///     &ReportOptions::new().show_all_backtraces(true),
+    /// );
+    ///
+    /// assert_eq!(rendered.matches("BACKTRACE").count(), 1);
+    /// ```
+    pub fn show_all_backtraces(mut self, cond: bool) -> Self {
+        self.show_all_backtraces = cond;
+        self
+    }
+
+    pub(crate) fn shows_all_backtraces(&self) -> bool {
+        self.show_all_backtraces
+    }
+
+    /// Mark the sections attached after `revision` with a dimmed `[new]` badge
+    ///
+    /// # Details
+    ///
+    /// The badge is added to the first line of each section attached since the report had
+    /// the given [`Handler::revision`], so reports printed repeatedly while sections are
+    /// added to them highlight what changed since they were last printed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder, ReportOptions}, Handler, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), _>(anyhow!("the upload failed"))
+    ///     .note("attempt 1 timed out")
+    ///     .note("attempt 2 timed out")
+    ///     .unwrap_err();
+    /// let revision = report.handler().downcast_ref::<Handler>().unwrap().revision();
+    /// let first = color_anyhow::format_report(&report, &ReportOptions::new());
+    /// assert!(!first.contains("[new]"));
+    ///
+    /// let report = Err::<(), _>(report).note("attempt 3 was refused").unwrap_err();
+    /// let second = color_anyhow::format_report(&report, &ReportOptions::new().mark_since(revision));
+    ///
+    /// assert_eq!(second.matches("[new]").count(), 1);
+    /// assert!(second.contains("Note: attempt 3 was refused [new]"));
+    /// assert!(second.contains("Note: attempt 2 timed out\n"));
+    /// ```
+    ///
+    /// [`Handler::revision`]: ../struct.Handler.html#method.revision
+    pub fn mark_since(mut self, revision: u64) -> Self {
+        self.mark_since = Some(revision);
+        self
+    }
+
+    /// Whether the section attached at `revision` is marked as new
+    pub(crate) fn is_new(&self, revision: u64) -> bool {
+        matches!(self.mark_since, Some(since) if revision >= since)
+    }
+
+    /// The width sections are wrapped to, taken from the report's formatter
+    pub(crate) fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub(crate) fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Render only the given blocks of the report, defaults to all of them
+    ///
+    /// # Details
+    ///
+    /// Each block is rendered the same way as in the full report, but without
+    /// the line breaks that separate it from the other blocks, so a report can
+    /// be shown block by block, e.g. in separate panes. Blocks the report
+    /// doesn't have are left out, so rendering only them yields an empty string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{BlockSet, ColorChoice, HookBuilder, ReportOptions};
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, Section, SectionExt};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// std::env::remove_var("RUST_SPANTRACE");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("connection reset"))
+    ///     .context("could not sync")
+    ///     .section("retried 3 times".header("Retries:"))
+    ///     .note("the server may be down")
+    ///     .unwrap_err();
+    ///
+    /// let render = |blocks| {
+    ///     color_anyhow::format_report(&report, &ReportOptions::new().include(blocks))
+    /// };
+    /// let chain = render(BlockSet::CHAIN);
+    /// let sections = render(BlockSet::SECTIONS);
+    /// let backtrace = render(BlockSet::BACKTRACE);
+    /// let help = render(BlockSet::HELP);
+    ///
+    /// assert_eq!(chain, "   0: could not sync\n   1: connection reset");
+    /// assert_eq!(sections, "Retries:\n   retried 3 times");
+    /// assert!(backtrace.trim_start().starts_with("━━━━"));
+    /// assert_eq!(help, "Note: the server may be down");
+    ///
+    /// // there is no SpanTrace without a tracing subscriber
+    /// assert_eq!(render(BlockSet::SPAN_TRACE), "");
+    ///
+    /// // the blocks compose into the full report
+    /// let full = color_anyhow::format_report(&report, &ReportOptions::new());
+    /// assert_eq!(full, format!("\n{}\n\n{}\n\n{}\n\n{}", chain, sections, backtrace, help));
+    /// assert_eq!(render(BlockSet::CHAIN | BlockSet::HELP), format!("{}\n\n{}", chain, help));
+    /// ```
+    pub fn include(mut self, blocks: BlockSet) -> Self {
+        self.include = Some(blocks);
+        self
+    }
+
+    /// Whether `block` is rendered
+    pub(crate) fn includes(&self, block: BlockSet) -> bool {
+        self.include.map_or(true, |blocks| blocks.contains(block))
+    }
+
+    /// Whether only some of the blocks are rendered
+    pub(crate) fn is_partial(&self) -> bool {
+        self.include.is_some()
+    }
+
+    /// Render the localized message attached with [`Section::localized`] in place of the
+    /// outermost error's message, for showing the report to end users
+    ///
+    /// # Details
+    ///
+    /// Only the outermost message is replaced, the causes are rendered unchanged. Reports
+    /// without a localized message are rendered with the original one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, config::{ColorChoice, HookBuilder, ReportOptions}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), _>(anyhow!("connection refused"))
+    ///     .context("the upload failed")
+    ///     .localized("le téléversement a échoué")
+    ///     .unwrap_err();
+    ///
+    /// let log = color_anyhow::format_report(&report, &ReportOptions::new());
+    /// let user = color_anyhow::format_report(&report, &ReportOptions::new().prefer_localized(true));
+    ///
+    /// assert_eq!(log, format!("{:?}", report));
+    /// assert!(log.contains("0: the upload failed"));
+    /// assert!(user.contains("0: le téléversement a échoué"));
+    /// assert_eq!(
+    ///     user.replace("le téléversement a échoué", "the upload failed"),
+    ///     log
+    /// );
+    ///
+    /// // without a localized message the original is kept
+    /// let report = anyhow!("connection refused");
+    /// let user = color_anyhow::format_report(&report, &ReportOptions::new().prefer_localized(true));
+    /// assert!(user.contains("0: connection refused"));
+    /// ```
+    ///
+    /// [`Section::localized`]: ../trait.Section.html#tymethod.localized
+    pub fn prefer_localized(mut self, cond: bool) -> Self {
+        self.prefer_localized = cond;
+        self
+    }
+
+    pub(crate) fn prefers_localized(&self) -> bool {
+        self.prefer_localized
+    }
+}
+
+/// Builder for customizing the behavior of the global panic and error report hooks
+///
+/// # Details
+///
+/// `HookBuilder` can be cloned cheaply, frame filters are shared between the
+/// clones, so a single base configuration can be tweaked for each entry point
+/// before it is installed.
+#[derive(Clone)]
+pub struct HookBuilder {
+    filters: Vec<FrameFilter>,
+    collapse_hidden_frames: bool,
+    show_filter_provenance: bool,
+    capture_span_trace_by_default: bool,
+    redact_user_paths: bool,
+    color_choice: ColorChoice,
+    on_conflict: Conflict,
+    group_errors: bool,
+    share_identical_backtraces: bool,
+    capture_error_section_backtraces: bool,
+    attach_collected_warnings: bool,
+    closure_display: ClosureDisplay,
+    simplify_async_frames: bool,
+    line_ending: LineEnding,
+    display_fingerprint: bool,
+    display_status_hints: bool,
+    display_help_codes: bool,
+    display_env_hints: bool,
+    display_uptime: bool,
+    display_uptime_in_error_reports: bool,
+    build_info: Option<crate::BuildInfo>,
+    display_build_info_in_error_reports: bool,
+    theme: Option<crate::Theme>,
+    table_of_contents: bool,
+    reverse_layout: bool,
+    display_report_id: bool,
+    panic_artifact: Option<crate::ArtifactSpec>,
+    backtrace_formatter: Option<Arc<BacktraceFormatterCallback>>,
+    panic_backtrace_formatter: Option<Arc<BacktraceFormatterCallback>>,
+    backtrace_suppressed_types: Vec<ErrorType>,
+    skip_backtrace_in_reports: bool,
+    chain_style: ChainStyle,
+    chain_label: Option<Option<String>>,
+    report_header: Option<String>,
+    support_footer: Option<String>,
+    severity_banner: bool,
+    max_path_width: Option<usize>,
+    width: Option<usize>,
+    max_error_sections: Option<usize>,
+    dedup_error_sections_against_chain: bool,
+    auto_mark_reported: bool,
+    annotate_frame_crate_versions: bool,
+    stripped_symbols_threshold: f64,
+    stripped_backtrace_note: String,
+    conditional_sections: Vec<ConditionalSection>,
+    runbooks: Vec<(String, String)>,
+    render_time_budget: Option<Duration>,
+    source_reader: Option<Arc<SourceReaderCallback>>,
+    report_observer: Option<Arc<ReportObserverCallback>>,
+    output_sinks: Vec<Arc<OutputSinkCallback>>,
+    clock: Option<Arc<dyn ReportClock>>,
+    number_format: Option<NumberFormat>,
+    #[cfg(feature = "capture-spantrace")]
+    spantrace_timestamps: bool,
+    #[cfg(feature = "capture-spantrace")]
+    date_format: Option<DateFormat>,
+    #[cfg(feature = "capture-spantrace")]
+    spantrace_self_field: SpanTraceSelfField,
+    #[cfg(feature = "capture-spantrace")]
+    collapse_same_target_spans: bool,
+    #[cfg(feature = "capture-spantrace")]
+    correlate_traces: bool,
+    #[cfg(feature = "highlight-patterns")]
+    highlight_patterns: Vec<(regex_lite::Regex, String)>,
+}
+
+/// A section added to the reports of the errors matched by its trigger
+#[derive(Clone)]
+struct ConditionalSection {
+    trigger: Arc<SectionTriggerCallback>,
+    provider: Arc<SectionProviderCallback>,
+}
+
+/// An error type registered with the `HookBuilder`
+#[derive(Clone, Copy)]
+struct ErrorType {
+    id: TypeId,
+    name: &'static str,
+    is: fn(&(dyn std::error::Error + 'static)) -> bool,
+}
+
+impl ErrorType {
+    fn of<E: std::error::Error + 'static>() -> Self {
+        ErrorType {
+            id: TypeId::of::<E>(),
+            name: std::any::type_name::<E>(),
+            is: |error| error.is::<E>(),
+        }
+    }
+}
+
+impl fmt::Debug for ErrorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+impl HookBuilder {
+    /// Construct a HookBuilder
+    ///
+    /// # Details
+    ///
+    /// By default this function calls `add_default_filters()` and
+    /// `capture_span_trace_by_default(true)`. To get a `HookBuilder` with all
+    /// features disabled by default call `HookBuilder::blank()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use color_anyhow::config::HookBuilder;
+    ///
+    /// HookBuilder::new()
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn new() -> Self {
+        Self::blank()
+            .add_default_filters()
+            .capture_span_trace_by_default(true)
+    }
+
+    /// Construct a HookBuilder with minimal features enabled
+    pub fn blank() -> Self {
+        HookBuilder {
+            filters: vec![],
+            collapse_hidden_frames: true,
+            show_filter_provenance: false,
+            capture_span_trace_by_default: false,
+            redact_user_paths: false,
+            color_choice: ColorChoice::Always,
+            on_conflict: Conflict::RollBack,
+            group_errors: false,
+            share_identical_backtraces: false,
+            capture_error_section_backtraces: false,
+            attach_collected_warnings: false,
+            closure_display: ClosureDisplay::Raw,
+            simplify_async_frames: false,
+            line_ending: LineEnding::Lf,
+            display_fingerprint: false,
+            display_status_hints: false,
+            display_help_codes: true,
+            display_env_hints: false,
+            display_uptime: true,
+            display_uptime_in_error_reports: false,
+            build_info: None,
+            display_build_info_in_error_reports: false,
+            theme: None,
+            table_of_contents: false,
+            reverse_layout: false,
+            display_report_id: false,
+            panic_artifact: None,
+            backtrace_formatter: None,
+            panic_backtrace_formatter: None,
+            backtrace_suppressed_types: vec![],
+            skip_backtrace_in_reports: false,
+            chain_style: ChainStyle::Numbered,
+            chain_label: None,
+            report_header: None,
+            support_footer: None,
+            severity_banner: false,
+            max_path_width: None,
+            width: None,
+            max_error_sections: None,
+            dedup_error_sections_against_chain: false,
+            auto_mark_reported: false,
+            annotate_frame_crate_versions: false,
+            stripped_symbols_threshold: 0.8,
+            stripped_backtrace_note: crate::stripped::DEFAULT_NOTE.to_owned(),
+            conditional_sections: vec![],
+            runbooks: vec![],
+            render_time_budget: None,
+            source_reader: None,
+            report_observer: None,
+            output_sinks: vec![],
+            clock: None,
+            number_format: None,
+            #[cfg(feature = "capture-spantrace")]
+            spantrace_timestamps: false,
+            #[cfg(feature = "capture-spantrace")]
+            date_format: None,
+            #[cfg(feature = "capture-spantrace")]
+            spantrace_self_field: SpanTraceSelfField::Show,
+            #[cfg(feature = "capture-spantrace")]
+            collapse_same_target_spans: false,
+            #[cfg(feature = "capture-spantrace")]
+            correlate_traces: false,
+            #[cfg(feature = "highlight-patterns")]
+            highlight_patterns: vec![],
+        }
+    }
+
+    /// Configures the default capture mode for `SpanTraces` in error reports and panics
+    pub fn capture_span_trace_by_default(mut self, cond: bool) -> Self {
+        self.capture_span_trace_by_default = cond;
+        self
+    }
+
+    /// Redact the current user's home directory and username from rendered reports
+    ///
+    /// # Details
+    ///
+    /// When enabled the home directories set in `HOME` and `USERPROFILE`, and
+    /// the directories they resolve to if they're symlinks, are replaced with
+    /// `~` wherever they appear in error reports and panic reports, including
+    /// the paths of trace frames, source snippets, error messages and section
+    /// bodies. Any other path component equal to the username, such as in
+    /// `C:\Users\alice\` paths from another machine, is replaced with `<user>`.
+    /// Only whole path components are replaced, words that merely contain the
+    /// username are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    ///
+    /// std::env::set_var("HOME", "/home/alice");
+    /// std::env::set_var("USER", "alice");
+    ///
+    /// color_anyhow::config::HookBuilder::default()
+    ///     .redact_user_paths(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("could not open /home/alice/.config/app.toml"))
+    ///     .section("last written by /srv/alice/sync and malice.rs")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains("could not open ~/.config/app.toml"));
+    /// assert!(report.contains("last written by /srv/<user>/sync and malice.rs"));
+    /// ```
+    ///
+    /// Windows style home directories are redacted the same way:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error};
+    ///
+    /// std::env::set_var("HOME", r"C:\Users\alice");
+    /// std::env::set_var("USER", "alice");
+    ///
+    /// color_anyhow::config::HookBuilder::default()
+    ///     .redact_user_paths(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!(r"could not open C:\Users\alice\app.toml or D:\alice\app.toml");
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains(r"could not open ~\app.toml or D:\<user>\app.toml"));
+    /// ```
+    ///
+    /// Homes in the verbatim form Windows resolves paths to are also redacted without it:
+    ///
+    /// ```rust
+    /// use color_anyhow::anyhow::anyhow;
+    ///
+    /// std::env::set_var("HOME", r"\\?\C:\Users\alice");
+    /// std::env::set_var("USER", "alice");
+    ///
+    /// color_anyhow::config::HookBuilder::default()
+    ///     .redact_user_paths(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!(r"could not open C:\Users\alice\app.toml");
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains(r"could not open ~\app.toml"));
+    /// ```
+    ///
+    /// Every home directory is redacted, e.g. a symlinked home and its target:
+    ///
+    /// ```rust
+    /// use color_anyhow::anyhow::anyhow;
+    ///
+    /// let root = std::env::temp_dir().join(format!("color-anyhow-{}", std::process::id()));
+    /// std::fs::create_dir_all(root.join("var/home/alice")).unwrap();
+    /// # #[cfg(unix)]
+    /// std::os::unix::fs::symlink(root.join("var/home"), root.join("home")).unwrap();
+    /// let home = root.join("home/alice");
+    /// let resolved = std::fs::canonicalize(&home).unwrap();
+    ///
+    /// std::env::set_var("HOME", &home);
+    /// std::env::set_var("USERPROFILE", "/mnt/users/alice");
+    /// std::env::set_var("USER", "alice");
+    ///
+    /// color_anyhow::config::HookBuilder::default()
+    ///     .redact_user_paths(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!(
+    ///     "{}/a.rs, {}/b.rs and /mnt/users/alice/c.rs differ",
+    ///     home.display(),
+    ///     resolved.display()
+    /// );
+    /// let report = format!("{:?}", report);
+    /// std::fs::remove_dir_all(&root).unwrap();
+    ///
+    /// assert!(report.contains("~/a.rs, ~/b.rs and ~/c.rs differ"));
+    /// ```
+    pub fn redact_user_paths(mut self, cond: bool) -> Self {
+        self.redact_user_paths = cond;
+        self
+    }
+
+    /// Replace the current user's home directory with `~` and their username with `<user>`
+    /// in rendered reports, the same as [`redact_user_paths`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// std::env::set_var("HOME", "/home/alice");
+    /// std::env::set_var("USER", "alice");
+    ///
+    /// let builder = HookBuilder::default().mask_home_paths(true);
+    /// assert!(builder.redact_user_paths_enabled());
+    /// builder.install().unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("could not open /home/alice/app.toml"));
+    /// assert!(report.contains("could not open ~/app.toml"));
+    /// ```
+    ///
+    /// [`redact_user_paths`]: #method.redact_user_paths
+    pub fn mask_home_paths(self, cond: bool) -> Self {
+        self.redact_user_paths(cond)
+    }
+
+    /// Configures whether reports are rendered with colors, defaults to `ColorChoice::Always`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("an error occurred"));
+    /// assert!(!report.contains('\x1b'));
+    /// ```
+    ///
+    /// `Always` and `AlwaysAnsi` emit escape codes even when not writing to a terminal:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::AlwaysAnsi)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("an error occurred"));
+    /// assert!(report.contains("\x1b["));
+    /// ```
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("an error occurred"));
+    /// assert!(report.contains("\x1b["));
+    /// ```
+    ///
+    /// `Auto` honors `NO_COLOR`:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("NO_COLOR", "1");
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Auto)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("an error occurred"));
+    /// assert!(!report.contains('\x1b'));
+    /// ```
+    pub fn color_choice(mut self, choice: ColorChoice) -> Self {
+        self.color_choice = choice;
+        self
+    }
+
+    /// Configures whether identical error sections are grouped into a single
+    /// block, defaults to `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    /// use thiserror::Error;
+    ///
+    /// #[derive(Debug, Error)]
+    /// #[error("{0}")]
+    /// struct StrError(&'static str);
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .group_errors(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("batch failed"))
+    ///     .error_labeled("a.txt", StrError("permission denied"))
+    ///     .error_labeled("b.txt", StrError("permission denied"))
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert_eq!(report.matches("permission denied").count(), 1);
+    /// assert!(report.contains("Error: ×2\n   0: permission denied\n   affected: a.txt, b.txt"));
+    /// ```
+    pub fn group_errors(mut self, cond: bool) -> Self {
+        self.group_errors = cond;
+        self
+    }
+
+    /// Render identical backtraces of reports attached with [`Section::error_report`]
+    /// once, in a shared section referenced by each of the errors
+    ///
+    /// # Details
+    ///
+    /// Backtraces are only shared when every frame matches, backtraces that
+    /// differ in any frame are still rendered separately.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .share_identical_backtraces(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let errors: Vec<_> = ["a.txt", "b.txt"]
+    ///     .iter()
+    ///     .map(|file| anyhow!("could not read {}", file))
+    ///     .collect();
+    ///
+    /// #[inline(never)]
+    /// fn read_elsewhere() -> Error {
+    ///     anyhow!("could not read c.txt")
+    /// }
+    ///
+    /// let mut report = Err::<(), Error>(anyhow!("batch failed"));
+    /// for error in errors {
+    ///     report = report.error_report(error);
+    /// }
+    /// let report = format!("{:?}", report.unwrap_err());
+    ///
+    /// // the main report's backtrace and the one shared by both errors
+    /// assert_eq!(report.matches("BACKTRACE").count(), 2);
+    /// assert_eq!(report.matches("backtrace: shared backtrace #1").count(), 2);
+    ///
+    /// let report = Err::<(), Error>(anyhow!("batch failed"))
+    ///     .error_report(anyhow!("could not read a.txt"))
+    ///     .error_report(read_elsewhere())
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// // backtraces captured at different call sites are not merged
+    /// assert_eq!(report.matches("BACKTRACE").count(), 3);
+    /// assert!(report.contains("backtrace: shared backtrace #2"));
+    /// ```
+    ///
+    /// [`Section::error_report`]: ../trait.Section.html#tymethod.error_report
+    pub fn share_identical_backtraces(mut self, cond: bool) -> Self {
+        self.share_identical_backtraces = cond;
+        self
+    }
+
+    /// Capture a backtrace for each error section attached with [`Section::error`],
+    /// [`Section::error_labeled`] or [`Section::with_error`], defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// The backtrace is captured where the section is attached and rendered below the error
+    /// section, the same way as the backtraces of reports attached with
+    /// `Section::error_report`, so errors of independent sub-operations each show their own
+    /// origin. Backtraces are only captured when backtraces are enabled with
+    /// `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE`, and when the sections are attached, so
+    /// errors that are never attached to a report cost nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Result, config::HookBuilder, Section};
+    /// use std::io;
+    ///
+    /// #[inline(never)]
+    /// fn check_disk(report: Result<()>) -> Result<()> {
+    ///     report.error(io::Error::new(io::ErrorKind::Other, "the disk is full"))
+    /// }
+    ///
+    /// #[inline(never)]
+    /// fn check_network(report: Result<()>) -> Result<()> {
+    ///     report.error(io::Error::new(io::ErrorKind::Other, "the network is down"))
+    /// }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .capture_error_section_backtraces(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = check_network(check_disk(Err(anyhow!("the health check failed"))));
+    /// let report = format!("{:?}", report.unwrap_err());
+    ///
+    /// let disk = report.find("the disk is full").unwrap();
+    /// let network = report.find("the network is down").unwrap();
+    /// let disk_section = &report[disk..network];
+    /// let network_section = &report[network..];
+    ///
+    /// assert!(disk_section.contains("check_disk"));
+    /// assert!(!disk_section.contains("check_network"));
+    /// assert!(network_section.contains("check_network"));
+    /// ```
+    ///
+    /// [`Section::error`]: ../trait.Section.html#tymethod.error
+    /// [`Section::error_labeled`]: ../trait.Section.html#tymethod.error_labeled
+    /// [`Section::with_error`]: ../trait.Section.html#tymethod.with_error
+    pub fn capture_error_section_backtraces(mut self, cond: bool) -> Self {
+        self.capture_error_section_backtraces = cond;
+        self
+    }
+
+    /// Drain the warnings pending in [`WarningsCollector::global`] into a
+    /// `Collected warnings:` section of each error report as it is constructed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, WarningsCollector};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .attach_collected_warnings(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let collector = WarningsCollector::global();
+    /// collector.warn("skipped a.txt: not valid utf-8");
+    /// collector.warn("--fast is deprecated, use --release");
+    ///
+    /// let report = format!("{:?}", anyhow!("could not build the index"));
+    /// assert!(report.contains(
+    ///     "Collected warnings:\n   Warning: skipped a.txt: not valid utf-8\n   Warning: --fast is deprecated"
+    /// ));
+    ///
+    /// // the warnings were drained into the first report
+    /// assert!(collector.is_empty());
+    /// assert!(!format!("{:?}", anyhow!("another error")).contains("Collected warnings"));
+    /// ```
+    ///
+    /// [`WarningsCollector::global`]: ../struct.WarningsCollector.html#method.global
+    pub fn attach_collected_warnings(mut self, cond: bool) -> Self {
+        self.attach_collected_warnings = cond;
+        self
+    }
+
+    /// Configures how backtrace frames of closures are rendered, defaults to `ClosureDisplay::Raw`
+    ///
+    /// # Details
+    ///
+    /// Frame filters always see the raw frame names, regardless of this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::{anyhow, Error}, config::{ClosureDisplay, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .closure_display(ClosureDisplay::Simplified)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// fn load_config() -> Error {
+    ///     let nested = || (|| anyhow!("config could not be loaded"))();
+    ///     nested()
+    /// }
+    ///
+    /// let report = format!("{:?}", load_config());
+    /// let frames: Vec<_> = report.lines().filter(|line| line.contains("load_config")).collect();
+    ///
+    /// // both closure frames and the function itself are rendered as `load_config`
+    /// assert_eq!(frames.len(), 3);
+    /// assert!(frames.iter().all(|frame| !frame.contains("closure")));
+    /// ```
+    ///
+    /// Hiding closure frames:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::{anyhow, Error}, config::{ClosureDisplay, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .closure_display(ClosureDisplay::Hidden)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// fn load_config() -> Error {
+    ///     let nested = || (|| anyhow!("config could not be loaded"))();
+    ///     nested()
+    /// }
+    ///
+    /// let report = format!("{:?}", load_config());
+    /// let frames: Vec<_> = report.lines().filter(|line| line.contains("load_config")).collect();
+    ///
+    /// assert_eq!(frames.len(), 1);
+    /// assert!(!report.contains("{{closure}}"));
+    /// ```
+    pub fn closure_display(mut self, display: ClosureDisplay) -> Self {
+        self.closure_display = display;
+        self
+    }
+
+    /// Render backtrace frames of `async fn` state machines as the name of the `async fn`,
+    /// defaults to false
+    ///
+    /// # Details
+    ///
+    /// The compiler names the state machine of an `async fn` like a closure in it, e.g.
+    /// `app::fetch::{{closure}}`, and older compilers poll it through a `GenFuture<T>` wrapper
+    /// frame. This maps both back to the `async fn`, e.g. `app::fetch`. Telling an `async fn`
+    /// apart from a closure needs the frame's source file and line, so frames without
+    /// debuginfo or whose source can't be read keep their raw name, as do `async` blocks.
+    ///
+    /// With `ClosureDisplay::Hidden` the frames of `async fn`s are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::{anyhow, Error}, config::HookBuilder};
+    /// # use std::future::Future;
+    /// # use std::sync::Arc;
+    /// # use std::task::{Context, Poll, Wake, Waker};
+    /// #
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     struct Noop;
+    /// #     impl Wake for Noop {
+    /// #         fn wake(self: Arc<Self>) {}
+    /// #     }
+    /// #     let waker = Waker::from(Arc::new(Noop));
+    /// #     let mut future = Box::pin(future);
+    /// #     loop {
+    /// #         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .simplify_async_frames(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// async fn load_config() -> Error {
+    ///     anyhow!("config could not be loaded")
+    /// }
+    ///
+    /// let report = format!("{:?}", block_on(load_config()));
+    /// let lines: Vec<_> = report.lines().collect();
+    /// let frames: Vec<_> = lines
+    ///     .windows(2)
+    ///     .filter(|frame| frame[0].contains("load_config"))
+    ///     .collect();
+    ///
+    /// assert!(!frames.is_empty());
+    /// for frame in frames {
+    ///     // without debuginfo the frame can't be told apart from a closure
+    ///     if !frame[1].contains("<unknown source file>") {
+    ///         assert!(!frame[0].contains("closure"));
+    ///     }
+    /// }
+    /// ```
+    pub fn simplify_async_frames(mut self, cond: bool) -> Self {
+        self.simplify_async_frames = cond;
+        self
+    }
+
+    /// Configures the line ending used in rendered error and panic reports, defaults to `LineEnding::Lf`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder, LineEnding}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .line_ending(LineEnding::CrLf)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("config could not be loaded"))
+    ///     .note("the config is read from the working directory")
+    ///     .suggestion("pass a config with --config")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains(
+    ///     "Note: the config is read from the working directory\r\nSuggestion: pass a config with --config"
+    /// ));
+    /// assert!(!report.replace("\r\n", "").contains('\n'));
+    /// ```
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Configures the markers in front of the messages of the chain of errors in error
+    /// reports, defaults to `ChainStyle::Numbered`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error};
+    /// use color_anyhow::config::{ChainStyle, ColorChoice, HookBuilder};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .chain_style(ChainStyle::Bulleted('-'))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("expected `=`"))
+    ///     .context("could not parse the config\nline 3: `port 8080`")
+    ///     .context("could not start the server")
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     format!("{:?}", report),
+    ///     "
+    ///    - could not start the server
+    ///    - could not parse the config
+    ///      line 3: `port 8080`
+    ///    - expected `=`"
+    /// );
+    /// ```
+    pub fn chain_style(mut self, chain_style: ChainStyle) -> Self {
+        self.chain_style = chain_style;
+        self
+    }
+
+    /// Render a leading label before the chain of errors, or remove the blank line reports
+    /// start with when `None`
+    ///
+    /// # Details
+    ///
+    /// By default reports start with a line break, so the chain of errors starts on its own
+    /// line after the `Error: ` that `fn main` prints before the reports it returns. When a
+    /// label is set it is rendered in bold red in place of that line break, directly followed
+    /// by the chain. When `None` is set the report starts with the chain itself, so it
+    /// composes with a prefix printed by the application. The label can be overridden for a
+    /// single report with [`Section::chain_label`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .chain_label(Some("error:".to_owned()))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the archive is corrupt"));
+    /// assert_eq!(report, "error:\n   0: the archive is corrupt");
+    ///
+    /// let report = Err::<(), _>(anyhow!("the archive is corrupt"))
+    ///     .chain_label("fatal:")
+    ///     .unwrap_err();
+    /// assert_eq!(format!("{:?}", report), "fatal:\n   0: the archive is corrupt");
+    /// ```
+    ///
+    /// Without a label the report starts with the chain:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .chain_label(None)
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("myapp error:\n{:?}", anyhow!("the archive is corrupt"));
+    /// assert_eq!(report, "myapp error:\n   0: the archive is corrupt");
+    /// ```
+    ///
+    /// [`Section::chain_label`]: ../trait.Section.html#tymethod.chain_label
+    pub fn chain_label(mut self, label: Option<String>) -> Self {
+        self.chain_label = Some(label);
+        self
+    }
+
+    /// Render `header` as the first line of every error report, e.g. to tell apart the reports
+    /// of the components logging to the same output, defaults to `None`
+    ///
+    /// # Details
+    ///
+    /// The header is rendered as-is above the severity banner, the chain label and the chain
+    /// of errors. It takes the place of the leading line break reports start with by default,
+    /// so the chain still starts on the line after it, and it is followed by a line break
+    /// when the report doesn't start with one, e.g. with `chain_label(None)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .report_header(Some("=== [auth-service] error report ===".to_owned()))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the token expired"));
+    /// assert_eq!(report.lines().next(), Some("=== [auth-service] error report ==="));
+    /// assert_eq!(report, "=== [auth-service] error report ===\n   0: the token expired");
+    /// ```
+    ///
+    /// Without the leading line break:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .report_header(Some("=== [auth-service] error report ===".to_owned()))
+    ///     .chain_label(None)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the token expired"));
+    /// assert_eq!(report, "=== [auth-service] error report ===\n   0: the token expired");
+    /// ```
+    pub fn report_header(mut self, header: Option<String>) -> Self {
+        self.report_header = header;
+        self
+    }
+
+    /// Set a footer rendered at the end of every error report, e.g. where to get help with the
+    /// error, defaults to no footer
+    ///
+    /// # Details
+    ///
+    /// The footer is rendered as-is after an empty line, below everything else in the report,
+    /// including the help text, and is rendered even when the report has nothing but the
+    /// chain of errors. It is only left out of partial reports, i.e. those rendered with
+    /// [`ReportOptions::include`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .support_footer("Need help? support@example.com".to_owned())
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the token expired"));
+    /// assert_eq!(report, "\n   0: the token expired\n\nNeed help? support@example.com");
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the token expired"))
+    ///     .note("tokens expire after an hour")
+    ///     .suggestion("log in again")
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report)
+    ///     .ends_with("Suggestion: log in again\n\nNeed help? support@example.com"));
+    /// ```
+    ///
+    /// [`ReportOptions::include`]: struct.ReportOptions.html#method.include
+    pub fn support_footer(mut self, footer: String) -> Self {
+        self.support_footer = Some(footer);
+        self
+    }
+
+    /// Render a colored banner with the severity attached with [`Section::severity`] above the
+    /// chain of errors, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// The banner is rendered in bold red for `Fatal`, red for `Error` and yellow for `Warn`
+    /// reports, and reports without a severity get no banner. It spans the width the report
+    /// is rendered to, see [`width`], and is kept short without one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{ColorChoice, HookBuilder, ReportOptions};
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, Severity};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .severity_banner(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the journal is corrupted"))
+    ///     .severity(Severity::Fatal)
+    ///     .unwrap_err();
+    ///
+    /// let colored = color_anyhow::format_report(&report, &ReportOptions::default());
+    /// assert!(colored.starts_with("\x1b[1;38;5;9m▰▰▰ FATAL ▰▰▰\x1b[0m\n"));
+    ///
+    ///
+    /// // formatted with a width, the banner spans it
+    /// let banner = format!("{} FATAL {}", "▰".repeat(16), "▰".repeat(17));
+    /// assert_eq!(banner.chars().count(), 40);
+    /// assert!(format!("{:40?}", report).contains(&banner));
+    /// ```
+    ///
+    /// [`Section::severity`]: ../trait.Section.html#tymethod.severity
+    /// [`width`]: #method.width
+    pub fn severity_banner(mut self, cond: bool) -> Self {
+        self.severity_banner = cond;
+        self
+    }
+
+    /// Shorten the source paths of backtrace and `SpanTrace` frames to at most `width`
+    /// characters, defaults to no limit
+    ///
+    /// # Details
+    ///
+    /// Longer paths have their leading components replaced with `...`, e.g.
+    /// `.../deep/path/file.rs`, so the line number stays on screen. The file name is always
+    /// kept, even if it alone is wider than `width`. Shorter paths are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .max_path_width(30)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the index is stale"));
+    /// let paths: Vec<_> = report
+    ///     .lines()
+    ///     .filter_map(|line| line.trim_start().strip_prefix("at "))
+    ///     .filter_map(|location| location.rsplitn(2, ':').nth(1))
+    ///     .collect();
+    ///
+    /// // the paths of the standard library are long enough to be shortened
+    /// assert!(paths.iter().any(|path| path.starts_with(".../")));
+    /// for path in paths {
+    ///     let file_name = path.rsplit('/').next().unwrap();
+    ///     assert!(path.chars().count() <= 30 || path == format!(".../{}", file_name));
+    /// }
+    /// ```
+    pub fn max_path_width(mut self, width: usize) -> Self {
+        self.max_path_width = Some(width);
+        self
+    }
+
+    /// Render error reports to `width` columns, defaults to the width of the terminal
+    ///
+    /// # Details
+    ///
+    /// The width is what sections are wrapped to and what severity banners span. A width
+    /// given when formatting a report, e.g. `{:80?}`, takes precedence over this one.
+    /// Without either, reports are rendered to the width of the terminal stderr is
+    /// connected to, or else to the width in the `COLUMNS` environment variable, as set
+    /// by many CI services whose logs aren't terminals, or else without a width. A
+    /// `COLUMNS` that isn't a positive number is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section, Severity};
+    ///
+    /// std::env::set_var("COLUMNS", "40");
+    /// HookBuilder::blank()
+    ///     .severity_banner(true)
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the journal is corrupted"))
+    ///     .severity(Severity::Fatal)
+    ///     .unwrap_err();
+    ///
+    /// // stderr isn't a terminal, so the banner spans `COLUMNS`
+    /// let banner = format!("{} FATAL {}", "▰".repeat(16), "▰".repeat(17));
+    /// assert!(format!("{:?}", report).contains(&banner));
+    ///
+    /// std::env::set_var("COLUMNS", "wide");
+    /// assert!(format!("{:?}", report).contains("▰▰▰ FATAL ▰▰▰\n"));
+    /// ```
+    ///
+    /// The configured width takes precedence over the terminal and `COLUMNS`:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section, Severity};
+    ///
+    /// std::env::set_var("COLUMNS", "100");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .width(20)
+    ///     .severity_banner(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the journal is corrupted"))
+    ///     .severity(Severity::Fatal)
+    ///     .unwrap_err();
+    ///
+    /// let banner = format!("{} FATAL {}", "▰".repeat(6), "▰".repeat(7));
+    /// assert!(format!("{:?}", report).contains(&format!("{}\n", banner)));
+    /// ```
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Render at most `max` of the error sections attached with `Section::error` and its
+    /// siblings, defaults to rendering all of them
+    ///
+    /// # Details
+    ///
+    /// The rest are summarized as `(and N more errors suppressed)`. Identical errors grouped
+    /// by `group_errors` count as one section but as all of their errors in the summary.
+    /// Other sections are unaffected by the limit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder, Section};
+    /// use std::io::{Error, ErrorKind};
+    ///
+    /// HookBuilder::blank()
+    ///     .max_error_sections(3)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = (0..10)
+    ///     .fold(Err::<(), _>(anyhow!("the batch failed")), |report, n| {
+    ///         report.error(Error::new(ErrorKind::InvalidData, format!("record {} is malformed", n)))
+    ///     })
+    ///     .section("processed 10 records")
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("record 2 is malformed"));
+    /// assert!(!report.contains("record 3 is malformed"));
+    /// assert!(report.contains("(and 7 more errors suppressed)"));
+    /// assert!(report.contains("processed 10 records"));
+    /// ```
+    pub fn max_error_sections(mut self, max: usize) -> Self {
+        self.max_error_sections = Some(max);
+        self
+    }
+
+    /// Skip the error sections attached with `Section::error` and its siblings whose error
+    /// is already in the chain of errors of the report, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// When errors are aggregated, the same error can end up both as the source of the
+    /// report and in an error section. Errors are matched by their message, so an error
+    /// section is also skipped when a distinct error in the chain happens to have the same
+    /// message. Only the message of the attached error itself is compared, not those of its
+    /// sources. Skipped sections don't count towards `max_error_sections`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, config::HookBuilder, Section};
+    /// use std::io::{Error, ErrorKind};
+    ///
+    /// HookBuilder::default()
+    ///     .dedup_error_sections_against_chain(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let not_found = || Error::new(ErrorKind::NotFound, "config.toml was not found");
+    /// let report = Err::<(), _>(not_found())
+    ///     .context("the config could not be loaded")
+    ///     .error(not_found())
+    ///     .error(Error::new(ErrorKind::PermissionDenied, "the cache is read only"))
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// assert_eq!(report.matches("config.toml was not found").count(), 1);
+    /// assert!(report.contains("the cache is read only"));
+    /// ```
+    pub fn dedup_error_sections_against_chain(mut self, cond: bool) -> Self {
+        self.dedup_error_sections_against_chain = cond;
+        self
+    }
+
+    /// Mark error reports as reported once they were rendered with `Debug`, defaults to
+    /// `false`
+    ///
+    /// # Details
+    ///
+    /// Layers that log every error they see can check [`is_reported`] to skip the reports
+    /// a layer below them already logged. Only complete renders mark a report, rendering
+    /// it with `{:#?}` doesn't. See [`mark_reported`] to mark reports explicitly instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, config::HookBuilder};
+    ///
+    /// HookBuilder::default().auto_mark_reported(true).install().unwrap();
+    ///
+    /// let report = anyhow!("the job timed out");
+    /// let _ = format!("{:#?}", report);
+    /// assert!(!color_anyhow::is_reported(&report));
+    ///
+    /// let _ = format!("{:?}", report);
+    /// let report = Err::<(), _>(report).context("the request failed").unwrap_err();
+    /// assert!(color_anyhow::is_reported(&report));
+    /// ```
+    ///
+    /// [`is_reported`]: ../fn.is_reported.html
+    /// [`mark_reported`]: ../fn.mark_reported.html
+    pub fn auto_mark_reported(mut self, cond: bool) -> Self {
+        self.auto_mark_reported = cond;
+        self
+    }
+
+    /// Annotate backtrace frames with the crate they belong to and its version, e.g.
+    /// `[tokio v1.28.2]`, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// This is best-effort. Versions are only known for crates built from the cargo
+    /// registry, which are recognized by the directories their sources are in, so they
+    /// need debuginfo for the frame's source location. Frames of other crates, such as
+    /// the standard library or the crates of the workspace, are annotated with the crate
+    /// name from their symbol name alone, e.g. `[std]`, and frames whose crate can't be
+    /// told are left as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .annotate_frame_crate_versions(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the lockfile is out of date"));
+    ///
+    /// // the frames of color-anyhow, which captured the backtrace
+    /// assert!(report
+    ///     .lines()
+    ///     .any(|line| line.contains(": color_anyhow::") && line.ends_with("[color_anyhow]")));
+    /// ```
+    pub fn annotate_frame_crate_versions(mut self, cond: bool) -> Self {
+        self.annotate_frame_crate_versions = cond;
+        self
+    }
+
+    /// Render backtraces as the list of their frames' addresses when at least `threshold` of
+    /// the frames have no symbol name, defaults to `0.8`
+    ///
+    /// # Details
+    ///
+    /// Backtraces captured in binaries whose symbols were stripped are all `<unknown>` frames
+    /// without source locations. Those are rendered as a note, the path of the executable, its
+    /// build-id where it can be read from the executable's ELF notes, and the address of each
+    /// frame, for symbolicating them offline with the debug artifacts of the build. Frame
+    /// filters don't apply to the addresses and no source snippets are read.
+    ///
+    /// The threshold is a fraction between `0.0` and `1.0`, anything above `1.0` never renders
+    /// the addresses. Only backtraces captured with the `backtrace` crate carry addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .stripped_symbols_threshold(0.0)
+    ///     .stripped_backtrace_note("no symbols, see the ci artifacts".to_string())
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the index is stale"));
+    /// let backtrace = report.split(" BACKTRACE ").nth(1).unwrap();
+    /// let mut lines = backtrace.lines().skip(1).map(str::trim_start);
+    ///
+    /// assert_eq!(lines.next(), Some("no symbols, see the ci artifacts"));
+    /// assert!(lines.next().unwrap().starts_with("executable: "));
+    /// assert!(backtrace.lines().any(|line| line.trim_start().starts_with("1: 0x")));
+    /// assert!(!backtrace.contains(" at "));
+    /// ```
+    ///
+    /// Backtraces with symbols are rendered as usual with the default threshold:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank().install().unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the index is stale"));
+    ///
+    /// assert!(!report.contains("symbols unavailable"));
+    /// assert!(report.contains("rust_out::main"));
+    /// ```
+    pub fn stripped_symbols_threshold(mut self, threshold: f64) -> Self {
+        self.stripped_symbols_threshold = threshold;
+        self
+    }
+
+    /// Replace the note rendered above backtraces rendered as addresses, see
+    /// [`stripped_symbols_threshold`]
+    ///
+    /// [`stripped_symbols_threshold`]: #method.stripped_symbols_threshold
+    pub fn stripped_backtrace_note(mut self, note: String) -> Self {
+        self.stripped_backtrace_note = note;
+        self
+    }
+
+    /// Show the [`Fingerprint`] of each error report as a `Fingerprint:` line after its help
+    /// text, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// The fingerprint is computed with the default `FingerprintOptions`, so equivalent errors
+    /// show the same fingerprint across runs even if their messages differ in ids, addresses,
+    /// or paths, or their backtraces in addresses and line numbers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .display_fingerprint(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// fn connect(port: u16) -> Error {
+    ///     anyhow!("connection to 127.0.0.1:{} refused", port)
+    /// }
+    ///
+    /// let first = connect(8080);
+    /// let second = connect(9000);
+    /// let fingerprint = color_anyhow::fingerprint(&first);
+    ///
+    /// assert_eq!(fingerprint, color_anyhow::fingerprint(&second));
+    /// assert!(format!("{:?}", first).ends_with(&format!("Fingerprint: {}", fingerprint)));
+    /// assert!(format!("{:?}", second).ends_with(&format!("Fingerprint: {}", fingerprint)));
+    /// ```
+    ///
+    /// [`Fingerprint`]: ../struct.Fingerprint.html
+    pub fn display_fingerprint(mut self, cond: bool) -> Self {
+        self.display_fingerprint = cond;
+        self
+    }
+
+    /// Show the status code attached to each error report with [`Section::status_hint`] as a
+    /// `Status:` line after its help text, defaults to `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .display_status_hints(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("no user with the id 42"))
+    ///     .status_hint(400)
+    ///     .note("ids are assigned on signup")
+    ///     .status_hint(404)
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).ends_with("Note: ids are assigned on signup\nStatus: 404"));
+    /// ```
+    ///
+    /// [`Section::status_hint`]: ../trait.Section.html#tymethod.status_hint
+    pub fn display_status_hints(mut self, cond: bool) -> Self {
+        self.display_status_hints = cond;
+        self
+    }
+
+    /// Show the codes attached with [`Section::note_coded`] and [`Section::suggestion_coded`]
+    /// as a dimmed `[CODE]` after their text, defaults to `true`
+    ///
+    /// # Details
+    ///
+    /// Hiding the codes only changes the rendered report, they are still returned by
+    /// `extract` and included in an `HttpReport`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .display_help_codes(false)
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the lockfile is out of date"))
+    ///     .suggestion_coded("E1042", "run cargo update")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).ends_with("Suggestion: run cargo update"));
+    /// ```
+    ///
+    /// [`Section::note_coded`]: ../trait.Section.html#tymethod.note_coded
+    /// [`Section::suggestion_coded`]: ../trait.Section.html#tymethod.suggestion_coded
+    pub fn display_help_codes(mut self, cond: bool) -> Self {
+        self.display_help_codes = cond;
+        self
+    }
+
+    /// Show which environment variables would add the traces missing from an error report,
+    /// defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// Hints are only shown for what is actually missing and can be enabled through the
+    /// environment: `RUST_LIB_BACKTRACE` when no backtrace was captured because backtraces are
+    /// disabled, and `RUST_SPANTRACE` when no `SpanTrace` was captured because `SpanTrace`
+    /// capture is disabled. Reports that already show everything get no hints. The hints are
+    /// rendered after the backtrace.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// std::env::remove_var("RUST_SPANTRACE");
+    /// HookBuilder::default().display_env_hints(true).install().unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the cache is stale"));
+    /// assert!(report.ends_with(
+    ///     "Run with RUST_LIB_BACKTRACE=1 environment variable to display the backtrace."
+    /// ));
+    /// assert!(!report.contains("RUST_SPANTRACE"));
+    ///
+    /// // nothing is missing once backtraces are enabled
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// let report = format!("{:?}", anyhow!("the cache is stale"));
+    /// assert!(!report.contains("Run with"));
+    /// ```
+    #[cfg_attr(
+        feature = "capture-spantrace",
+        doc = r#"
+Only the backtrace hint is shown for reports with a `SpanTrace`:
+
+```rust
+use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}};
+use tracing::instrument;
+use tracing_error::ErrorLayer;
+use tracing_subscriber::prelude::*;
+
+#[instrument]
+fn fetch(key: &str) -> Error {
+    anyhow!("the cache is stale")
+}
+
+tracing_subscriber::registry().with(ErrorLayer::default()).init();
+
+std::env::set_var("RUST_LIB_BACKTRACE", "0");
+std::env::set_var("RUST_SPANTRACE", "1");
+HookBuilder::default()
+    .display_env_hints(true)
+    .color_choice(ColorChoice::Never)
+    .install()
+    .unwrap();
+
+let report = format!("{:?}", fetch("users"));
+assert!(report.contains("rust_out::fetch with key=\"users\""));
+assert!(report.contains("RUST_LIB_BACKTRACE=1"));
+assert!(!report.contains("RUST_SPANTRACE"));
+```
+"#
+    )]
+    pub fn display_env_hints(mut self, cond: bool) -> Self {
+        self.display_env_hints = cond;
+        self
+    }
+
+    /// Show how long the process ran since the hooks were installed in panic reports, defaults
+    /// to `true`
+    ///
+    /// # Details
+    ///
+    /// The uptime is shown dimmed below the panic location, e.g. `process uptime: 3d 4h 12m`,
+    /// or in milliseconds for panics within the first second, e.g. `process uptime: 840ms`,
+    /// which usually point at a configuration error. See [`HumanDuration`] for the format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::HookBuilder;
+    ///
+    /// let builder = HookBuilder::default();
+    /// assert!(builder.display_uptime_enabled());
+    /// assert!(!builder.display_uptime(false).display_uptime_enabled());
+    /// ```
+    ///
+    /// [`HumanDuration`]: ../struct.HumanDuration.html
+    pub fn display_uptime(mut self, cond: bool) -> Self {
+        self.display_uptime = cond;
+        self
+    }
+
+    /// Show how long the process ran since the hooks were installed in error reports, defaults
+    /// to `false`
+    ///
+    /// # Details
+    ///
+    /// The uptime is shown dimmed right after the chain of errors, formatted the same way as
+    /// in panic reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .display_uptime_in_error_reports(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("config could not be loaded"));
+    /// assert!(report.contains("config could not be loaded\n\nprocess uptime: "));
+    /// assert!(report.contains("ms"));
+    /// ```
+    pub fn display_uptime_in_error_reports(mut self, cond: bool) -> Self {
+        self.display_uptime_in_error_reports = cond;
+        self
+    }
+
+    /// Identify the build of the application in panic reports, e.g. with the commit it was
+    /// built from
+    ///
+    /// # Details
+    ///
+    /// The build is shown below the panic location, e.g.
+    /// `Build:    1a2b3c4 (dirty) release x86_64-unknown-linux-gnu 2024-05-02`. Without a
+    /// `BuildInfo`, or with one without any information, the line is omitted. See
+    /// [`BuildInfo`] for collecting the information at build time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::HookBuilder, BuildInfo};
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// #
+    /// # #[derive(Clone, Default)]
+    /// # struct Captured(Arc<Mutex<Vec<u8>>>);
+    /// #
+    /// # impl Write for Captured {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    /// #         self.0.lock().unwrap().write(buf)
+    /// #     }
+    /// #
+    /// #     fn flush(&mut self) -> io::Result<()> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// const BUILD: BuildInfo = BuildInfo {
+    ///     commit: Some("1a2b3c4"),
+    ///     dirty: true,
+    ///     profile: Some("release"),
+    ///     target: Some("x86_64-unknown-linux-gnu"),
+    ///     timestamp: Some("2024-05-02"),
+    /// };
+    ///
+    /// let panics = Captured::default();
+    /// let sink = panics.clone();
+    /// HookBuilder::default()
+    ///     .build_info(BUILD)
+    ///     .add_output_sink(Box::new(move || Box::new(sink.clone())))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    ///
+    /// let report = String::from_utf8(panics.0.lock().unwrap().clone()).unwrap();
+    /// assert_eq!(report.matches("Build:").count(), 1);
+    /// assert!(report.contains(
+    ///     "\nBuild:    1a2b3c4 (dirty) release x86_64-unknown-linux-gnu 2024-05-02\n"
+    /// ));
+    /// ```
+    ///
+    /// [`BuildInfo`]: ../struct.BuildInfo.html
+    pub fn build_info(mut self, build: crate::BuildInfo) -> Self {
+        self.build_info = Some(build);
+        self
+    }
+
+    /// Identify the build of the application in error reports as well, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// The build is shown dimmed right after the chain of errors, following the process
+    /// uptime if it's shown.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, BuildInfo};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .build_info(BuildInfo {
+    ///         commit: Some("1a2b3c4"),
+    ///         profile: Some("debug"),
+    ///         ..BuildInfo::new()
+    ///     })
+    ///     .display_build_info_in_error_reports(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("config could not be loaded"));
+    /// assert!(report.contains("config could not be loaded\n\nBuild: 1a2b3c4 debug"));
+    /// assert_eq!(report.matches("Build:").count(), 1);
+    /// ```
+    pub fn display_build_info_in_error_reports(mut self, cond: bool) -> Self {
+        self.display_build_info_in_error_reports = cond;
+        self
+    }
+
+    /// Recolor panic and error reports with a `Theme`
+    ///
+    /// # Details
+    ///
+    /// The theme replaces the colors of every report, backtraces and `SpanTrace`s
+    /// included. Reports with a theme of their own, attached with `Section::theme`,
+    /// are rendered with that theme instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, TextColor, Theme};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .theme(Theme::new().color(TextColor::BrightRed, TextColor::Rgb(255, 85, 0)))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("config could not be loaded"));
+    /// assert!(report.contains("\x1b[38;2;255;85;0mconfig could not be loaded"));
+    /// ```
+    pub fn theme(mut self, theme: crate::Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Begin error reports with a table of contents listing the line each block starts at
+    ///
+    /// # Details
+    ///
+    /// The table lists the blocks the report has, out of the error chain, the sections, the
+    /// `SpanTrace`, the backtrace and the help text, on a single line like
+    /// `Contents: 1. Error chain (line 4)  2. Backtrace (line 9)`. Lines are counted from the
+    /// start of the report, which begins with a line break, so they match the line numbers
+    /// of a pager showing an error returned from `main`. Reports rendered with only some of
+    /// their blocks by `ReportOptions::include` have no table of contents.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .table_of_contents(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("connection reset"))
+    ///     .context("could not sync")
+    ///     .note("the server may be down")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    /// let lines: Vec<_> = report.lines().collect();
+    ///
+    /// // there is neither a backtrace nor a SpanTrace, nor any sections
+    /// assert_eq!(lines[1], "Contents: 1. Error chain (line 4)  2. Help (line 7)");
+    /// assert_eq!(lines[3], "   0: could not sync");
+    /// assert_eq!(lines[6], "Note: the server may be down");
+    /// ```
+    pub fn table_of_contents(mut self, cond: bool) -> Self {
+        self.table_of_contents = cond;
+        self
+    }
+
+    /// Render the blocks of error reports in reverse, with the chain of errors last, for log
+    /// viewers that show the newest lines at the top, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// The help text comes first, followed by the sections, the backtrace, the `SpanTrace`
+    /// and the chain of errors, so that the most relevant information is at the top once the
+    /// viewer has flipped the lines. The lines within each block keep their order. Reports
+    /// rendered with only some of their blocks by `ReportOptions::include` keep the usual
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .reverse_layout(true)
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("connection reset"))
+    ///     .context("could not sync")
+    ///     .section("last synced 2 days ago")
+    ///     .suggestion("check that the server is up")
+    ///     .unwrap_err();
+    ///
+    /// // without reversing, the report reads
+    /// // "\n   0: could not sync\n   1: connection reset\n\nlast synced 2 days ago\n\nSuggestion: check that the server is up"
+    /// assert_eq!(
+    ///     format!("{:?}", report),
+    ///     "\nSuggestion: check that the server is up\n\nlast synced 2 days ago\n\n   0: could not sync\n   1: connection reset"
+    /// );
+    /// ```
+    pub fn reverse_layout(mut self, cond: bool) -> Self {
+        self.reverse_layout = cond;
+        self
+    }
+
+    /// Show a unique identifier in every panic and error report, for users to pass on to
+    /// support
+    ///
+    /// # Details
+    ///
+    /// The identifier is generated each time a report is rendered, so printing the same
+    /// error twice yields two identifiers, and is the same in the report written to the
+    /// output sinks. It's in the format of a [ULID], 26 characters of Crockford's base32 that
+    /// sort by the time the report was rendered. Error reports show it after the fingerprint,
+    /// as `Report ID: 01HX3K5Q2J8ZJ4V6T0M9N7C1RB`, panic reports after the location.
+    ///
+    /// [ULID]: https://github.com/ulid/spec
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .display_report_id(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!("the upload failed");
+    /// let report_id = |rendered: &str| {
+    ///     let line = rendered.lines().find(|line| line.starts_with("Report ID: ")).unwrap();
+    ///     line["Report ID: ".len()..].to_owned()
+    /// };
+    /// let first = report_id(&format!("{:?}", report));
+    /// let second = report_id(&format!("{:?}", report));
+    ///
+    /// for id in [&first, &second].iter() {
+    ///     assert_eq!(id.len(), 26);
+    ///     assert!(id.chars().all(|c| c.is_ascii_digit() || "ABCDEFGHJKMNPQRSTVWXYZ".contains(c)));
+    /// }
+    /// assert_ne!(first, second);
+    /// ```
+    pub fn display_report_id(mut self, cond: bool) -> Self {
+        self.display_report_id = cond;
+        self
+    }
+
+    /// Write an artifact of every panic to a directory, for post-mortem tooling
+    ///
+    /// # Details
+    ///
+    /// The panic hook writes the artifact after printing the report, as described by the
+    /// [`ArtifactSpec`]. Failing to write it never fails the hook, a single line describing
+    /// the error is printed to stderr instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::HookBuilder, ArtifactSpec};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("color-anyhow-artifacts-{}", std::process::id()));
+    /// HookBuilder::default()
+    ///     .display_report_id(true)
+    ///     .panic_artifact(ArtifactSpec::new(&dir).file_name("crash-{report_id}-{timestamp}").retain(2))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// for n in 0..3 {
+    ///     let _ = std::thread::spawn(move || panic!("the index is corrupt ({})", n)).join();
+    /// }
+    ///
+    /// let artifacts: Vec<_> = std::fs::read_dir(&dir)
+    ///     .unwrap()
+    ///     .map(|entry| entry.unwrap().path())
+    ///     .collect();
+    ///
+    /// // only the 2 most recent are kept
+    /// assert_eq!(artifacts.len(), 2);
+    /// let mut reports = String::new();
+    /// for artifact in &artifacts {
+    ///     let name = artifact.file_name().unwrap().to_str().unwrap();
+    ///     let (report_id, timestamp) = name["crash-".len()..name.len() - ".txt".len()].split_at(26);
+    ///     assert!(report_id.chars().all(|c| c.is_ascii_alphanumeric()));
+    ///     assert_eq!(timestamp.len(), "-20240502T101500Z".len());
+    ///     assert!(timestamp.ends_with('Z'));
+    ///
+    ///     let report = std::fs::read_to_string(artifact).unwrap();
+    ///     assert!(report.starts_with("The application panicked (crashed).\n"));
+    ///     assert!(report.contains(&format!("Report ID: {}", report_id)));
+    ///     reports.push_str(&report);
+    /// }
+    /// assert!(!reports.contains("the index is corrupt (0)"));
+    /// assert!(reports.contains("the index is corrupt (1)"));
+    /// assert!(reports.contains("the index is corrupt (2)"));
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    ///
+    /// When the directory can't be created, the panic is still reported:
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::HookBuilder, ArtifactSpec};
+    /// use std::io::Write;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Captured(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Captured {
+    ///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// // a file stands where the directory should be created
+    /// let file = std::env::temp_dir().join(format!("color-anyhow-not-a-dir-{}", std::process::id()));
+    /// std::fs::write(&file, "").unwrap();
+    ///
+    /// let log = Captured::default();
+    /// let sink = log.clone();
+    /// HookBuilder::default()
+    ///     .panic_artifact(ArtifactSpec::new(file.join("artifacts")))
+    ///     .add_output_sink(Box::new(move || Box::new(sink.clone())))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    ///
+    /// let report = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+    /// assert!(report.contains("the index is corrupt"));
+    /// assert!(std::fs::metadata(&file).unwrap().is_file());
+    /// # std::fs::remove_file(&file).unwrap();
+    /// ```
+    ///
+    /// [`ArtifactSpec`]: ../struct.ArtifactSpec.html
+    pub fn panic_artifact(mut self, spec: crate::ArtifactSpec) -> Self {
+        self.panic_artifact = Some(spec);
+        self
+    }
+
+    /// Show the wall-clock time each span was entered on the frames of `SpanTrace`s
+    ///
+    /// # Details
+    ///
+    /// The entry times are recorded by [`SpanTimestampLayer`], which must be
+    /// installed in the subscriber alongside `tracing_error::ErrorLayer`. Only
+    /// `SpanTrace`s captured by color-anyhow itself carry timestamps, spans
+    /// entered before the layer was installed are shown without one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "test-util")]
+    /// # fn main() {
+    /// use color_anyhow::{
+    ///     anyhow::anyhow,
+    ///     config::{ColorChoice, HookBuilder},
+    ///     ManualClock, SpanTimestampLayer,
+    /// };
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// tracing_subscriber::registry()
+    ///     .with(ErrorLayer::default())
+    ///     .with(SpanTimestampLayer::default())
+    ///     .init();
+    ///
+    /// // 2021-03-04 05:06:07 UTC
+    /// let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_614_834_367)));
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .clock(clock.clone())
+    ///     .spantrace_timestamps(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// clock.advance(Duration::from_millis(89));
+    /// let report = tracing::info_span!("load_config").in_scope(|| anyhow!("config missing"));
+    /// let report = format!("{:?}", report);
+    ///
+    /// // the frame is followed by the location of the span and when it was entered
+    /// let frame: Vec<_> = report.split("load_config").nth(1).unwrap().lines().collect();
+    /// assert_eq!(frame[2].trim(), "entered at 2021-03-04T05:06:07.089Z");
+    /// # }
+    /// # #[cfg(not(feature = "test-util"))]
+    /// # fn main() {}
+    /// ```
+    ///
+    /// Spans entered before the layer was installed are shown without a timestamp:
+    ///
+    /// ```rust
+    /// use color_anyhow::{
+    ///     anyhow::anyhow,
+    ///     config::{ColorChoice, HookBuilder},
+    ///     SpanTimestampLayer,
+    /// };
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::{filter::filter_fn, prelude::*};
+    ///
+    /// // only spans created once this is set are seen by the timestamp layer
+    /// static TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+    ///
+    /// tracing_subscriber::registry()
+    ///     .with(ErrorLayer::default())
+    ///     .with(
+    ///         SpanTimestampLayer::default()
+    ///             .with_filter(filter_fn(|_| TIMESTAMPS.load(Ordering::SeqCst))),
+    ///     )
+    ///     .init();
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .spantrace_timestamps(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = tracing::info_span!("run_server").in_scope(|| {
+    ///     TIMESTAMPS.store(true, Ordering::SeqCst);
+    ///     tracing::info_span!("load_config").in_scope(|| anyhow!("config missing"))
+    /// });
+    /// let report = format!("{:?}", report);
+    ///
+    /// let (load_config, run_server) = report.split_at(report.find("run_server").unwrap());
+    /// assert!(load_config.contains("entered at "));
+    /// assert!(!run_server.contains("entered at "));
+    /// ```
+    ///
+    /// [`SpanTimestampLayer`]: ../struct.SpanTimestampLayer.html
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn spantrace_timestamps(mut self, cond: bool) -> Self {
+        self.spantrace_timestamps = cond;
+        self
+    }
+
+    /// Configures how the `self` field of instrumented methods is rendered in `SpanTrace`s,
+    /// defaults to `SpanTraceSelfField::Show`
+    ///
+    /// # Details
+    ///
+    /// `#[instrument]` records the receiver of a method as its `self` field, which for types
+    /// with a verbose `Debug` output can drown out the other fields. Spans without a `self`
+    /// field are rendered unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder, SpanTraceSelfField}};
+    /// use tracing::instrument;
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// #[derive(Debug)]
+    /// struct Loader {
+    ///     search_paths: Vec<&'static str>,
+    /// }
+    ///
+    /// impl Loader {
+    ///     #[instrument]
+    ///     fn load(&self, name: &str) -> Error {
+    ///         anyhow!("config not found")
+    ///     }
+    /// }
+    ///
+    /// tracing_subscriber::registry().with(ErrorLayer::default()).init();
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .spantrace_self_field(SpanTraceSelfField::Hide)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let loader = Loader { search_paths: vec!["/etc/app", "~/.config/app"] };
+    /// let report = format!("{:?}", loader.load("app.toml"));
+    ///
+    /// assert!(report.contains("rust_out::load with name=\"app.toml\""));
+    /// assert!(!report.contains("self="));
+    /// assert!(!report.contains("search_paths"));
+    /// ```
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn spantrace_self_field(mut self, self_field: SpanTraceSelfField) -> Self {
+        self.spantrace_self_field = self_field;
+        self
+    }
+
+    /// Group consecutive `SpanTrace` frames from the same target under a single target
+    /// header, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// Each frame of a group is shown below the header with only its span name, followed by
+    /// its own fields, source location and timestamp. Frames whose target differs from both
+    /// of their neighbours are shown as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}};
+    /// use tracing::instrument;
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// #[instrument]
+    /// fn connect(host: &str) -> Error {
+    ///     query("users")
+    /// }
+    ///
+    /// #[instrument]
+    /// fn query(table: &str) -> Error {
+    ///     fetch(42)
+    /// }
+    ///
+    /// #[instrument]
+    /// fn fetch(id: u32) -> Error {
+    ///     anyhow!("the connection was reset")
+    /// }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// tracing_subscriber::registry().with(ErrorLayer::default()).init();
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .collapse_same_target_spans(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", connect("db.internal"));
+    ///
+    /// assert_eq!(report.matches("rust_out").count(), 1);
+    /// assert!(report.contains("rust_out\n      0: fetch with id=42"));
+    /// assert!(report.contains("1: query with table=\"users\""));
+    /// assert!(report.contains("2: connect with host=\"db.internal\""));
+    /// ```
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn collapse_same_target_spans(mut self, cond: bool) -> Self {
+        self.collapse_same_target_spans = cond;
+        self
+    }
+
+    /// Annotate backtrace frames with the `SpanTrace` span they correlate with, defaults to
+    /// `false`
+    ///
+    /// # Details
+    ///
+    /// This is experimental. When a report has both a backtrace and a `SpanTrace`, each span
+    /// is matched by its source location to the innermost backtrace frame in the same file
+    /// at or below the line the span was created on, with no other span created in between,
+    /// e.g. the frame of a function instrumented with `#[instrument]`. The frame is then
+    /// followed by a line such as `in span app::fetch with id=42`. Frames and spans that
+    /// don't correlate are rendered as usual, and the `SpanTrace` is still rendered in full.
+    /// Matching requires the debug info of the binary, without it no frame is annotated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder};
+    /// use tracing::instrument;
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// #[instrument]
+    /// fn fetch(id: u32) -> Error {
+    ///     anyhow!("the connection was reset")
+    /// }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// tracing_subscriber::registry().with(ErrorLayer::default()).init();
+    ///
+    /// HookBuilder::default()
+    ///     .correlate_traces(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", fetch(42));
+    /// assert!(report.contains("SPANTRACE"));
+    ///
+    /// // frames only have source locations if the binary has debug info
+    /// let frame = report.lines().position(|line| line.contains("rust_out::fetch::h"));
+    /// let location = frame.and_then(|n| report.lines().nth(n + 1));
+    /// if let Some(location) = location.filter(|line| !line.contains("<unknown source file>")) {
+    ///     assert!(report.contains("in span rust_out::fetch with id=42"));
+    /// }
+    /// ```
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn correlate_traces(mut self, cond: bool) -> Self {
+        self.correlate_traces = cond;
+        self
+    }
+
+    /// Scan the chain of errors of each report for patterns and highlight the key facts they
+    /// extract below the chain
+    ///
+    /// # Details
+    ///
+    /// Each pattern is matched against the message of every error in the chain. For every
+    /// match its template is expanded, with `$1` or `${name}` replaced by the text of the
+    /// capture group, and the result is shown as a bold line below the chain. Identical lines
+    /// are shown once and at most 3 lines are shown per report. Patterns are compiled when
+    /// they are constructed, not when reports are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, config::{ColorChoice, HookBuilder}, regex_lite::Regex};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .highlight_patterns(vec![
+    ///         (
+    ///             Regex::new(r"expected version (\d+), found (\d+)").unwrap(),
+    ///             "expected: $1, found: $2".to_owned(),
+    ///         ),
+    ///         (
+    ///             Regex::new(r"unknown field `(?P<field>\w+)`").unwrap(),
+    ///             "unknown field: ${field}".to_owned(),
+    ///         ),
+    ///     ])
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), _>(anyhow!("expected version 3, found 5"))
+    ///     .context("could not load the schema")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("1: expected version 3, found 5\n\nexpected: 3, found: 5"));
+    ///
+    /// // no match
+    /// let report = format!("{:?}", anyhow!("schema not found"));
+    /// assert!(!report.contains("expected:"));
+    ///
+    /// // multiple matches, duplicates are shown once
+    /// let report = Err::<(), _>(anyhow!("unknown field `nmae`, unknown field `agee`"))
+    ///     .context("unknown field `nmae` in the config")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("\n\nunknown field: nmae\nunknown field: agee"));
+    /// assert_eq!(report.matches("unknown field: nmae").count(), 1);
+    /// ```
+    #[cfg(feature = "highlight-patterns")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "highlight-patterns")))]
+    pub fn highlight_patterns(mut self, patterns: Vec<(regex_lite::Regex, String)>) -> Self {
+        self.highlight_patterns = patterns;
+        self
+    }
+
+    /// Add a `Diagnostics:` section to the reports of the errors matched by `trigger`, with a
+    /// body produced by `provider`
+    ///
+    /// # Details
+    ///
+    /// `trigger` is called with the error a report is created for, before any context is added
+    /// to it, and `provider` is only called when it matches, so reports of other errors only
+    /// pay for the trigger. Both run when the report is created, so the diagnostics describe
+    /// the state of the program at the time of the error, e.g. the tokio runtime with
+    /// [`tokio_runtime_diagnostics`] for errors caused by timeouts. A trigger or provider that
+    /// panics is skipped, the report is still created.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// HookBuilder::default()
+    ///     .add_conditional_section(
+    ///         Box::new(|error| error.to_string().contains("timed out")),
+    ///         Box::new(|| "3 tasks waiting on the pool lock".to_string()),
+    ///     )
+    ///     .add_conditional_section(
+    ///         Box::new(|error| error.to_string().contains("timed out")),
+    ///         Box::new(|| panic!("the diagnostics are unavailable")),
+    ///     )
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let timeout = format!("{:?}", anyhow!("timed out acquiring the pool lock"));
+    /// let other = format!("{:?}", anyhow!("the pool is closed"));
+    ///
+    /// assert!(timeout.contains("Diagnostics:\n   3 tasks waiting on the pool lock"));
+    /// assert_eq!(timeout.matches("Diagnostics:").count(), 1);
+    /// assert!(!other.contains("Diagnostics:"));
+    /// ```
+    ///
+    /// [`tokio_runtime_diagnostics`]: ../fn.tokio_runtime_diagnostics.html
+    pub fn add_conditional_section(
+        mut self,
+        trigger: Box<SectionTriggerCallback>,
+        provider: Box<SectionProviderCallback>,
+    ) -> Self {
+        self.conditional_sections.push(ConditionalSection {
+            trigger: trigger.into(),
+            provider: provider.into(),
+        });
+        self
+    }
+
+    /// Link the reports of errors in `category` to the runbook at `url`
+    ///
+    /// # Details
+    ///
+    /// Reports of errors whose category was attached with `Section::category` get a
+    /// `Runbook: <url>` line after their notes and suggestions, when a runbook is registered
+    /// for the category. Registering a category again replaces its runbook, and reports of errors in
+    /// unregistered categories or without a category render no runbook.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .register_runbook("database", "https://runbooks.example.com/db".to_string())
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the connection pool is exhausted"))
+    ///     .category("database")
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report).ends_with("Runbook: https://runbooks.example.com/db"));
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the queue is full"))
+    ///     .category("queue")
+    ///     .unwrap_err();
+    /// assert!(!format!("{:?}", report).contains("Runbook:"));
+    /// ```
+    pub fn register_runbook(mut self, category: &str, url: String) -> Self {
+        self.runbooks.retain(|(other, _)| other != category);
+        self.runbooks.push((category.to_owned(), url));
+        self
+    }
+
+    /// Add a custom filter to the set of frame filters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// color_anyhow::config::HookBuilder::default()
+    ///     .add_frame_filter(Box::new(|frames| {
+    ///         let filters = &[
+    ///             "uninteresting_function",
+    ///         ];
+    ///
+    ///         frames.retain(|frame| {
+    ///             !filters.iter().any(|f| {
+    ///                 let name = if let Some(name) = frame.name.as_ref() {
+    ///                     name.as_str()
+    ///                 } else {
+    ///                     return true;
+    ///                 };
+    ///
+    ///                 name.starts_with(f)
+    ///             })
+    ///         });
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_frame_filter(mut self, filter: Box<FilterCallback>) -> Self {
+        self.filters.push(FrameFilter {
+            name: None,
+            filter: filter.into(),
+        });
+        self
+    }
+
+    /// Add a custom filter to the set of frame filters, under a name identifying the frames
+    /// it hides
+    ///
+    /// # Details
+    ///
+    /// The name is shown on the "N frames hidden" markers of the frames the filter hid with
+    /// `show_filter_provenance`. The default filters are named `std` and `anyhow`, frames of
+    /// closures hidden with `ClosureDisplay::Hidden` are attributed to `closures`, and frames
+    /// hidden by filters added with `add_frame_filter` to `unnamed`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder};
+    ///
+    /// #[inline(never)]
+    /// fn hidden_leaf() -> Error {
+    ///     anyhow!("the cache could not be warmed")
+    /// }
+    ///
+    /// #[inline(never)]
+    /// fn shown_middle() -> Error {
+    ///     hidden_leaf()
+    /// }
+    ///
+    /// #[inline(never)]
+    /// fn hidden_root() -> Error {
+    ///     shown_middle()
+    /// }
+    ///
+    /// let named = |needle: &'static str| -> Box<color_anyhow::config::FilterCallback> {
+    ///     Box::new(move |frames| {
+    ///         frames.retain(|frame| !matches!(&frame.name, Some(name) if name.contains(needle)))
+    ///     })
+    /// };
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .add_named_frame_filter("leaves", named("hidden_leaf"))
+    ///     .add_named_frame_filter("roots", named("hidden_root"))
+    ///     .show_filter_provenance(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", hidden_root());
+    /// let leaf = report.find("⋮ 1 frame hidden by leaves ⋮").unwrap();
+    /// let middle = report.find("shown_middle").unwrap();
+    /// let root = report.find("⋮ 1 frame hidden by roots ⋮").unwrap();
+    /// assert!(leaf < middle && middle < root);
+    /// ```
+    pub fn add_named_frame_filter(mut self, name: &str, filter: Box<FilterCallback>) -> Self {
+        self.filters.push(FrameFilter {
+            name: Some(name.to_owned()),
+            filter: filter.into(),
+        });
+        self
+    }
+
+    /// Collapse the backtrace frames hidden by the frame filters into "N frames hidden"
+    /// markers, defaults to true
+    ///
+    /// # Details
+    ///
+    /// When disabled the hidden frames of panic and error report backtraces are printed
+    /// dimmed in between the others instead, unlike `COLORBT_SHOW_HIDDEN`, which disables
+    /// the frame filters altogether. A single report can be expanded the same way with
+    /// `ReportOptions::expand_hidden_frames`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder};
+    ///
+    /// #[inline(never)]
+    /// fn warm_cache() -> Error {
+    ///     anyhow!("the cache could not be warmed")
+    /// }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .add_frame_filter(Box::new(|frames| {
+    ///         frames.retain(|frame| !matches!(&frame.name, Some(name) if name.contains("warm_cache")))
+    ///     }))
+    ///     .collapse_hidden_frames(false)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", warm_cache());
+    /// assert!(report.contains("warm_cache"));
+    /// assert!(!report.contains("hidden"));
+    /// ```
+    #[doc(alias = "collapse_hidden")]
+    pub fn collapse_hidden_frames(mut self, cond: bool) -> Self {
+        self.collapse_hidden_frames = cond;
+        self
+    }
+
+    /// Name the frame filters that hid the frames of each "N frames hidden" marker,
+    /// defaults to false
+    ///
+    /// # Details
+    ///
+    /// The names are the ones the filters were added with by `add_named_frame_filter`, in the
+    /// order of the frames they hid, e.g. `⋮ 4 frames hidden by anyhow, std ⋮`. A frame
+    /// hidden by several filters is attributed to the first of them. The setting applies to
+    /// panic and error report backtraces, and can be overridden for a single report with
+    /// `ReportOptions::show_filter_provenance`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::HookBuilder;
+    ///
+    /// let builder = HookBuilder::default().show_filter_provenance(true);
+    /// assert!(builder.show_filter_provenance_enabled());
+    /// builder.install().unwrap();
+    /// ```
+    pub fn show_filter_provenance(mut self, cond: bool) -> Self {
+        self.show_filter_provenance = cond;
+        self
+    }
+
+    /// Replace the rendering of the frames of error report backtraces with a custom formatter
+    ///
+    /// # Details
+    ///
+    /// The formatter is given the captured backtrace and writes the body of the `BACKTRACE`
+    /// section, which is still placed under the standard banner and indented like the default
+    /// rendering. Frame filters and closure display settings aren't applied to its output. If
+    /// the formatter returns an error the default rendering is used instead, preceded by a
+    /// dimmed note.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    ///
+    /// HookBuilder::default()
+    ///     .backtrace_formatter(Box::new(|_backtrace, f| {
+    ///         writeln!(f, " 0: symbolicated::frame")?;
+    ///         writeln!(f, "    at build-id 5f0c:src/main.rs:12")
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("config could not be loaded"));
+    /// assert!(report.contains(
+    ///     " BACKTRACE ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n   0: symbolicated::frame\n      at build-id 5f0c:src/main.rs:12"
+    /// ));
+    /// ```
+    pub fn backtrace_formatter(mut self, formatter: Box<BacktraceFormatterCallback>) -> Self {
+        self.backtrace_formatter = Some(formatter.into());
+        self
+    }
+
+    /// Replace the rendering of the frames of panic report backtraces with a custom formatter
+    ///
+    /// # Details
+    ///
+    /// Works the same way as [`backtrace_formatter`] does for error reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{ColorChoice, HookBuilder};
+    /// use std::process::Command;
+    ///
+    /// if std::env::var("PANIC_CHILD").is_ok() {
+    ///     HookBuilder::default()
+    ///         .color_choice(ColorChoice::Never)
+    ///         .panic_backtrace_formatter(Box::new(|_backtrace, f| {
+    ///             writeln!(f, " 0: symbolicated::frame")
+    ///         }))
+    ///         .install()
+    ///         .unwrap();
+    ///
+    ///     panic!("the disk is full");
+    /// }
+    ///
+    /// let output = Command::new(std::env::current_exe().unwrap())
+    ///     .env("PANIC_CHILD", "1")
+    ///     .env("RUST_BACKTRACE", "1")
+    ///     .output()
+    ///     .unwrap();
+    /// let report = String::from_utf8(output.stdout).unwrap();
+    ///
+    /// assert!(report.contains("Message:  the disk is full"));
+    /// assert!(report.contains(" BACKTRACE ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n 0: symbolicated::frame\n"));
+    /// ```
+    ///
+    /// [`backtrace_formatter`]: #method.backtrace_formatter
+    pub fn panic_backtrace_formatter(mut self, formatter: Box<BacktraceFormatterCallback>) -> Self {
+        self.panic_backtrace_formatter = Some(formatter.into());
+        self
+    }
+
+    /// Don't render the backtrace of error reports whose chain contains an error of type `E`
+    ///
+    /// # Details
+    ///
+    /// Use this for expected errors, such as validation errors, for which a backtrace is
+    /// only noise. The whole chain is checked, so the backtrace is also hidden when the
+    /// registered error is wrapped with context, or when it is the source of another
+    /// error that isn't registered: a single registered error anywhere in the chain marks
+    /// the report as expected. Backtraces of reports attached with
+    /// [`Section::error_report`] are checked against their own chain.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, config::HookBuilder};
+    ///
+    /// #[derive(Debug)]
+    /// struct ValidationError;
+    ///
+    /// impl std::fmt::Display for ValidationError {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         f.write_str("the name must not be empty")
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for ValidationError {}
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .suppress_backtrace_for::<ValidationError>()
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let expected = Err::<(), _>(Error::new(ValidationError))
+    ///     .context("could not create the user")
+    ///     .unwrap_err();
+    /// let unexpected = anyhow!("the database connection was lost");
+    ///
+    /// assert!(!format!("{:?}", expected).contains("BACKTRACE"));
+    /// assert!(format!("{:?}", unexpected).contains("BACKTRACE"));
+    /// ```
+    ///
+    /// [`Section::error_report`]: ../trait.Section.html#tymethod.error_report
+    pub fn suppress_backtrace_for<E>(mut self) -> Self
+    where
+        E: std::error::Error + 'static,
+    {
+        let ty = ErrorType::of::<E>();
+        if self
+            .backtrace_suppressed_types
+            .iter()
+            .all(|other| other.id != ty.id)
+        {
+            self.backtrace_suppressed_types.push(ty);
+        }
+        self
+    }
+
+    /// Leave the backtrace out of rendered error reports, defaults to `false`
+    ///
+    /// # Details
+    ///
+    /// Backtraces are still captured as configured with `RUST_LIB_BACKTRACE`, and
+    /// remain available through [`extract`], but the `BACKTRACE` sections of reports
+    /// and of the reports attached to them are skipped. Nothing is symbolized while
+    /// rendering, which makes tests asserting on the content of reports fast and
+    /// deterministic without having to change environment variables. Panic reports
+    /// are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::default()
+    ///     .skip_backtrace_in_reports(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!("the cache is corrupted");
+    ///
+    /// assert!(color_anyhow::extract(&report).backtrace.is_some());
+    /// assert!(!format!("{:?}", report).contains("BACKTRACE"));
+    /// ```
+    ///
+    /// [`extract`]: ../fn.extract.html
+    pub fn skip_backtrace_in_reports(mut self, cond: bool) -> Self {
+        self.skip_backtrace_in_reports = cond;
+        self
+    }
+
+    /// Limit how long rendering a single error or panic report may spend on expensive
+    /// enrichment, such as reading source snippets, defaults to no limit
+    ///
+    /// # Details
+    ///
+    /// The budget is checked before each expensive step, so a step that already started
+    /// still completes. Once it is exceeded the remaining backtrace frames render without
+    /// their source snippets, marked with a dimmed `(skipped: render budget exceeded)`, and
+    /// backtraces that weren't resolved yet render as that marker alone. The chain of errors,
+    /// the sections and the help text are always rendered in full.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+    /// use std::time::Duration;
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    /// HookBuilder::default()
+    ///     .render_time_budget(Duration::from_millis(20))
+    ///     // a source file on a slow network mount
+    ///     .source_reader(Box::new(|_| {
+    ///         std::thread::sleep(Duration::from_millis(50));
+    ///         Ok("fn main() {}\n".repeat(1000))
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", anyhow!("the upload failed"));
+    ///
+    /// assert!(report.contains("the upload failed"));
+    /// assert!(report.contains("fn main() {}"));
+    /// assert!(report.contains("(skipped: render budget exceeded)"));
+    /// ```
+    pub fn render_time_budget(mut self, budget: Duration) -> Self {
+        self.render_time_budget = Some(budget);
+        self
+    }
+
+    /// Read the source files shown in the source snippets of backtrace frames with a custom
+    /// reader instead of the filesystem
+    ///
+    /// # Details
+    ///
+    /// Snippets are only shown with `RUST_BACKTRACE=full` or `RUST_LIB_BACKTRACE=full`. The
+    /// reader is called with the path of the frame's source file, an error renders the frame
+    /// without a snippet. Each file is read once per rendered backtrace, however many
+    /// of its frames point into it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::HookBuilder;
+    /// use std::path::Path;
+    ///
+    /// // sources shipped alongside the binary
+    /// HookBuilder::default()
+    ///     .source_reader(Box::new(|path| {
+    ///         let file = path.file_name().unwrap_or_default();
+    ///         std::fs::read_to_string(Path::new("/usr/share/app/src").join(file))
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Reading each file once for a deep recursion:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::{anyhow, Error}, config::HookBuilder, Handler};
+    /// use std::collections::HashSet;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static READS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    /// HookBuilder::blank()
+    ///     .source_reader(Box::new(|_| {
+    ///         READS.fetch_add(1, Ordering::SeqCst);
+    ///         Ok(String::new())
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// fn descend(depth: usize) -> Error {
+    ///     if depth == 0 {
+    ///         anyhow!("recursed too deep")
+    ///     } else {
+    ///         descend(depth - 1)
+    ///     }
+    /// }
+    ///
+    /// let report = descend(400);
+    /// let _ = format!("{:?}", report);
+    ///
+    /// let handler = report.handler().downcast_ref::<Handler>().unwrap();
+    /// let frames: Vec<_> = (1..).map_while(|idx| handler.frame(idx)).collect();
+    /// let files: HashSet<_> = frames
+    ///     .iter()
+    ///     .filter(|frame| frame.lineno.is_some())
+    ///     .filter_map(|frame| frame.filename.as_ref())
+    ///     .collect();
+    ///
+    /// assert!(frames.len() > 400);
+    /// assert_eq!(READS.load(Ordering::SeqCst), files.len());
+    /// ```
+    pub fn source_reader(mut self, reader: Box<SourceReaderCallback>) -> Self {
+        self.source_reader = Some(reader.into());
+        self
+    }
+
+    /// Call `observer` with a summary of each error report once it is rendered
+    ///
+    /// # Details
+    ///
+    /// The observer is called on every complete render of an error report, like the
+    /// counters of [`render_stats`], and only then. Rendering a report from the observer
+    /// calls it again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{BlockSet, HookBuilder}, ReportSummary};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let summaries = Arc::new(Mutex::new(Vec::<ReportSummary>::new()));
+    /// let observed = summaries.clone();
+    /// HookBuilder::default()
+    ///     .on_report(Box::new(move |summary| observed.lock().unwrap().push(summary.clone())))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// let first = anyhow!("the first upload failed");
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// let second = anyhow!("the second upload failed");
+    ///
+    /// let report = format!("{:?}", first);
+    /// let _ = format!("{:?}", second);
+    ///
+    /// let summaries = summaries.lock().unwrap();
+    /// assert_eq!(summaries.len(), 2);
+    /// assert!(summaries[0].backtrace_captured && summaries[0].backtrace_displayed);
+    /// assert!(!summaries[1].backtrace_captured && !summaries[1].backtrace_displayed);
+    /// assert_eq!(summaries[0].bytes, report.len());
+    /// assert!(summaries[0].block_bytes(BlockSet::BACKTRACE) > 0);
+    /// assert_eq!(summaries[1].block_bytes(BlockSet::BACKTRACE), 0);
+    /// ```
+    ///
+    /// The bytes of each block add up to the rendered report:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{BlockSet, HookBuilder}, ReportSummary, Section};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let summary = Arc::new(Mutex::new(None::<ReportSummary>));
+    /// let observed = summary.clone();
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .on_report(Box::new(move |summary| *observed.lock().unwrap() = Some(summary.clone())))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), _>(anyhow!("the upload failed"))
+    ///     .note("the server is in maintenance")
+    ///     .unwrap_err();
+    /// let rendered = format!("{:?}", report);
+    ///
+    /// let summary = summary.lock().unwrap().clone().unwrap();
+    /// let help = summary.block_bytes(BlockSet::HELP);
+    /// assert!(rendered[rendered.len() - help..].contains("the server is in maintenance"));
+    /// assert_eq!(summary.block_bytes(BlockSet::all()), rendered.len());
+    /// ```
+    ///
+    /// [`render_stats`]: ../fn.render_stats.html
+    pub fn on_report(mut self, observer: Box<ReportObserverCallback>) -> Self {
+        self.report_observer = Some(observer.into());
+        self
+    }
+
+    /// Also write panic reports to the writer opened by `sink`, in addition to stdout
+    ///
+    /// # Details
+    ///
+    /// `sink` is called once per panic, and each sink receives the full rendered report, e.g.
+    /// to keep a copy in a log file or send it to a telemetry service. Sinks aren't terminals,
+    /// so they receive reports without colors unless the `ColorChoice` forces colors. A sink
+    /// that fails to write doesn't keep stdout or the other sinks from receiving the report.
+    /// Reports logged by `HttpReport::log_and_respond` are written to the sinks as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{ColorChoice, HookBuilder};
+    /// use std::io::{self, Write};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Captured(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Captured {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct Disconnected;
+    ///
+    /// impl Write for Disconnected {
+    ///     fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+    ///         Err(io::ErrorKind::BrokenPipe.into())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let (log_file, telemetry) = (Captured::default(), Captured::default());
+    /// let (log_file_sink, telemetry_sink) = (log_file.clone(), telemetry.clone());
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .add_output_sink(Box::new(move || Box::new(log_file_sink.clone())))
+    ///     .add_output_sink(Box::new(|| Box::new(Disconnected)))
+    ///     .add_output_sink(Box::new(move || Box::new(telemetry_sink.clone())))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    ///
+    /// for sink in [log_file, telemetry].iter() {
+    ///     let report = String::from_utf8(sink.0.lock().unwrap().clone()).unwrap();
+    ///     assert!(report.starts_with("The application panicked (crashed).\n"));
+    ///     assert!(report.contains("the index is corrupt"));
+    ///     assert!(!report.contains('\x1b'));
+    /// }
+    /// ```
+    pub fn add_output_sink(mut self, sink: Box<OutputSinkCallback>) -> Self {
+        self.output_sinks.push(sink.into());
+        self
+    }
+
+    /// Read the current time from a custom clock, defaults to the system's clocks
+    ///
+    /// # Details
+    ///
+    /// The clock is used for every time read by the hooks, such as span entry timestamps,
+    /// the process uptime and the render time budget. Tests can install a
+    /// [`ManualClock`](../struct.ManualClock.html), available with the `test-util` feature,
+    /// to control the time shown in reports.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::HookBuilder, ReportClock};
+    /// use std::sync::Arc;
+    /// use std::time::{Instant, SystemTime};
+    ///
+    /// // a clock that is kept in sync with a remote time source
+    /// struct SyncedClock;
+    ///
+    /// impl ReportClock for SyncedClock {
+    ///     fn now(&self) -> SystemTime {
+    ///         SystemTime::now()
+    ///     }
+    ///
+    ///     fn instant(&self) -> Instant {
+    ///         Instant::now()
+    ///     }
+    /// }
+    ///
+    /// let builder = HookBuilder::default().clock(Arc::new(SyncedClock));
+    /// assert!(builder.configured_clock().is_some());
+    /// builder.install().unwrap();
+    /// ```
+    pub fn clock(mut self, clock: Arc<dyn ReportClock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Render numbers like the counts of hidden backtrace frames with a thousands separator,
+    /// defaults to no separator
+    ///
+    /// # Details
+    ///
+    /// The format is given as the number one thousand written with the separator, e.g.
+    /// `"1,000"`, `"1.000"`, `"1 000"` or `"1'000"`, or `"1000"` for no separator. Invalid
+    /// formats are ignored and the default is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder};
+    ///
+    /// #[inline(never)]
+    /// fn descend(depth: usize) -> Error {
+    ///     if depth == 0 {
+    ///         anyhow!("the tree is too deep")
+    ///     } else {
+    ///         descend(depth - 1)
+    ///     }
+    /// }
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// HookBuilder::blank()
+    ///     .number_format("1,000")
+    ///     .add_frame_filter(Box::new(|frames| {
+    ///         frames.retain(|frame| !matches!(&frame.name, Some(name) if name.contains("descend")))
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = format!("{:?}", descend(1500));
+    /// assert!(report.contains("⋮ 1,501 frames hidden ⋮"));
+    ///
+    /// let builder = HookBuilder::blank().number_format("one thousand");
+    /// assert_eq!(builder.configured_number_format(), None);
+    /// ```
+    pub fn number_format(mut self, format: &str) -> Self {
+        if let Some(format) = NumberFormat::parse(format) {
+            self.number_format = Some(format);
+        }
+        self
+    }
+
+    /// Render the timestamps of `SpanTrace` frames in the given format, defaults to RFC 3339
+    ///
+    /// # Details
+    ///
+    /// The format is literal text with the specifiers `%Y` (year), `%m` (month), `%d` (day),
+    /// `%H` (hour), `%M` (minute), `%S` (second), `%3f` (milliseconds) and `%%` (a literal
+    /// `%`). Timestamps are always in UTC. Invalid formats, with any other specifier, are
+    /// ignored and the default is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::HookBuilder, ReportClock, SpanTimestampLayer};
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// // a clock stopped at 2021-03-04 05:06:07.089 UTC
+    /// struct StoppedClock;
+    ///
+    /// impl ReportClock for StoppedClock {
+    ///     fn now(&self) -> SystemTime {
+    ///         UNIX_EPOCH + Duration::from_millis(1_614_834_367_089)
+    ///     }
+    ///
+    ///     fn instant(&self) -> Instant {
+    ///         Instant::now()
+    ///     }
+    /// }
+    ///
+    /// tracing_subscriber::registry()
+    ///     .with(ErrorLayer::default())
+    ///     .with(SpanTimestampLayer::default())
+    ///     .init();
+    ///
+    /// HookBuilder::default()
+    ///     .clock(Arc::new(StoppedClock))
+    ///     .spantrace_timestamps(true)
+    ///     .date_format("%d.%m.%Y %H:%M:%S")
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = tracing::info_span!("sync").in_scope(|| anyhow!("the sync failed"));
+    /// assert!(format!("{:?}", report).contains("entered at 04.03.2021 05:06:07"));
+    ///
+    /// let builder = HookBuilder::default().date_format("%A %B");
+    /// assert_eq!(builder.configured_date_format(), None);
+    /// ```
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn date_format(mut self, format: &str) -> Self {
+        if let Some(format) = DateFormat::parse(format) {
+            self.date_format = Some(format);
+        }
+        self
+    }
+
+    /// Choose what is installed when another crate has already installed its own report
+    /// handler, defaults to `Conflict::RollBack`
+    ///
+    /// # Details
+    ///
+    /// Once a panic hook has been installed this way, later installs return
+    /// `InstallError::AlreadyInstalled`, as the configuration it renders with can't be replaced.
+    ///
+    /// # Examples
+    ///
+    /// Rolling back leaves the default panic hook in place:
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{Conflict, HookBuilder, InstallError};
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// #
+    /// # struct OtherHandler;
+    /// #
+    /// # impl color_anyhow::anyhow::ReportHandler for OtherHandler {
+    /// #     fn debug(
+    /// #         &self,
+    /// #         error: &(dyn std::error::Error + 'static),
+    /// #         f: &mut std::fmt::Formatter<'_>,
+    /// #     ) -> std::fmt::Result {
+    /// #         write!(f, "{}", error)
+    /// #     }
+    /// # }
+    /// #
+    /// # #[derive(Clone, Default)]
+    /// # struct Captured(Arc<Mutex<Vec<u8>>>);
+    /// #
+    /// # impl Write for Captured {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    /// #         self.0.lock().unwrap().write(buf)
+    /// #     }
+    /// #
+    /// #     fn flush(&mut self) -> io::Result<()> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// // a dependency installed its own report handler first
+    /// color_anyhow::anyhow::set_hook(Box::new(|_| Box::new(OtherHandler))).unwrap();
+    ///
+    /// let panics = Captured::default();
+    /// let sink = panics.clone();
+    /// let installed = HookBuilder::default()
+    ///     .on_conflict(Conflict::RollBack)
+    ///     .add_output_sink(Box::new(move || Box::new(sink.clone())))
+    ///     .try_install();
+    /// assert_eq!(installed, Err(InstallError::ForeignHandler));
+    /// assert!(!color_anyhow::is_installed());
+    ///
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    /// assert!(panics.0.lock().unwrap().is_empty());
+    ///
+    /// // nothing of the rolled back configuration is kept for the next install
+    /// let retried = Captured::default();
+    /// let sink = retried.clone();
+    /// let installed = HookBuilder::default()
+    ///     .on_conflict(Conflict::PanicHookOnly)
+    ///     .add_output_sink(Box::new(move || Box::new(sink.clone())))
+    ///     .try_install();
+    /// assert_eq!(installed, Ok(()));
+    ///
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    /// assert!(panics.0.lock().unwrap().is_empty());
+    /// assert!(!retried.0.lock().unwrap().is_empty());
+    /// ```
+    ///
+    /// Otherwise the panic hook is installed, with or without reporting the conflict:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, Conflict, HookBuilder, InstallError}};
+    /// # use std::io::{self, Write};
+    /// # use std::sync::{Arc, Mutex};
+    /// #
+    /// # struct OtherHandler;
+    /// #
+    /// # impl color_anyhow::anyhow::ReportHandler for OtherHandler {
+    /// #     fn debug(
+    /// #         &self,
+    /// #         error: &(dyn std::error::Error + 'static),
+    /// #         f: &mut std::fmt::Formatter<'_>,
+    /// #     ) -> std::fmt::Result {
+    /// #         write!(f, "{}", error)
+    /// #     }
+    /// # }
+    /// #
+    /// # #[derive(Clone, Default)]
+    /// # struct Captured(Arc<Mutex<Vec<u8>>>);
+    /// #
+    /// # impl Write for Captured {
+    /// #     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    /// #         self.0.lock().unwrap().write(buf)
+    /// #     }
+    /// #
+    /// #     fn flush(&mut self) -> io::Result<()> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// color_anyhow::anyhow::set_hook(Box::new(|_| Box::new(OtherHandler))).unwrap();
+    ///
+    /// let panics = Captured::default();
+    /// let sink = panics.clone();
+    /// let builder = HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .add_output_sink(Box::new(move || Box::new(sink.clone())));
+    ///
+    /// let installed = builder.clone().on_conflict(Conflict::Error).try_install();
+    /// assert_eq!(installed, Err(InstallError::ForeignHandler));
+    /// assert!(color_anyhow::uptime().is_some());
+    ///
+    /// // panics are rendered by color-anyhow, errors by the other crate's handler
+    /// let _ = std::thread::spawn(|| panic!("the index is corrupt")).join();
+    /// let report = String::from_utf8(panics.0.lock().unwrap().clone()).unwrap();
+    /// assert!(report.starts_with("The application panicked (crashed).\n"));
+    /// assert_eq!(format!("{:?}", anyhow!("the sync failed")), "the sync failed");
+    /// assert!(!color_anyhow::is_installed());
+    ///
+    /// // the installed panic hook keeps this builder's configuration
+    /// let installed = builder.on_conflict(Conflict::PanicHookOnly).try_install();
+    /// assert_eq!(installed, Err(InstallError::AlreadyInstalled));
+    /// ```
+    pub fn on_conflict(mut self, policy: Conflict) -> Self {
+        self.on_conflict = policy;
+        self
+    }
+
+    /// Install the given Hook as the global error report hook
+    pub fn install(self) -> Result<(), crate::anyhow::Error> {
+        self.try_install()?;
+        Ok(())
+    }
+
+    /// Install the given Hook as the global error report hook along with a global
+    /// `tracing` subscriber that captures `SpanTrace`s
+    ///
+    /// # Details
+    ///
+    /// The subscriber is a `tracing_subscriber::registry()` with an `ErrorLayer`,
+    /// an `EnvFilter` read from `RUST_LOG` that falls back to `default_directives`
+    /// when `RUST_LOG` is unset, and a `fmt` layer. A `SpanTimestampLayer` is
+    /// added as well when `spantrace_timestamps` is enabled. The hooks are
+    /// installed first, so failing to set the subscriber because another global
+    /// subscriber is already set is reported as a regular error report.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder};
+    /// use tracing::instrument;
+    ///
+    /// HookBuilder::default()
+    ///     .install_with_tracing("warn,rust_out=info")
+    ///     .unwrap();
+    ///
+    /// #[instrument]
+    /// fn load_config() -> Error {
+    ///     anyhow!("config could not be loaded")
+    /// }
+    ///
+    /// let report = format!("{:?}", load_config());
+    /// assert!(report.contains("SPANTRACE"));
+    /// assert!(report.contains("load_config"));
+    ///
+    /// // only one global subscriber can be set
+    /// let error = HookBuilder::default().install_with_tracing("info").unwrap_err();
+    /// assert!(error.to_string().contains("already installed"));
+    /// ```
+    #[cfg(feature = "tracing-init")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing-init")))]
+    pub fn install_with_tracing(
+        self,
+        default_directives: &str,
+    ) -> Result<(), crate::anyhow::Error> {
+        use crate::anyhow::Context;
+        use tracing_error::ErrorLayer;
+        use tracing_subscriber::prelude::*;
+        use tracing_subscriber::{fmt, EnvFilter};
+
+        let timestamps = if self.spantrace_timestamps {
+            Some(crate::SpanTimestampLayer::default())
+        } else {
+            None
+        };
+
+        let filter_layer = match EnvFilter::try_from_default_env() {
+            Ok(filter) => filter,
+            Err(_) => EnvFilter::try_new(default_directives).with_context(|| {
+                format!(
+                    "invalid default tracing directives {:?}",
+                    default_directives
+                )
+            })?,
+        };
+
+        self.install()?;
+
+        tracing_subscriber::registry()
+            .with(filter_layer)
+            .with(fmt::layer().with_target(false))
+            .with(ErrorLayer::default())
+            .with(timestamps)
+            .try_init()
+            .context("could not set the tracing subscriber as one was already installed")
+    }
+
+    /// Install the given Hook as the global error report hook, returning a
+    /// typed error describing why installation failed
+    ///
+    /// # Details
+    ///
+    /// The report hook is installed before the panic hook, so a report handler installed by
+    /// another crate is detected before anything is replaced. What is installed then is
+    /// chosen with `on_conflict`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{HookBuilder, InstallError};
+    ///
+    /// let builder = HookBuilder::default();
+    /// let for_tests = builder.clone().capture_span_trace_by_default(false);
+    ///
+    /// builder.try_install().unwrap();
+    /// assert_eq!(for_tests.try_install(), Err(InstallError::AlreadyInstalled));
+    /// ```
+    ///
+    /// A report handler installed by another crate is reported separately:
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{HookBuilder, InstallError};
+    /// use std::fmt;
+    ///
+    /// struct OtherHandler;
+    ///
+    /// impl color_anyhow::anyhow::ReportHandler for OtherHandler {
+    ///     fn debug(
+    ///         &self,
+    ///         error: &(dyn std::error::Error + 'static),
+    ///         f: &mut fmt::Formatter<'_>,
+    ///     ) -> fmt::Result {
+    ///         write!(f, "{}", error)
+    ///     }
+    /// }
+    ///
+    /// color_anyhow::anyhow::set_hook(Box::new(|_| Box::new(OtherHandler))).unwrap();
+    ///
+    /// assert_eq!(
+    ///     HookBuilder::default().try_install(),
+    ///     Err(InstallError::ForeignHandler)
+    /// );
+    /// ```
+    pub fn try_install(self) -> Result<(), InstallError> {
+        // serialize installs so concurrent callers observe either nothing or
+        // the fully installed hooks
+        let _guard = INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        // an earlier install that kept just the panic hook has already set the
+        // printer, which can't be replaced, so this builder would be ignored
+        if INSTALLED.load(Ordering::Acquire) || crate::CONFIG.get().is_some() {
+            return Err(InstallError::AlreadyInstalled);
+        }
+
+        // the printer is only set once a hook that reads it is installed, so a
+        // rolled back install leaves it free for the next one
+        let on_conflict = self.on_conflict;
+        let (panic_hook, report_hook) = self.into_hooks();
+
+        if crate::anyhow::set_hook(Box::new(move |e| Box::new(report_hook.default(e)))).is_err() {
+            // the report hook is only ours once `INSTALLED` is set, so later installs retry it
+            return match on_conflict {
+                Conflict::RollBack => Err(InstallError::ForeignHandler),
+                Conflict::PanicHookOnly => {
+                    let _ = crate::CONFIG.set(panic_hook);
+                    install_panic_hook();
+                    crate::uptime::record_install();
+                    Ok(())
+                }
+                Conflict::Error => {
+                    let _ = crate::CONFIG.set(panic_hook);
+                    install_panic_hook();
+                    crate::uptime::record_install();
+                    Err(InstallError::ForeignHandler)
+                }
+            };
+        }
+
+        let _ = crate::CONFIG.set(panic_hook);
+        install_panic_hook();
+        crate::uptime::record_install();
+        INSTALLED.store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Returns the number of frame filters currently configured
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::config::{ColorChoice, HookBuilder};
+    ///
+    /// let builder = HookBuilder::new().color_choice(ColorChoice::Never);
+    /// let tweaked = builder.clone().add_frame_filter(Box::new(|_| {}));
+    ///
+    /// assert_eq!(builder.frame_filter_count(), 2);
+    /// assert_eq!(tweaked.frame_filter_count(), 3);
+    /// assert!(tweaked.capture_span_trace_by_default_enabled());
+    /// assert!(!tweaked.redact_user_paths_enabled());
+    /// assert_eq!(tweaked.configured_color_choice(), ColorChoice::Never);
+    /// ```
+    pub fn frame_filter_count(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Returns whether hidden backtrace frames are collapsed into "N frames hidden" markers
+    pub fn collapse_hidden_frames_enabled(&self) -> bool {
+        self.collapse_hidden_frames
+    }
+
+    /// Returns whether the "N frames hidden" markers name the filters that hid the frames
+    pub fn show_filter_provenance_enabled(&self) -> bool {
+        self.show_filter_provenance
+    }
+
+    /// Returns whether `SpanTraces` are captured by default
+    pub fn capture_span_trace_by_default_enabled(&self) -> bool {
+        self.capture_span_trace_by_default
+    }
+
+    /// Returns whether the current user's home directory and username are redacted
+    pub fn redact_user_paths_enabled(&self) -> bool {
+        self.redact_user_paths
+    }
+
+    /// Returns the configured `ColorChoice`
+    pub fn configured_color_choice(&self) -> ColorChoice {
+        self.color_choice
+    }
+
+    /// Returns what is installed when another crate has already installed its own report handler
+    pub fn configured_on_conflict(&self) -> Conflict {
+        self.on_conflict
+    }
+
+    /// Returns whether identical error sections are grouped
+    pub fn group_errors_enabled(&self) -> bool {
+        self.group_errors
+    }
+
+    /// Returns whether identical backtraces of attached reports are shared
+    pub fn share_identical_backtraces_enabled(&self) -> bool {
+        self.share_identical_backtraces
+    }
+
+    /// Returns whether error sections capture their own backtrace
+    pub fn capture_error_section_backtraces_enabled(&self) -> bool {
+        self.capture_error_section_backtraces
+    }
+
+    /// Returns whether collected warnings are attached to error reports
+    pub fn attach_collected_warnings_enabled(&self) -> bool {
+        self.attach_collected_warnings
+    }
+
+    /// Returns the configured `ClosureDisplay`
+    pub fn configured_closure_display(&self) -> ClosureDisplay {
+        self.closure_display
+    }
+
+    /// Returns whether frames of `async fn` state machines are rendered as the function's name
+    pub fn simplify_async_frames_enabled(&self) -> bool {
+        self.simplify_async_frames
+    }
+
+    /// Returns the configured `LineEnding`
+    pub fn configured_line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Returns whether the fingerprint of each error report is shown
+    pub fn display_fingerprint_enabled(&self) -> bool {
+        self.display_fingerprint
+    }
+
+    /// Returns whether the status codes attached with `Section::status_hint` are shown
+    pub fn display_status_hints_enabled(&self) -> bool {
+        self.display_status_hints
+    }
+
+    /// Returns whether the codes of coded notes and suggestions are shown
+    pub fn display_help_codes_enabled(&self) -> bool {
+        self.display_help_codes
+    }
+
+    /// Returns whether hints for the environment variables that add missing traces are shown
+    pub fn display_env_hints_enabled(&self) -> bool {
+        self.display_env_hints
+    }
+
+    /// Returns whether the process uptime is shown in panic reports
+    pub fn display_uptime_enabled(&self) -> bool {
+        self.display_uptime
+    }
+
+    /// Returns whether the process uptime is shown in error reports
+    pub fn display_uptime_in_error_reports_enabled(&self) -> bool {
+        self.display_uptime_in_error_reports
+    }
+
+    /// Returns the configured information identifying the build of the application
+    pub fn configured_build_info(&self) -> Option<crate::BuildInfo> {
+        self.build_info
+    }
+
+    /// Returns whether the build of the application is identified in error reports
+    pub fn display_build_info_in_error_reports_enabled(&self) -> bool {
+        self.display_build_info_in_error_reports
+    }
+
+    /// Returns the configured theme of reports
+    pub fn configured_theme(&self) -> Option<&crate::Theme> {
+        self.theme.as_ref()
+    }
+
+    /// Returns whether error reports begin with a table of contents
+    pub fn table_of_contents_enabled(&self) -> bool {
+        self.table_of_contents
+    }
+
+    /// Returns whether the blocks of error reports are rendered in reverse
+    pub fn reverse_layout_enabled(&self) -> bool {
+        self.reverse_layout
+    }
+
+    /// Returns whether reports show a unique identifier
+    pub fn display_report_id_enabled(&self) -> bool {
+        self.display_report_id
+    }
+
+    /// Returns where and how artifacts of panics are written, if they are
+    pub fn configured_panic_artifact(&self) -> Option<&crate::ArtifactSpec> {
+        self.panic_artifact.as_ref()
+    }
+
+    /// Returns whether the backtrace is hidden for reports of errors of type `E`
+    pub fn backtrace_suppressed_for<E: 'static>(&self) -> bool {
+        self.backtrace_suppressed_types
+            .iter()
+            .any(|ty| ty.id == TypeId::of::<E>())
+    }
+
+    /// Returns whether backtraces are left out of rendered error reports
+    pub fn skip_backtrace_in_reports_enabled(&self) -> bool {
+        self.skip_backtrace_in_reports
+    }
+
+    /// Returns the configured `ChainStyle`
+    pub fn configured_chain_style(&self) -> ChainStyle {
+        self.chain_style
+    }
+
+    /// Returns the configured leading label, `None` if it was never configured and
+    /// `Some(None)` if the leading line break is removed
+    pub fn configured_chain_label(&self) -> Option<Option<&str>> {
+        self.chain_label.as_ref().map(Option::as_deref)
+    }
+
+    /// Returns the configured header line of error reports
+    pub fn configured_report_header(&self) -> Option<&str> {
+        self.report_header.as_deref()
+    }
+
+    /// Returns the configured footer of error reports
+    pub fn configured_support_footer(&self) -> Option<&str> {
+        self.support_footer.as_deref()
+    }
+
+    /// Returns whether a banner with the severity of the report is rendered
+    pub fn severity_banner_enabled(&self) -> bool {
+        self.severity_banner
+    }
+
+    /// Returns the configured maximum width of source paths in traces
+    pub fn configured_max_path_width(&self) -> Option<usize> {
+        self.max_path_width
+    }
+
+    /// Returns the configured width of error reports
+    pub fn configured_width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Returns the configured maximum number of error sections rendered
+    pub fn configured_max_error_sections(&self) -> Option<usize> {
+        self.max_error_sections
+    }
+
+    /// Returns whether error sections already in the chain of errors are skipped
+    pub fn dedup_error_sections_against_chain_enabled(&self) -> bool {
+        self.dedup_error_sections_against_chain
+    }
+
+    /// Returns whether reports are marked as reported once rendered
+    pub fn auto_mark_reported_enabled(&self) -> bool {
+        self.auto_mark_reported
+    }
+
+    /// Returns the url of the runbook registered for `category`
+    pub fn configured_runbook(&self, category: &str) -> Option<&str> {
+        self.runbooks
+            .iter()
+            .find(|(other, _)| other == category)
+            .map(|(_, url)| url.as_str())
+    }
+
+    /// Returns whether backtrace frames are annotated with their crate and its version
+    pub fn annotate_frame_crate_versions_enabled(&self) -> bool {
+        self.annotate_frame_crate_versions
+    }
+
+    /// Returns the fraction of frames without symbols at which backtraces are rendered as
+    /// addresses
+    pub fn configured_stripped_symbols_threshold(&self) -> f64 {
+        self.stripped_symbols_threshold
+    }
+
+    /// Returns the note rendered above backtraces rendered as addresses
+    pub fn configured_stripped_backtrace_note(&self) -> &str {
+        &self.stripped_backtrace_note
+    }
+
+    /// Returns the configured time budget for rendering a report
+    pub fn configured_render_time_budget(&self) -> Option<Duration> {
+        self.render_time_budget
+    }
+
+    /// Returns the configured clock, or `None` if the system's clocks are used
+    pub fn configured_clock(&self) -> Option<&Arc<dyn ReportClock>> {
+        self.clock.as_ref()
+    }
+
+    /// Returns the configured number format, or `None` if numbers have no separator
+    pub fn configured_number_format(&self) -> Option<&str> {
+        self.number_format.as_ref().map(NumberFormat::pattern)
+    }
+
+    /// Returns the configured timestamp format, or `None` if timestamps are in RFC 3339
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn configured_date_format(&self) -> Option<&str> {
+        self.date_format.as_ref().map(DateFormat::format)
+    }
+
+    /// Returns whether span entry timestamps are shown on `SpanTrace` frames
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn spantrace_timestamps_enabled(&self) -> bool {
+        self.spantrace_timestamps
+    }
+
+    /// Returns the configured `SpanTraceSelfField`
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn configured_spantrace_self_field(&self) -> &SpanTraceSelfField {
+        &self.spantrace_self_field
+    }
+
+    /// Returns whether consecutive `SpanTrace` frames from the same target are grouped
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn collapse_same_target_spans_enabled(&self) -> bool {
+        self.collapse_same_target_spans
+    }
+
+    /// Returns whether backtrace frames are annotated with the spans they correlate with
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub fn correlate_traces_enabled(&self) -> bool {
+        self.correlate_traces
+    }
+
+    /// Add the default set of filters to this `HookBuilder`'s configuration
+    pub fn add_default_filters(self) -> Self {
+        self.add_named_frame_filter("std", Box::new(default_frame_filter))
+            .add_named_frame_filter("anyhow", Box::new(anyhow_frame_filters))
     }
 
     pub(crate) fn into_hooks(self) -> (PanicHook, ReportHook) {
         let panic_hook = PanicHook {
-            filters: self.filters.into_iter().map(Into::into).collect(),
+            filters: self.filters,
+            collapse_hidden_frames: self.collapse_hidden_frames,
+            show_filter_provenance: self.show_filter_provenance,
             capture_span_trace_by_default: self.capture_span_trace_by_default,
+            user_paths: if self.redact_user_paths {
+                UserPaths::detect()
+            } else {
+                None
+            },
+            color_choice: self.color_choice,
+            group_errors: self.group_errors,
+            share_identical_backtraces: self.share_identical_backtraces,
+            capture_error_section_backtraces: self.capture_error_section_backtraces,
+            closure_display: self.closure_display,
+            simplify_async_frames: self.simplify_async_frames,
+            line_ending: self.line_ending,
+            display_fingerprint: self.display_fingerprint,
+            display_status_hints: self.display_status_hints,
+            display_help_codes: self.display_help_codes,
+            display_env_hints: self.display_env_hints,
+            display_uptime: self.display_uptime,
+            display_uptime_in_error_reports: self.display_uptime_in_error_reports,
+            build_info: self.build_info,
+            display_build_info_in_error_reports: self.display_build_info_in_error_reports,
+            theme: self.theme,
+            table_of_contents: self.table_of_contents,
+            reverse_layout: self.reverse_layout,
+            display_report_id: self.display_report_id,
+            panic_artifact: self.panic_artifact,
+            backtrace_formatter: self.backtrace_formatter,
+            panic_backtrace_formatter: self.panic_backtrace_formatter,
+            backtrace_suppressed_types: self.backtrace_suppressed_types,
+            skip_backtrace_in_reports: self.skip_backtrace_in_reports,
+            chain_style: self.chain_style,
+            chain_label: self.chain_label,
+            report_header: self.report_header,
+            support_footer: self.support_footer,
+            severity_banner: self.severity_banner,
+            max_path_width: self.max_path_width,
+            width: self.width,
+            max_error_sections: self.max_error_sections,
+            dedup_error_sections_against_chain: self.dedup_error_sections_against_chain,
+            auto_mark_reported: self.auto_mark_reported,
+            annotate_frame_crate_versions: self.annotate_frame_crate_versions,
+            stripped_symbols_threshold: self.stripped_symbols_threshold,
+            stripped_backtrace_note: self.stripped_backtrace_note,
+            runbooks: self.runbooks,
+            render_time_budget: self.render_time_budget,
+            source_reader: self.source_reader,
+            report_observer: self.report_observer,
+            output_sinks: self.output_sinks,
+            clock: self.clock,
+            number_format: self.number_format,
+            #[cfg(feature = "capture-spantrace")]
+            spantrace_timestamps: self.spantrace_timestamps,
+            #[cfg(feature = "capture-spantrace")]
+            date_format: self.date_format,
+            #[cfg(feature = "capture-spantrace")]
+            spantrace_self_field: self.spantrace_self_field,
+            #[cfg(feature = "capture-spantrace")]
+            collapse_same_target_spans: self.collapse_same_target_spans,
+            #[cfg(feature = "capture-spantrace")]
+            correlate_traces: self.correlate_traces,
+            #[cfg(feature = "highlight-patterns")]
+            highlight_patterns: self.highlight_patterns,
         };
 
         let report_hook = ReportHook {
             capture_span_trace_by_default: self.capture_span_trace_by_default,
+            attach_collected_warnings: self.attach_collected_warnings,
+            conditional_sections: self.conditional_sections,
+            #[cfg(feature = "capture-spantrace")]
+            spantrace_timestamps: self.spantrace_timestamps,
         };
 
         (panic_hook, report_hook)
@@ -345,6 +4806,108 @@ impl Default for HookBuilder {
     }
 }
 
+impl fmt::Debug for HookBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct("HookBuilder");
+        builder
+            .field("filters", &self.filters.len())
+            .field("collapse_hidden_frames", &self.collapse_hidden_frames)
+            .field("show_filter_provenance", &self.show_filter_provenance)
+            .field(
+                "capture_span_trace_by_default",
+                &self.capture_span_trace_by_default,
+            )
+            .field("redact_user_paths", &self.redact_user_paths)
+            .field("color_choice", &self.color_choice)
+            .field("on_conflict", &self.on_conflict)
+            .field("group_errors", &self.group_errors)
+            .field(
+                "share_identical_backtraces",
+                &self.share_identical_backtraces,
+            )
+            .field(
+                "capture_error_section_backtraces",
+                &self.capture_error_section_backtraces,
+            )
+            .field("attach_collected_warnings", &self.attach_collected_warnings)
+            .field("closure_display", &self.closure_display)
+            .field("simplify_async_frames", &self.simplify_async_frames)
+            .field("line_ending", &self.line_ending)
+            .field("display_fingerprint", &self.display_fingerprint)
+            .field("display_status_hints", &self.display_status_hints)
+            .field("display_help_codes", &self.display_help_codes)
+            .field("display_env_hints", &self.display_env_hints)
+            .field("display_uptime", &self.display_uptime)
+            .field(
+                "display_uptime_in_error_reports",
+                &self.display_uptime_in_error_reports,
+            )
+            .field("build_info", &self.build_info)
+            .field(
+                "display_build_info_in_error_reports",
+                &self.display_build_info_in_error_reports,
+            )
+            .field("theme", &self.theme)
+            .field("table_of_contents", &self.table_of_contents)
+            .field("reverse_layout", &self.reverse_layout)
+            .field("display_report_id", &self.display_report_id)
+            .field("panic_artifact", &self.panic_artifact)
+            .field("backtrace_formatter", &self.backtrace_formatter.is_some())
+            .field(
+                "panic_backtrace_formatter",
+                &self.panic_backtrace_formatter.is_some(),
+            )
+            .field(
+                "backtrace_suppressed_types",
+                &self.backtrace_suppressed_types,
+            )
+            .field("skip_backtrace_in_reports", &self.skip_backtrace_in_reports)
+            .field("chain_style", &self.chain_style)
+            .field("chain_label", &self.chain_label)
+            .field("report_header", &self.report_header)
+            .field("support_footer", &self.support_footer)
+            .field("severity_banner", &self.severity_banner)
+            .field("max_path_width", &self.max_path_width)
+            .field("width", &self.width)
+            .field("max_error_sections", &self.max_error_sections)
+            .field(
+                "dedup_error_sections_against_chain",
+                &self.dedup_error_sections_against_chain,
+            )
+            .field("auto_mark_reported", &self.auto_mark_reported)
+            .field(
+                "annotate_frame_crate_versions",
+                &self.annotate_frame_crate_versions,
+            )
+            .field(
+                "stripped_symbols_threshold",
+                &self.stripped_symbols_threshold,
+            )
+            .field("stripped_backtrace_note", &self.stripped_backtrace_note)
+            .field("conditional_sections", &self.conditional_sections.len())
+            .field("runbooks", &self.runbooks)
+            .field("render_time_budget", &self.render_time_budget)
+            .field("source_reader", &self.source_reader.is_some())
+            .field("report_observer", &self.report_observer.is_some())
+            .field("output_sinks", &self.output_sinks.len())
+            .field("clock", &self.clock.is_some())
+            .field("number_format", &self.configured_number_format());
+        #[cfg(feature = "capture-spantrace")]
+        builder
+            .field("spantrace_timestamps", &self.spantrace_timestamps)
+            .field("date_format", &self.configured_date_format())
+            .field("spantrace_self_field", &self.spantrace_self_field)
+            .field(
+                "collapse_same_target_spans",
+                &self.collapse_same_target_spans,
+            )
+            .field("correlate_traces", &self.correlate_traces);
+        #[cfg(feature = "highlight-patterns")]
+        builder.field("highlight_patterns", &self.highlight_patterns.len());
+        builder.finish()
+    }
+}
+
 fn default_frame_filter(frames: &mut Vec<&Frame>) {
     let top_cutoff = frames
         .iter()
@@ -392,19 +4955,61 @@ fn install_panic_hook() {
 }
 
 fn print_panic_info(pi: &std::panic::PanicInfo<'_>) -> std::io::Result<()> {
-    use std::io::Write;
+    let printer = installed_printer();
+    let report_id = printer.report_id();
+    let mut out = String::new();
+    format_panic_info(printer, pi, report_id.as_deref(), &mut out)
+        .expect("formatting into a String cannot fail");
+
+    if let Some(theme) = printer.theme() {
+        out = theme.apply(&out);
+    }
+    if let Some(user_paths) = printer.user_paths() {
+        out = user_paths.redact(&out);
+    }
 
     let stdout = std::io::stdout();
-    let mut out = stdout.lock();
-    writeln!(out, "{}", Red.paint("The application panicked (crashed)."))?;
+    let colors = printer.colors_enabled(atty::Stream::Stdout);
+    let result = printer.emit(&out, &mut stdout.lock(), colors);
 
-    // Print panic message.
-    let payload = pi
-        .payload()
+    if let Some(spec) = printer.panic_artifact() {
+        let report_id = report_id.unwrap_or_else(crate::report_id::generate);
+        if let Err(error) =
+            crate::artifact::write_panic_artifact(spec, printer, pi, &report_id, &out)
+        {
+            eprintln!(
+                "Error while writing the panic artifact to {}: {}",
+                spec.dir().display(),
+                error
+            );
+        }
+    }
+
+    result
+}
+
+/// The message of a panic
+pub(crate) fn panic_payload<'a>(pi: &'a std::panic::PanicInfo<'_>) -> &'a str {
+    pi.payload()
         .downcast_ref::<String>()
         .map(String::as_str)
         .or_else(|| pi.payload().downcast_ref::<&str>().cloned())
-        .unwrap_or("<non string panic payload>");
+        .unwrap_or("<non string panic payload>")
+}
+
+fn format_panic_info(
+    printer: &PanicHook,
+    pi: &std::panic::PanicInfo<'_>,
+    report_id: Option<&str>,
+    out: &mut String,
+) -> fmt::Result {
+    use std::fmt::Write;
+
+    let deadline = printer.render_deadline();
+    writeln!(out, "{}", Red.paint("The application panicked (crashed)."))?;
+
+    // Print panic message.
+    let payload = panic_payload(pi);
 
     write!(out, "Message:  ")?;
     writeln!(out, "{}", Cyan.paint(payload))?;
@@ -419,6 +5024,21 @@ fn print_panic_info(pi: &std::panic::PanicInfo<'_>) -> std::io::Result<()> {
         writeln!(out, "<unknown>")?;
     }
 
+    if let Some(build) = printer.build_info() {
+        writeln!(out, "Build:    {}", build)?;
+    }
+
+    if let Some(report_id) = report_id {
+        writeln!(out, "Report ID: {}", report_id)?;
+    }
+
+    if printer.display_uptime {
+        if let Some(uptime) = crate::uptime() {
+            let line = format!("process uptime: {}", HumanDuration(uptime));
+            writeln!(out, "{}", ansi_term::Style::new().dimmed().paint(line))?;
+        }
+    }
+
     let v = panic_verbosity();
 
     // Print some info on how to increase verbosity.
@@ -441,19 +5061,44 @@ fn print_panic_info(pi: &std::panic::PanicInfo<'_>) -> std::io::Result<()> {
         writeln!(out, " to include source snippets.")?;
     }
 
-    let printer = installed_printer();
+    #[cfg(feature = "capture-spantrace")]
+    let span_trace =
+        Some(tracing_error::SpanTrace::capture()).filter(|_| printer.spantrace_capture_enabled());
 
     #[cfg(feature = "capture-spantrace")]
     {
-        if printer.spantrace_capture_enabled() {
-            let span_trace = tracing_error::SpanTrace::capture();
-            write!(out, "{}", crate::writers::FormattedSpanTrace(&span_trace))?;
+        if let Some(span_trace) = &span_trace {
+            let timestamps = if printer.spantrace_timestamps {
+                crate::timestamps::capture_span_timestamps()
+            } else {
+                None
+            };
+
+            write!(
+                out,
+                "{}",
+                crate::writers::FormattedSpanTrace {
+                    span_trace,
+                    timestamps: timestamps.as_deref(),
+                    self_field: &printer.spantrace_self_field,
+                    max_path_width: printer.max_path_width,
+                    collapse_same_target: printer.collapse_same_target_spans,
+                }
+            )?;
         }
     }
 
     if panic_verbosity() != Verbosity::Minimal {
         let bt = capture_backtrace();
-        let fmt_bt = printer.format_backtrace(&bt);
+        #[cfg(feature = "capture-spantrace")]
+        let spans = printer.correlated_spans(span_trace.as_ref());
+        let fmt_bt = printer.format_backtrace(&bt).deadline(deadline);
+        #[cfg(feature = "capture-spantrace")]
+        let fmt_bt = fmt_bt.spans(&spans);
+        let fmt_bt = BacktraceFormatter {
+            custom: printer.panic_backtrace_formatter.as_deref(),
+            ..fmt_bt
+        };
         writeln!(out, "\n\n{}", fmt_bt)?;
     }
 
@@ -461,8 +5106,67 @@ fn print_panic_info(pi: &std::panic::PanicInfo<'_>) -> std::io::Result<()> {
 }
 
 pub(crate) struct PanicHook {
-    filters: Vec<Arc<FilterCallback>>,
+    filters: Vec<FrameFilter>,
+    collapse_hidden_frames: bool,
+    show_filter_provenance: bool,
     capture_span_trace_by_default: bool,
+    user_paths: Option<UserPaths>,
+    color_choice: ColorChoice,
+    group_errors: bool,
+    share_identical_backtraces: bool,
+    capture_error_section_backtraces: bool,
+    closure_display: ClosureDisplay,
+    simplify_async_frames: bool,
+    line_ending: LineEnding,
+    display_fingerprint: bool,
+    display_status_hints: bool,
+    display_help_codes: bool,
+    display_env_hints: bool,
+    display_uptime: bool,
+    display_uptime_in_error_reports: bool,
+    build_info: Option<crate::BuildInfo>,
+    display_build_info_in_error_reports: bool,
+    theme: Option<crate::Theme>,
+    table_of_contents: bool,
+    reverse_layout: bool,
+    display_report_id: bool,
+    panic_artifact: Option<crate::ArtifactSpec>,
+    backtrace_formatter: Option<Arc<BacktraceFormatterCallback>>,
+    panic_backtrace_formatter: Option<Arc<BacktraceFormatterCallback>>,
+    backtrace_suppressed_types: Vec<ErrorType>,
+    skip_backtrace_in_reports: bool,
+    chain_style: ChainStyle,
+    chain_label: Option<Option<String>>,
+    report_header: Option<String>,
+    support_footer: Option<String>,
+    severity_banner: bool,
+    max_path_width: Option<usize>,
+    width: Option<usize>,
+    max_error_sections: Option<usize>,
+    dedup_error_sections_against_chain: bool,
+    auto_mark_reported: bool,
+    annotate_frame_crate_versions: bool,
+    stripped_symbols_threshold: f64,
+    stripped_backtrace_note: String,
+    runbooks: Vec<(String, String)>,
+    render_time_budget: Option<Duration>,
+    source_reader: Option<Arc<SourceReaderCallback>>,
+    report_observer: Option<Arc<ReportObserverCallback>>,
+    output_sinks: Vec<Arc<OutputSinkCallback>>,
+    clock: Option<Arc<dyn ReportClock>>,
+    number_format: Option<NumberFormat>,
+    #[cfg(feature = "capture-spantrace")]
+    spantrace_timestamps: bool,
+    #[cfg(feature = "capture-spantrace")]
+    date_format: Option<DateFormat>,
+    #[cfg(feature = "capture-spantrace")]
+    spantrace_self_field: SpanTraceSelfField,
+    #[cfg(feature = "capture-spantrace")]
+    collapse_same_target_spans: bool,
+    #[cfg(feature = "capture-spantrace")]
+    correlate_traces: bool,
+    #[cfg(feature = "highlight-patterns")]
+    highlight_patterns: Vec<(regex_lite::Regex, String)>,
 }
 
 impl PanicHook {
@@ -473,53 +5177,474 @@ impl PanicHook {
         BacktraceFormatter {
             printer: self,
             inner: trace,
+            expand_hidden: !self.collapse_hidden_frames,
+            filter_provenance: self.show_filter_provenance,
+            custom: self.backtrace_formatter.as_deref(),
+            cause: None,
+            deadline: None,
+            #[cfg(feature = "capture-spantrace")]
+            spans: &[],
         }
     }
 
-    fn spantrace_capture_enabled(&self) -> bool {
+    /// Returns every frame of `trace`, marking the ones hidden by the frame filters
+    pub(crate) fn frame_infos(&self, trace: &crate::Backtrace) -> Vec<FrameInfo> {
+        let frames = trace.get_frames();
+        let visible = self.visible_frames(&frames, &SourceFiles::default());
+        frames
+            .into_iter()
+            .map(|frame| FrameInfo {
+                hidden: visible.binary_search(&frame.n).is_err(),
+                n: frame.n,
+                name: frame.name,
+                lineno: frame.lineno,
+                filename: frame.filename,
+            })
+            .collect()
+    }
+
+    /// Returns the sorted indices of the frames left visible by the frame filters
+    fn visible_frames(&self, frames: &[Frame], files: &SourceFiles) -> Vec<usize> {
+        self.filter_frames(frames, files).0
+    }
+
+    /// Returns the sorted indices of the frames left visible by the frame filters, along
+    /// with the name of the filter that hid each of the other frames
+    fn filter_frames(
+        &self,
+        frames: &[Frame],
+        files: &SourceFiles,
+    ) -> (Vec<usize>, HashMap<usize, &str>) {
+        // attribute the frames a filter removed from `filtered_frames` to `name`
+        fn record<'a>(
+            hidden_by: &mut HashMap<usize, &'a str>,
+            before: Vec<usize>,
+            filtered_frames: &[&Frame],
+            name: &'a str,
+        ) {
+            let mut after: Vec<usize> = filtered_frames.iter().map(|frame| frame.n).collect();
+            after.sort_unstable();
+            for n in before {
+                if after.binary_search(&n).is_err() {
+                    hidden_by.entry(n).or_insert(name);
+                }
+            }
+        }
+
+        let mut filtered_frames: Vec<&Frame> = frames.iter().collect();
+        let mut hidden_by = HashMap::new();
+        if !shows_hidden_frames() {
+            for filter in &self.filters {
+                let before = filtered_frames.iter().map(|frame| frame.n).collect();
+                (filter.filter)(&mut filtered_frames);
+                let name = filter.name.as_deref().unwrap_or("unnamed");
+                record(&mut hidden_by, before, &filtered_frames, name);
+            }
+
+            if self.closure_display == ClosureDisplay::Hidden {
+                let before = filtered_frames.iter().map(|frame| frame.n).collect();
+                let simplify_async = self.simplify_async_frames;
+                filtered_frames.retain(|frame| {
+                    !frame.is_closure() || simplify_async && frame.async_fn_name(files).is_some()
+                });
+                record(&mut hidden_by, before, &filtered_frames, "closures");
+            }
+        }
+
+        let mut visible: Vec<_> = filtered_frames.iter().map(|frame| frame.n).collect();
+        visible.sort_unstable();
+        (visible, hidden_by)
+    }
+
+    pub(crate) fn spantrace_capture_enabled(&self) -> bool {
         std::env::var("RUST_SPANTRACE")
             .map(|val| val != "0")
             .unwrap_or(self.capture_span_trace_by_default)
     }
+
+    pub(crate) fn frame_filters(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub(crate) fn color_choice(&self) -> ColorChoice {
+        self.color_choice
+    }
+
+    pub(crate) fn user_paths(&self) -> Option<&UserPaths> {
+        self.user_paths.as_ref()
+    }
+
+    pub(crate) fn colors_enabled(&self, stream: atty::Stream) -> bool {
+        self.color_choice.use_colors(stream)
+    }
+
+    /// Whether reports written to output sinks are colored, which are never terminals
+    fn sink_colors_enabled(&self) -> bool {
+        matches!(
+            self.color_choice,
+            ColorChoice::Always | ColorChoice::AlwaysAnsi
+        )
+    }
+
+    /// Write a rendered and redacted report to `out`, with colors if `colors` is set, and to
+    /// every output sink
+    pub(crate) fn emit(
+        &self,
+        report: &str,
+        out: &mut dyn std::io::Write,
+        colors: bool,
+    ) -> std::io::Result<()> {
+        let plain = self.line_ending().apply(crate::writers::strip_ansi(report));
+        let colored = self.line_ending().apply(report.to_owned());
+        let report = |colors| if colors { &colored } else { &plain };
+
+        let mut result = out.write_all(report(colors).as_bytes());
+
+        // a failing sink must not keep the others from receiving the report, the first error
+        // is returned once all of them were written to
+        let sink_report = report(self.sink_colors_enabled());
+        for sink in &self.output_sinks {
+            let mut sink = sink();
+            let written = sink
+                .write_all(sink_report.as_bytes())
+                .and_then(|()| sink.flush());
+            if result.is_ok() {
+                result = written;
+            }
+        }
+
+        result
+    }
+
+    pub(crate) fn groups_errors(&self) -> bool {
+        self.group_errors
+    }
+
+    pub(crate) fn shares_backtraces(&self) -> bool {
+        self.share_identical_backtraces
+    }
+
+    /// A backtrace for an error section attached at the caller, if they are captured
+    pub(crate) fn capture_error_section_backtrace(&self) -> Option<Backtrace> {
+        if self.capture_error_section_backtraces && lib_verbosity() != Verbosity::Minimal {
+            Some(capture_backtrace())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    pub(crate) fn chain_style(&self) -> ChainStyle {
+        self.chain_style
+    }
+
+    pub(crate) fn chain_label(&self) -> Option<Option<&str>> {
+        self.chain_label.as_ref().map(Option::as_deref)
+    }
+
+    pub(crate) fn report_header(&self) -> Option<&str> {
+        self.report_header.as_deref()
+    }
+
+    pub(crate) fn support_footer(&self) -> Option<&str> {
+        self.support_footer.as_deref()
+    }
+
+    pub(crate) fn severity_banner(&self) -> bool {
+        self.severity_banner
+    }
+
+    pub(crate) fn max_path_width(&self) -> Option<usize> {
+        self.max_path_width
+    }
+
+    /// The width of reports written to `stream` that weren't formatted with a width
+    pub(crate) fn report_width(&self, stream: atty::Stream) -> Option<usize> {
+        self.width.or_else(|| crate::width::detect(stream))
+    }
+
+    pub(crate) fn max_error_sections(&self) -> Option<usize> {
+        self.max_error_sections
+    }
+
+    pub(crate) fn dedups_error_sections_against_chain(&self) -> bool {
+        self.dedup_error_sections_against_chain
+    }
+
+    pub(crate) fn auto_marks_reported(&self) -> bool {
+        self.auto_mark_reported
+    }
+
+    #[cfg(feature = "capture-spantrace")]
+    pub(crate) fn spantrace_self_field(&self) -> &SpanTraceSelfField {
+        &self.spantrace_self_field
+    }
+
+    #[cfg(feature = "capture-spantrace")]
+    pub(crate) fn collapse_same_target_spans(&self) -> bool {
+        self.collapse_same_target_spans
+    }
+
+    /// The spans of `span_trace` if backtrace frames are annotated with them
+    #[cfg(feature = "capture-spantrace")]
+    pub(crate) fn correlated_spans(
+        &self,
+        span_trace: Option<&tracing_error::SpanTrace>,
+    ) -> Vec<crate::writers::TraceSpan> {
+        match span_trace {
+            Some(span_trace) if self.correlate_traces => crate::writers::trace_spans(span_trace),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the backtrace of reports of `error` is hidden, either because all of them are
+    /// or because an error in its chain is of a type registered with `suppress_backtrace_for`
+    pub(crate) fn suppresses_backtrace(&self, error: &(dyn std::error::Error + 'static)) -> bool {
+        self.skip_backtrace_in_reports
+            || !self.backtrace_suppressed_types.is_empty()
+                && anyhow::Chain::new(error).any(|error| {
+                    self.backtrace_suppressed_types
+                        .iter()
+                        .any(|ty| (ty.is)(error))
+                })
+    }
+
+    /// The instant after which a render call starting now skips expensive enrichment
+    pub(crate) fn render_deadline(&self) -> Option<Instant> {
+        self.render_time_budget
+            .map(|budget| self.clock().instant() + budget)
+    }
+
+    pub(crate) fn clock(&self) -> &dyn ReportClock {
+        self.clock.as_deref().unwrap_or(&SystemClock)
+    }
+
+    /// Render `n` in the configured number format
+    pub(crate) fn format_number(&self, n: usize) -> String {
+        match &self.number_format {
+            Some(format) => format.display(n).to_string(),
+            None => n.to_string(),
+        }
+    }
+
+    #[cfg(feature = "capture-spantrace")]
+    pub(crate) fn date_format(&self) -> Option<&DateFormat> {
+        self.date_format.as_ref()
+    }
+
+    /// Read the source file at `path` with the configured source reader
+    pub(crate) fn read_source(&self, path: &Path) -> std::io::Result<String> {
+        match &self.source_reader {
+            Some(reader) => reader(path),
+            // sources in another encoding are still worth a snippet
+            None => std::fs::read(path).map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+
+    /// The observer of rendered reports, if one was installed
+    pub(crate) fn report_observer(&self) -> Option<&ReportObserverCallback> {
+        self.report_observer.as_deref()
+    }
+
+    /// The url of the runbook registered for `category`
+    pub(crate) fn runbook(&self, category: &str) -> Option<&str> {
+        self.runbooks
+            .iter()
+            .find(|(other, _)| other == category)
+            .map(|(_, url)| url.as_str())
+    }
+
+    pub(crate) fn displays_fingerprint(&self) -> bool {
+        self.display_fingerprint
+    }
+
+    pub(crate) fn displays_status_hints(&self) -> bool {
+        self.display_status_hints
+    }
+
+    pub(crate) fn displays_help_codes(&self) -> bool {
+        self.display_help_codes
+    }
+
+    pub(crate) fn displays_env_hints(&self) -> bool {
+        self.display_env_hints
+    }
+
+    /// The uptime to show in error reports, if enabled
+    /// The information identifying the build, if there is any
+    pub(crate) fn build_info(&self) -> Option<&crate::BuildInfo> {
+        self.build_info
+            .as_ref()
+            .filter(|build| **build != crate::BuildInfo::new())
+    }
+
+    /// The theme of reports without a theme of their own
+    pub(crate) fn theme(&self) -> Option<&crate::Theme> {
+        self.theme.as_ref()
+    }
+
+    pub(crate) fn table_of_contents(&self) -> bool {
+        self.table_of_contents
+    }
+
+    pub(crate) fn reverse_layout(&self) -> bool {
+        self.reverse_layout
+    }
+
+    /// A new identifier for the report being rendered, if they are shown
+    pub(crate) fn report_id(&self) -> Option<String> {
+        if self.display_report_id {
+            Some(crate::report_id::generate())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn panic_artifact(&self) -> Option<&crate::ArtifactSpec> {
+        self.panic_artifact.as_ref()
+    }
+
+    pub(crate) fn error_report_build_info(&self) -> Option<&crate::BuildInfo> {
+        self.build_info()
+            .filter(|_| self.display_build_info_in_error_reports)
+    }
+
+    pub(crate) fn error_report_uptime(&self) -> Option<Duration> {
+        if self.display_uptime_in_error_reports {
+            crate::uptime()
+        } else {
+            None
+        }
+    }
+
+    /// The key facts extracted from the chain of `error` by the highlight patterns
+    #[cfg(feature = "highlight-patterns")]
+    pub(crate) fn highlights(&self, error: &(dyn std::error::Error + 'static)) -> Vec<String> {
+        const MAX_HIGHLIGHTS: usize = 3;
+
+        let mut highlights = vec![];
+        for error in anyhow::Chain::new(error) {
+            let message = error.to_string();
+            for (pattern, template) in &self.highlight_patterns {
+                for captures in pattern.captures_iter(&message) {
+                    let mut line = String::new();
+                    captures.expand(template, &mut line);
+                    if !highlights.contains(&line) {
+                        highlights.push(line);
+                    }
+
+                    if highlights.len() == MAX_HIGHLIGHTS {
+                        return highlights;
+                    }
+                }
+            }
+        }
+
+        highlights
+    }
 }
 
 pub(crate) struct ReportHook {
     capture_span_trace_by_default: bool,
+    attach_collected_warnings: bool,
+    conditional_sections: Vec<ConditionalSection>,
+    #[cfg(feature = "capture-spantrace")]
+    spantrace_timestamps: bool,
 }
 
 #[cfg(backtrace)]
-fn capture_backtrace() -> Backtrace {
+pub(crate) fn capture_backtrace() -> Backtrace {
     Backtrace::capture()
 }
 
 #[cfg(not(backtrace))]
-fn capture_backtrace() -> Backtrace {
+pub(crate) fn capture_backtrace() -> Backtrace {
     Backtrace::new()
 }
 
 impl ReportHook {
     #[allow(unused_variables)]
     pub(crate) fn default(&self, error: &(dyn std::error::Error + 'static)) -> crate::Handler {
-        let backtrace = if lib_verbosity() != Verbosity::Minimal {
-            Some(capture_backtrace())
-        } else {
+        // whether a trace wasn't captured because it isn't safe in the current context
+        let mut capture_skipped = false;
+        // stand-ins only carry what's attached to them over to the report of a stream
+        let stand_in = crate::stream::creating_stand_in();
+
+        let backtrace = if stand_in || lib_verbosity() == Verbosity::Minimal {
+            None
+        } else if crate::unsafe_context::skips_backtrace() {
+            capture_skipped = true;
             None
+        } else {
+            Some(capture_backtrace())
         };
 
         #[cfg(feature = "capture-spantrace")]
-        let span_trace = if self.spantrace_capture_enabled()
-            && crate::handler::get_deepest_spantrace(error).is_none()
+        let span_trace = if stand_in
+            || !self.spantrace_capture_enabled()
+            || crate::handler::get_deepest_spantrace(error).is_some()
         {
+            None
+        } else if crate::unsafe_context::skips_span_trace() {
+            capture_skipped = true;
+            None
+        } else {
             Some(tracing_error::SpanTrace::capture())
+        };
+
+        #[cfg(feature = "capture-spantrace")]
+        let span_timestamps = if self.spantrace_timestamps && span_trace.is_some() {
+            crate::timestamps::capture_span_timestamps()
         } else {
             None
         };
 
+        let mut sections = Sections::default();
+        if self.attach_collected_warnings && !stand_in {
+            let warnings = crate::WarningsCollector::global().drain();
+            if !warnings.is_empty() {
+                sections.push(HelpInfo::Custom(Box::new(warnings)));
+            }
+        }
+
+        for section in self.conditional_sections.iter().filter(|_| !stand_in) {
+            // a panicking trigger or provider must not keep the report from being created
+            let body = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                if (section.trigger)(error) {
+                    Some((section.provider)())
+                } else {
+                    None
+                }
+            }));
+            if let Ok(Some(body)) = body {
+                let section = crate::SectionExt::header(body, "Diagnostics:");
+                sections.push(HelpInfo::Custom(Box::new(section)));
+            }
+        }
+
         crate::Handler {
             backtrace,
             #[cfg(feature = "capture-spantrace")]
             span_trace,
-            sections: Vec::new(),
+            #[cfg(feature = "capture-spantrace")]
+            span_timestamps,
+            sections,
+            breadcrumbs: vec![],
+            chain_label: None,
+            severity: None,
+            localized: None,
+            category: None,
+            source_locations: vec![],
+            #[cfg(feature = "json")]
+            json_source: None,
+            inline_hints: vec![],
+            theme: None,
+            capture_skipped,
+            reported: Default::default(),
+            converted_at: None,
         }
     }
 
@@ -533,10 +5658,69 @@ impl ReportHook {
 pub(crate) struct BacktraceFormatter<'a> {
     printer: &'a PanicHook,
     inner: &'a crate::Backtrace,
+    expand_hidden: bool,
+    filter_provenance: bool,
+    custom: Option<&'a BacktraceFormatterCallback>,
+    cause: Option<usize>,
+    deadline: Option<Instant>,
+    #[cfg(feature = "capture-spantrace")]
+    spans: &'a [crate::writers::TraceSpan],
+}
+
+impl BacktraceFormatter<'_> {
+    /// Print hidden frames dimmed instead of collapsing them
+    pub(crate) fn expand_hidden(mut self, cond: bool) -> Self {
+        self.expand_hidden = cond;
+        self
+    }
+
+    /// Name the filters that hid the frames of each "N frames hidden" marker
+    pub(crate) fn filter_provenance(mut self, cond: bool) -> Self {
+        self.filter_provenance = cond;
+        self
+    }
+
+    /// Title the backtrace as the one provided by the error at index `n` of the chain
+    pub(crate) fn cause(mut self, n: usize) -> Self {
+        self.cause = Some(n);
+        self
+    }
+
+    /// Skip the expensive steps of rendering the backtrace once `deadline` has passed
+    pub(crate) fn deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+}
+
+impl<'a> BacktraceFormatter<'a> {
+    /// Annotate the frames with the `spans` they correlate with
+    #[cfg(feature = "capture-spantrace")]
+    pub(crate) fn spans(mut self, spans: &'a [crate::writers::TraceSpan]) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Write the span the frame numbered `n` is annotated with, if any
+    #[cfg(feature = "capture-spantrace")]
+    fn write_span(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        spans: &[(usize, usize)],
+        n: usize,
+    ) -> fmt::Result {
+        match spans.iter().find(|(frame, _)| *frame == n) {
+            Some((_, span)) => writeln!(f, "    {}", self.spans[*span]),
+            None => Ok(()),
+        }
+    }
 }
 
 trait BacktraceFrames {
     fn get_frames(&self) -> Vec<Frame>;
+
+    /// The address of each frame and whether a symbol name was resolved for it
+    fn symbols(&self) -> Vec<(usize, bool)>;
 }
 
 #[cfg(backtrace)]
@@ -555,40 +5739,141 @@ impl BacktraceFrames for std::backtrace::Backtrace {
             })
             .collect()
     }
+
+    fn symbols(&self) -> Vec<(usize, bool)> {
+        // the standard library's backtraces don't expose the addresses of their frames
+        vec![]
+    }
 }
 
 #[cfg(not(backtrace))]
 impl BacktraceFrames for backtrace::Backtrace {
     fn get_frames(&self) -> Vec<Frame> {
+        // recursion repeats the same symbols, each distinct one is demangled once
+        let mut names: HashMap<&[u8], String> = HashMap::new();
         self.frames()
             .iter()
             .flat_map(|frame| frame.symbols())
             .zip(1usize..)
             .map(|(sym, n)| Frame {
-                name: sym.name().map(|x| x.to_string()),
+                name: sym.name().map(|name| {
+                    names
+                        .entry(name.as_bytes())
+                        .or_insert_with(|| name.to_string())
+                        .clone()
+                }),
                 lineno: sym.lineno(),
                 filename: sym.filename().map(|x| x.into()),
                 n,
             })
             .collect()
     }
+
+    fn symbols(&self) -> Vec<(usize, bool)> {
+        self.frames()
+            .iter()
+            .map(|frame| {
+                let resolved = frame.symbols().iter().any(|sym| sym.name().is_some());
+                (frame.ip() as usize, resolved)
+            })
+            .collect()
+    }
 }
 
 impl fmt::Display for BacktraceFormatter<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{:━^80}", " BACKTRACE ")?;
+        match self.cause {
+            Some(n) => writeln!(f, "{:━^80}", format!(" BACKTRACE (cause #{}) ", n))?,
+            None => writeln!(f, "{:━^80}", " BACKTRACE ")?,
+        }
+
+        if let Some(custom) = self.custom {
+            let mut body = String::new();
+            match custom(self.inner, &mut body) {
+                Ok(()) => {
+                    f.write_str(&body)?;
+                    return if body.is_empty() || body.ends_with('\n') {
+                        Ok(())
+                    } else {
+                        writeln!(f)
+                    };
+                }
+                Err(_) => {
+                    let note =
+                        "the custom backtrace formatter failed, showing the default rendering";
+                    writeln!(f, "{}", ansi_term::Style::new().dimmed().paint(note))?;
+                }
+            }
+        }
+
+        self.fmt_frames(f)
+    }
+}
+
+impl BacktraceFormatter<'_> {
+    fn fmt_frames(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if past(self.deadline) {
+            crate::stats::truncated();
+            let note = "(skipped: render budget exceeded)";
+            return writeln!(f, "{}", ansi_term::Style::new().dimmed().paint(note));
+        }
+
+        let symbols = self.inner.symbols();
+        let threshold = self.printer.stripped_symbols_threshold;
+        if crate::stripped::is_stripped(&symbols, threshold) {
+            let addresses = crate::stripped::AddressList {
+                note: &self.printer.stripped_backtrace_note,
+                symbols: &symbols,
+            };
+            return write!(f, "{}", addresses);
+        }
 
         // Collect frame info.
-        let frames = self.inner.get_frames();
-
-        let mut filtered_frames = frames.iter().collect();
-        match env::var("COLORBT_SHOW_HIDDEN").ok().as_deref() {
-            Some("1") | Some("on") | Some("y") => (),
-            _ => {
-                for filter in &self.printer.filters {
-                    filter(&mut filtered_frames);
+        let mut frames = self.inner.get_frames();
+        let files = SourceFiles::default();
+        let (visible, hidden_by) = self.printer.filter_frames(&frames, &files);
+
+        if self.printer.simplify_async_frames {
+            for frame in &mut frames {
+                frame.simplify_async_name(&files);
+            }
+        }
+
+        if self.printer.closure_display == ClosureDisplay::Simplified {
+            frames.iter_mut().for_each(Frame::simplify_closure_name);
+        }
+
+        // Don't let filters mess with the order.
+        let filtered_frames: Vec<_> = frames
+            .iter()
+            .filter(|frame| visible.binary_search(&frame.n).is_ok())
+            .collect();
+
+        #[cfg(feature = "capture-spantrace")]
+        let spans = crate::writers::correlate(&filtered_frames, self.spans);
+
+        if self.expand_hidden && !frames.is_empty() {
+            let dimmed = ansi_term::Style::new().dimmed();
+            for frame in &frames {
+                let frame_n = frame.n;
+                let frame = BudgetedFrame {
+                    frame,
+                    deadline: self.deadline,
+                    files: &files,
+                };
+                if visible.binary_search(&frame_n).is_ok() {
+                    write!(f, "{}", frame)?;
+                    #[cfg(feature = "capture-spantrace")]
+                    self.write_span(f, &spans, frame_n)?;
+                } else {
+                    let frame = crate::writers::strip_ansi(&frame.to_string());
+                    for line in frame.lines() {
+                        writeln!(f, "{}", dimmed.paint(line))?;
+                    }
                 }
             }
+
+            return Ok(());
         }
 
         if filtered_frames.is_empty() {
@@ -596,19 +5881,34 @@ impl fmt::Display for BacktraceFormatter<'_> {
             return writeln!(f, "<empty backtrace>");
         }
 
-        // Don't let filters mess with the order.
-        filtered_frames.sort_by_key(|x| x.n);
+        // the names of the filters that hid the frames in `hidden`, in the order of the frames
+        let provenance = |hidden: std::ops::Range<usize>| {
+            let mut names: Vec<&str> = vec![];
+            for name in hidden.filter_map(|n| hidden_by.get(&n)) {
+                if !names.contains(name) {
+                    names.push(name);
+                }
+            }
+            names.join(", ")
+        };
 
         macro_rules! print_hidden {
-            ($n:expr) => {
+            ($hidden:expr) => {
                 let color = Cyan.make_intense();
-                let n = $n;
+                let hidden = $hidden;
+                let n = hidden.len();
+                let names = match self.filter_provenance {
+                    true => provenance(hidden),
+                    false => String::new(),
+                };
                 let text = format!(
                     "{:^80}",
                     format!(
-                        "{decorator} {n} frame{plural} hidden {decorator}",
-                        n = n,
+                        "{decorator} {n} frame{plural} hidden{by}{names} {decorator}",
+                        n = self.printer.format_number(n),
                         plural = if n == 1 { "" } else { "s" },
+                        by = if names.is_empty() { "" } else { " by " },
+                        names = names,
                         decorator = "⋮",
                     )
                 );
@@ -618,18 +5918,27 @@ impl fmt::Display for BacktraceFormatter<'_> {
 
         let mut last_n = 0;
         for frame in &filtered_frames {
-            let frame_delta = frame.n - last_n - 1;
-            if frame_delta != 0 {
-                print_hidden!(frame_delta);
+            if frame.n - last_n - 1 != 0 {
+                print_hidden!(last_n + 1..frame.n);
             }
-            write!(f, "{}", frame)?;
+            write!(
+                f,
+                "{}",
+                BudgetedFrame {
+                    frame,
+                    deadline: self.deadline,
+                    files: &files,
+                }
+            )?;
+            #[cfg(feature = "capture-spantrace")]
+            self.write_span(f, &spans, frame.n)?;
             last_n = frame.n;
         }
 
         let last_filtered_n = filtered_frames.last().unwrap().n;
         let last_unfiltered_n = frames.last().unwrap().n;
         if last_filtered_n < last_unfiltered_n {
-            print_hidden!(last_unfiltered_n - last_filtered_n);
+            print_hidden!(last_filtered_n + 1..last_unfiltered_n + 1);
         }
 
         Ok(())
@@ -637,7 +5946,9 @@ impl fmt::Display for BacktraceFormatter<'_> {
 }
 
 pub(crate) fn installed_printer() -> &'static PanicHook {
-    crate::CONFIG.get_or_init(default_printer)
+    // fall back to the defaults without claiming the cell, so a later install
+    // still takes effect
+    crate::CONFIG.get().unwrap_or(&DEFAULT_PRINTER)
 }
 
 fn default_printer() -> PanicHook {
@@ -652,7 +5963,7 @@ pub(crate) enum Verbosity {
     Full,
 }
 
-fn panic_verbosity() -> Verbosity {
+pub(crate) fn panic_verbosity() -> Verbosity {
     match env::var("RUST_BACKTRACE") {
         Ok(s) if s == "full" => Verbosity::Full,
         Ok(s) if s != "0" => Verbosity::Medium,
@@ -660,6 +5971,14 @@ fn panic_verbosity() -> Verbosity {
     }
 }
 
+/// Whether `COLORBT_SHOW_HIDDEN` disables the frame filters
+pub(crate) fn shows_hidden_frames() -> bool {
+    matches!(
+        env::var("COLORBT_SHOW_HIDDEN").ok().as_deref(),
+        Some("1") | Some("on") | Some("y")
+    )
+}
+
 pub(crate) fn lib_verbosity() -> Verbosity {
     match env::var("RUST_LIB_BACKTRACE").or_else(|_| env::var("RUST_BACKTRACE")) {
         Ok(s) if s == "full" => Verbosity::Full,
@@ -670,3 +5989,30 @@ pub(crate) fn lib_verbosity() -> Verbosity {
 
 /// Callback for filtering a vector of `Frame`s
 pub type FilterCallback = dyn Fn(&mut Vec<&Frame>) + Send + Sync + 'static;
+
+/// A frame filter, along with the name it was added with
+#[derive(Clone)]
+struct FrameFilter {
+    name: Option<String>,
+    filter: Arc<FilterCallback>,
+}
+
+/// Callback deciding whether a conditional section is added to the report of an error
+pub type SectionTriggerCallback =
+    dyn Fn(&(dyn std::error::Error + 'static)) -> bool + Send + Sync + 'static;
+
+/// Callback producing the body of a conditional section
+pub type SectionProviderCallback = dyn Fn() -> String + Send + Sync + 'static;
+
+/// Callback opening a writer that panic reports are also written to
+pub type OutputSinkCallback = dyn Fn() -> Box<dyn std::io::Write + Send> + Send + Sync + 'static;
+
+/// Callback for reading the source files shown in the source snippets of backtrace frames
+pub type SourceReaderCallback = dyn Fn(&Path) -> std::io::Result<String> + Send + Sync + 'static;
+
+/// Callback observing the summary of each rendered error report
+pub type ReportObserverCallback = dyn Fn(&crate::ReportSummary) + Send + Sync + 'static;
+
+/// Callback for rendering the frames of a backtrace in place of the default rendering
+pub type BacktraceFormatterCallback =
+    dyn Fn(&Backtrace, &mut dyn fmt::Write) -> fmt::Result + Send + Sync + 'static;
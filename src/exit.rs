@@ -0,0 +1,67 @@
+//! Exiting `fn main` with a rendered error report
+use crate::config::installed_printer;
+use crate::handler::render_redacted;
+use std::process::ExitCode;
+
+/// Print the error of `result` to stderr with the installed settings and convert it into the
+/// exit code of the process
+///
+/// # Details
+///
+/// Returning a `Result` from `fn main` prints the error with its `Debug` output, prefixed
+/// with `Error: `. This prints the same layout, but writes it the way the hooks write
+/// their reports: colors follow the configured `ColorChoice` for stderr, user paths are
+/// redacted, the configured line endings are used, and the report is also written to the
+/// sinks added with `HookBuilder::add_output_sink`. Returns `ExitCode::SUCCESS` for `Ok`
+/// and `ExitCode::FAILURE`, i.e. `1`, for `Err`.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Result, config::{ColorChoice, HookBuilder}};
+/// use std::process::{Command, ExitCode};
+///
+/// fn run() -> Result<()> {
+///     Err(anyhow!("the config could not be loaded"))
+/// }
+///
+/// fn main() -> ExitCode {
+///     if std::env::var("EXIT_CHILD").is_ok() {
+///         HookBuilder::default()
+///             .color_choice(ColorChoice::Never)
+///             .add_output_sink(Box::new(|| Box::new(std::io::stdout())))
+///             .install()
+///             .unwrap();
+///
+///         return color_anyhow::report_to_exit_code(run());
+///     }
+///
+///     let output = Command::new(std::env::current_exe().unwrap())
+///         .env("EXIT_CHILD", "1")
+///         .output()
+///         .unwrap();
+///     let stderr = String::from_utf8(output.stderr).unwrap();
+///     let stdout = String::from_utf8(output.stdout).unwrap();
+///
+///     assert_eq!(output.status.code(), Some(1));
+///     assert!(stderr.starts_with("Error: \n   0: the config could not be loaded"));
+///     // returning the error from `main` would have left the sink empty
+///     assert!(stdout.contains("0: the config could not be loaded"));
+///     ExitCode::SUCCESS
+/// }
+/// ```
+pub fn report_to_exit_code(result: Result<(), anyhow::Error>) -> ExitCode {
+    let error = match result {
+        Ok(()) => return ExitCode::SUCCESS,
+        Err(error) => error,
+    };
+
+    let printer = installed_printer();
+    let report = format!("Error: {}\n", render_redacted(&error));
+    let stderr = std::io::stderr();
+    let colors = printer.colors_enabled(atty::Stream::Stderr);
+    // the process is exiting either way, there is nowhere left to report a failed write
+    let _ = printer.emit(&report, &mut stderr.lock(), colors);
+
+    ExitCode::FAILURE
+}
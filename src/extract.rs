@@ -0,0 +1,299 @@
+//! Extraction of the data of error reports, independent of how they're rendered
+use crate::config::{installed_printer, FrameInfo};
+use crate::section::help::HelpInfo;
+use crate::writers::strip_ansi;
+use crate::Handler;
+#[cfg(feature = "capture-spantrace")]
+use tracing_error::{SpanTrace, SpanTraceStatus};
+
+/// The data of an error report, for rendering reports in a custom format
+///
+/// # Details
+///
+/// `ReportData` is constructed with [`extract`]. It holds the same information
+/// as the rendered report, as plain text without any colors, so it can be
+/// serialized in whatever schema a tool needs, e.g. for a `--json` flag.
+///
+/// [`extract`]: fn.extract.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReportData {
+    /// The messages of the chain of errors, outermost first
+    pub chain: Vec<String>,
+    /// The sections attached to the report, in the order they were attached
+    pub sections: Vec<SectionData>,
+    /// The breadcrumbs attached with `Section::breadcrumb`, outermost first
+    pub breadcrumbs: Vec<String>,
+    /// The revision of the report, see [`Handler::revision`]
+    ///
+    /// [`Handler::revision`]: struct.Handler.html#method.revision
+    pub revision: u64,
+    /// The frames of the `SpanTrace`, innermost first, if one was captured
+    #[cfg(feature = "capture-spantrace")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+    pub span_trace: Option<Vec<SpanFrame>>,
+    /// Every frame of the backtrace, if one was captured, with the frames
+    /// hidden by the frame filters marked as such
+    pub backtrace: Option<Vec<FrameInfo>>,
+}
+
+/// A section attached to an error report
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SectionData {
+    /// An error or report attached with `Section::error`, `error_labeled` or `error_report`
+    Error {
+        /// The messages of the chain of the attached error, outermost first
+        chain: Vec<String>,
+        /// The label of the error, if it was attached with `error_labeled`
+        label: Option<String>,
+    },
+    /// A custom section, rendered to text
+    Custom(String),
+    /// A note
+    Note(String),
+    /// A warning
+    Warning(String),
+    /// A suggestion
+    Suggestion(String),
+    /// A recovery action that was already attempted
+    Recovery(String),
+    /// The steps of a remediation, in order
+    Remediation(Vec<String>),
+    /// A command attached with `Section::try_command`
+    TryCommand(String),
+    /// The commands attached with `Section::quick_fix`, one per line
+    QuickFix(String),
+    /// A note attached with `Section::note_coded`
+    CodedNote {
+        /// The code identifying the note for tooling
+        code: String,
+        /// The text of the note
+        text: String,
+    },
+    /// A suggestion attached with `Section::suggestion_coded`
+    CodedSuggestion {
+        /// The code identifying the suggestion for tooling
+        code: String,
+        /// The text of the suggestion
+        text: String,
+    },
+    /// A transport status code attached with `Section::status_hint`
+    Status(u16),
+}
+
+/// A frame of a `SpanTrace`
+#[cfg(feature = "capture-spantrace")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SpanFrame {
+    /// The target of the span, usually its module path
+    pub target: String,
+    /// The name of the span
+    pub name: String,
+    /// The formatted fields of the span
+    pub fields: String,
+    /// The source file the span was created in, if known
+    pub file: Option<String>,
+    /// The source line the span was created on, if known
+    pub line: Option<u32>,
+}
+
+/// Extract the data of an error report, for rendering it in a custom format
+///
+/// # Details
+///
+/// Reports whose handler isn't the one installed by color-anyhow only have
+/// their chain of errors extracted.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error, Section, SectionData};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("file not found"))
+///     .context("config could not be loaded")
+///     .note("the config is read from the working directory")
+///     .suggestion("pass a config with --config")
+///     .unwrap_err();
+///
+/// let data = color_anyhow::extract(&report);
+/// assert_eq!(data.chain, ["config could not be loaded", "file not found"]);
+/// assert_eq!(
+///     data.sections,
+///     [
+///         SectionData::Note("the config is read from the working directory".into()),
+///         SectionData::Suggestion("pass a config with --config".into()),
+///     ]
+/// );
+/// assert!(data.backtrace.is_none());
+/// # #[cfg(feature = "capture-spantrace")]
+/// assert!(data.span_trace.is_none());
+/// ```
+pub fn extract(error: &anyhow::Error) -> ReportData {
+    let chain = error.chain().map(|error| error.to_string()).collect();
+    let handler = match error.handler().downcast_ref::<Handler>() {
+        Some(handler) => handler,
+        None => {
+            return ReportData {
+                chain,
+                sections: vec![],
+                breadcrumbs: vec![],
+                revision: 0,
+                #[cfg(feature = "capture-spantrace")]
+                span_trace: None,
+                backtrace: None,
+            }
+        }
+    };
+
+    #[cfg(feature = "capture-spantrace")]
+    let span_trace = handler
+        .span_trace
+        .as_ref()
+        .or_else(|| crate::handler::get_deepest_spantrace(error.as_ref()))
+        .and_then(span_frames);
+
+    ReportData {
+        chain,
+        sections: handler.sections.iter().map(section_data).collect(),
+        breadcrumbs: handler.breadcrumbs.iter().rev().cloned().collect(),
+        revision: handler.revision(),
+        #[cfg(feature = "capture-spantrace")]
+        span_trace,
+        backtrace: handler
+            .backtrace
+            .as_ref()
+            .map(|backtrace| installed_printer().frame_infos(backtrace)),
+    }
+}
+
+/// Returns the transport status code attached to an error report with `Section::status_hint`
+///
+/// # Details
+///
+/// If several codes were attached the last one is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("the quota is exhausted"))
+///     .status_hint(503)
+///     .status_hint(429)
+///     .unwrap_err();
+///
+/// assert_eq!(color_anyhow::status_hint(&report), Some(429));
+/// assert_eq!(color_anyhow::status_hint(&anyhow!("unmapped")), None);
+/// ```
+pub fn status_hint(error: &anyhow::Error) -> Option<u16> {
+    error
+        .handler()
+        .downcast_ref::<Handler>()
+        .and_then(Handler::status_hint)
+}
+
+/// Returns the commands attached to an error report with `Section::try_command`
+///
+/// # Details
+///
+/// The commands are returned in the order they were attached, exactly as they
+/// were attached, without the `Try:` prefix or any styling.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("the migration failed"))
+///     .try_command("myapp db status")
+///     .try_command(" myapp db rollback --to \"2024-01-01\" ")
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     color_anyhow::try_commands_of(&report),
+///     ["myapp db status", " myapp db rollback --to \"2024-01-01\" "]
+/// );
+/// assert!(color_anyhow::try_commands_of(&anyhow!("no commands")).is_empty());
+/// ```
+pub fn try_commands_of(error: &anyhow::Error) -> Vec<String> {
+    let handler = match error.handler().downcast_ref::<Handler>() {
+        Some(handler) => handler,
+        None => return vec![],
+    };
+
+    handler
+        .sections
+        .iter()
+        .filter_map(|section| match section {
+            HelpInfo::TryCommand(command) => Some(command.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn section_data(section: &HelpInfo) -> SectionData {
+    let chain = |error: &(dyn std::error::Error + 'static)| {
+        anyhow::Chain::new(error)
+            .map(|error| error.to_string())
+            .collect()
+    };
+
+    match section {
+        HelpInfo::Error(error, label, _) => SectionData::Error {
+            chain: chain(error.as_ref()),
+            label: label.clone(),
+        },
+        HelpInfo::Report(report) => SectionData::Error {
+            chain: chain(report.as_ref()),
+            label: None,
+        },
+        HelpInfo::Custom(section) => SectionData::Custom(strip_ansi(&section.to_string())),
+        HelpInfo::Note(note, None) => SectionData::Note(note.to_string()),
+        HelpInfo::Note(note, Some(code)) => SectionData::CodedNote {
+            code: code.clone(),
+            text: note.to_string(),
+        },
+        HelpInfo::Warning(warning) => SectionData::Warning(warning.to_string()),
+        HelpInfo::Suggestion(suggestion, None) => SectionData::Suggestion(suggestion.to_string()),
+        HelpInfo::Suggestion(suggestion, Some(code)) => SectionData::CodedSuggestion {
+            code: code.clone(),
+            text: suggestion.to_string(),
+        },
+        HelpInfo::Recovery(recovery) => SectionData::Recovery(recovery.to_string()),
+        HelpInfo::Remediation(steps) => SectionData::Remediation(steps.steps().to_vec()),
+        HelpInfo::TryCommand(command) => SectionData::TryCommand(command.clone()),
+        HelpInfo::QuickFix(commands) => SectionData::QuickFix(commands.clone()),
+        HelpInfo::Status(code) => SectionData::Status(*code),
+    }
+}
+
+#[cfg(feature = "capture-spantrace")]
+fn span_frames(span_trace: &SpanTrace) -> Option<Vec<SpanFrame>> {
+    if span_trace.status() != SpanTraceStatus::CAPTURED {
+        return None;
+    }
+
+    let mut frames = vec![];
+    span_trace.with_spans(|metadata, fields| {
+        frames.push(SpanFrame {
+            target: metadata.target().to_owned(),
+            name: metadata.name().to_owned(),
+            fields: fields.to_owned(),
+            file: metadata.file().map(str::to_owned),
+            line: metadata.line(),
+        });
+        true
+    });
+
+    Some(frames)
+}
@@ -0,0 +1,120 @@
+//! Rendering of error reports into buffers provided by C callers
+use crate::config::ReportOptions;
+use std::os::raw::c_char;
+use std::panic::AssertUnwindSafe;
+
+/// Render the report of `error` into the C buffer `buf` of `len` bytes,
+/// returning the length of the full report in bytes
+///
+/// # Details
+///
+/// The report is rendered the same way as [`format_report`] with the default
+/// options, as UTF-8 text terminated by a null byte. Like `snprintf`, the
+/// returned length excludes the terminating null byte, so the report was
+/// truncated if it is `len` or more, and a buffer of the returned length plus
+/// one holds all of it. Truncated reports are cut at a character boundary and
+/// still null-terminated. Nothing is written if `buf` is null or `len` is `0`,
+/// and a null `error` renders an empty report.
+///
+/// A panic while rendering, e.g. in a custom section, never unwinds into the
+/// caller. `usize::MAX` is returned instead, with `buf` holding an empty
+/// string if it can hold anything.
+///
+/// The function is exported unmangled as `render_to_buffer`.
+///
+/// # Safety
+///
+/// - `error` must be null or point to a valid `anyhow::Error` that isn't
+///   mutated for the duration of the call
+/// - `buf` must be null or valid for writes of `len` bytes, and must not
+///   overlap `error`
+/// - at most `len` bytes are written to `buf`, including the null byte, and
+///   nothing past the returned length plus one is written
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::anyhow::anyhow;
+/// use std::ffi::CStr;
+/// use std::os::raw::c_char;
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// color_anyhow::install().unwrap();
+///
+/// let report = anyhow!("the device is busy");
+/// let full = format!("{:?}", report);
+///
+/// let mut buf = [0x7f as c_char; 12];
+/// let needed = unsafe { color_anyhow::render_to_buffer(&report, buf.as_mut_ptr(), buf.len()) };
+/// assert_eq!(needed, full.len());
+///
+/// let rendered = unsafe { CStr::from_ptr(buf.as_ptr()) };
+/// assert_eq!(rendered.to_str().unwrap(), &full[..11]);
+///
+/// let mut buf = vec![0 as c_char; needed + 1];
+/// unsafe { color_anyhow::render_to_buffer(&report, buf.as_mut_ptr(), buf.len()) };
+/// let rendered = unsafe { CStr::from_ptr(buf.as_ptr()) };
+/// assert_eq!(rendered.to_str().unwrap(), full);
+/// ```
+///
+/// Panics are caught:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, Section};
+/// use std::fmt;
+/// use std::os::raw::c_char;
+///
+/// struct Broken;
+///
+/// impl fmt::Display for Broken {
+///     fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         panic!("the section is broken")
+///     }
+/// }
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), _>(anyhow!("the device is busy")).section(Broken).unwrap_err();
+/// let mut buf = [0x7f as c_char; 64];
+/// let needed = unsafe { color_anyhow::render_to_buffer(&report, buf.as_mut_ptr(), buf.len()) };
+/// assert_eq!(needed, usize::MAX);
+/// assert_eq!(buf[0], 0);
+/// ```
+///
+/// [`format_report`]: fn.format_report.html
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+#[no_mangle]
+pub unsafe extern "C" fn render_to_buffer(
+    error: *const anyhow::Error,
+    buf: *mut c_char,
+    len: usize,
+) -> usize {
+    let rendered = std::panic::catch_unwind(AssertUnwindSafe(|| match error.as_ref() {
+        Some(error) => crate::format_report(error, &ReportOptions::default()),
+        None => String::new(),
+    }));
+    let report = match rendered {
+        Ok(report) => report,
+        Err(_) => {
+            if !buf.is_null() && len > 0 {
+                *buf = 0;
+            }
+            return usize::MAX;
+        }
+    };
+
+    if buf.is_null() || len == 0 {
+        return report.len();
+    }
+
+    // leave room for the null byte
+    let mut end = report.len().min(len - 1);
+    while !report.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    std::ptr::copy_nonoverlapping(report.as_ptr(), buf as *mut u8, end);
+    *buf.add(end) = 0;
+
+    report.len()
+}
@@ -0,0 +1,408 @@
+//! Stable fingerprints of error reports for grouping identical failures
+use crate::config::Frame;
+use crate::Handler;
+use std::fmt;
+
+/// The number of application frames that contribute to a fingerprint by default
+const DEFAULT_FRAMES: usize = 5;
+
+/// A stable identifier of the logical failure described by an error report
+///
+/// # Details
+///
+/// Fingerprints are computed with [`fingerprint`] and display as 16 hex
+/// digits, they can be added to every report with
+/// [`HookBuilder::display_fingerprint`]. The same failure produces the same fingerprint across machines,
+/// builds, and versions of Rust, so they are suited as grouping keys for
+/// crash reporting backends.
+///
+/// [`fingerprint`]: fn.fingerprint.html
+/// [`HookBuilder::display_fingerprint`]: config/struct.HookBuilder.html#method.display_fingerprint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Returns the fingerprint as an integer
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Options controlling which parts of a report contribute to its [`Fingerprint`]
+///
+/// By default the normalized chain of error messages, the type of the root
+/// cause, and the top 5 application frames of the backtrace all contribute.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, FingerprintOptions};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let messages_only = FingerprintOptions::new().root_cause_type(false).frames(0);
+/// let a = color_anyhow::fingerprint_with(&anyhow!("timed out"), &messages_only);
+/// let b = color_anyhow::fingerprint_with(&anyhow!("timed out"), &messages_only);
+/// let c = color_anyhow::fingerprint_with(&anyhow!("refused"), &messages_only);
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+///
+/// // without any contributing parts every report is alike
+/// let nothing = messages_only.messages(false);
+/// assert_eq!(
+///     color_anyhow::fingerprint_with(&anyhow!("timed out"), &nothing),
+///     color_anyhow::fingerprint_with(&anyhow!("refused"), &nothing),
+/// );
+/// ```
+///
+/// [`Fingerprint`]: struct.Fingerprint.html
+#[derive(Debug, Clone)]
+pub struct FingerprintOptions {
+    messages: bool,
+    root_cause_type: bool,
+    frames: usize,
+}
+
+impl FingerprintOptions {
+    /// Construct the default `FingerprintOptions`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the chain of error messages contributes, defaults to `true`
+    ///
+    /// Messages are normalized first: UUIDs, hex numbers, and other numbers
+    /// are masked, as are words that look like file paths.
+    pub fn messages(mut self, cond: bool) -> Self {
+        self.messages = cond;
+        self
+    }
+
+    /// Whether the type of the root cause contributes, defaults to `true`
+    ///
+    /// The type is taken from the root cause's `Debug` output, e.g.
+    /// `ParseError` for `ParseError { .. }`. Root causes whose `Debug` output
+    /// doesn't start with a type name, like the ones constructed with
+    /// `anyhow!`, contribute nothing.
+    pub fn root_cause_type(mut self, cond: bool) -> Self {
+        self.root_cause_type = cond;
+        self
+    }
+
+    /// The number of application frames from the top of the backtrace that
+    /// contribute, defaults to 5
+    ///
+    /// Only the symbol names of frames left visible by the frame filters and
+    /// outside of the standard library and dependencies are used, without
+    /// their hash suffixes.
+    pub fn frames(mut self, n: usize) -> Self {
+        self.frames = n;
+        self
+    }
+}
+
+impl Default for FingerprintOptions {
+    fn default() -> Self {
+        Self {
+            messages: true,
+            root_cause_type: true,
+            frames: DEFAULT_FRAMES,
+        }
+    }
+}
+
+/// Compute the [`Fingerprint`] of an error report with the default [`FingerprintOptions`]
+///
+/// # Details
+///
+/// Cosmetic differences between reports of the same failure, such as ids,
+/// addresses, ports, line numbers, and paths in error messages, or the
+/// addresses and hash suffixes of backtrace frames, don't change the
+/// fingerprint.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error};
+///
+/// color_anyhow::install().unwrap();
+///
+/// fn startup(error: Error) -> Error {
+///     Err::<(), _>(error).context("startup failed").unwrap_err()
+/// }
+///
+/// fn load(detail: &str) -> Error {
+///     startup(anyhow!("could not load {}", detail))
+/// }
+///
+/// let fingerprint = |detail| color_anyhow::fingerprint(&load(detail));
+/// let base = fingerprint("/home/alice/app/config.toml");
+///
+/// assert_eq!(base.to_string().len(), 16);
+/// assert_eq!(base, fingerprint("C:\\Users\\bob\\app\\config.toml"));
+/// assert_eq!(base, fingerprint("./config.toml"));
+///
+/// let base = fingerprint("session 7f9c24e8-3b1a-4c2d-9e8f-0a1b2c3d4e5f");
+/// assert_eq!(base, fingerprint("session 00000000-aaaa-bbbb-cccc-123456789abc"));
+/// assert_ne!(base, fingerprint("session"));
+///
+/// let base = fingerprint("object at 0x7ffd5c3a9e10");
+/// assert_eq!(base, fingerprint("object at 0x1"));
+/// assert_eq!(base, fingerprint("object at 7ffd5c3a9e10"));
+/// assert_ne!(base, fingerprint("object at home"));
+///
+/// let base = fingerprint("chunk 12 of 40 at src/main.rs:20:5");
+/// assert_eq!(base, fingerprint("chunk 3 of 7 at src/lib.rs:4:1"));
+/// assert_eq!(base, fingerprint("chunk  3 of 7 at  src/lib.rs:4:1"));
+/// assert_ne!(base, fingerprint("chunk 12 of 40"));
+/// assert_ne!(base, fingerprint("block 12 of 40 at src/main.rs:20:5"));
+///
+/// let base = fingerprint("cache.rs:112 failed");
+/// assert_eq!(base, fingerprint("cache.rs:7 failed"));
+/// assert_ne!(base, fingerprint("store.rs:112 failed"));
+///
+/// // the whole chain contributes, not just the outermost message
+/// assert_ne!(
+///     color_anyhow::fingerprint(&load("config.toml")),
+///     color_anyhow::fingerprint(&startup(anyhow!("something else"))),
+/// );
+/// ```
+///
+/// [`Fingerprint`]: struct.Fingerprint.html
+/// [`FingerprintOptions`]: struct.FingerprintOptions.html
+pub fn fingerprint(error: &anyhow::Error) -> Fingerprint {
+    fingerprint_with(error, &FingerprintOptions::default())
+}
+
+/// Compute the [`Fingerprint`] of an error report, with `options` selecting
+/// which parts of the report contribute
+///
+/// [`Fingerprint`]: struct.Fingerprint.html
+pub fn fingerprint_with(error: &anyhow::Error, options: &FingerprintOptions) -> Fingerprint {
+    compute(
+        error.as_ref(),
+        error.handler().downcast_ref::<Handler>(),
+        options,
+    )
+}
+
+/// Compute the fingerprint of the report for `error`, whose backtrace was captured by `handler`
+pub(crate) fn compute(
+    error: &(dyn std::error::Error + 'static),
+    handler: Option<&Handler>,
+    options: &FingerprintOptions,
+) -> Fingerprint {
+    let mut hasher = Fnv::default();
+
+    if options.messages {
+        for cause in anyhow::Chain::new(error) {
+            hasher.component(b'm', &normalize_message(&cause.to_string()));
+        }
+    }
+
+    if options.root_cause_type {
+        if let Some(root_cause) = anyhow::Chain::new(error).last() {
+            let root_cause = format!("{:?}", root_cause);
+            if let Some(name) = type_name(&root_cause) {
+                hasher.component(b't', name);
+            }
+        }
+    }
+
+    if options.frames > 0 {
+        if let Some(handler) = handler {
+            handler
+                .frame_infos()
+                .into_iter()
+                .filter(|frame| !frame.hidden)
+                .filter_map(|frame| {
+                    let frame = Frame {
+                        n: frame.n,
+                        name: frame.name,
+                        lineno: frame.lineno,
+                        filename: frame.filename,
+                    };
+
+                    match frame.name {
+                        Some(ref name) if !frame.is_dependency_code() => {
+                            Some(strip_hash_suffix(name).to_owned())
+                        }
+                        _ => None,
+                    }
+                })
+                .take(options.frames)
+                .for_each(|name| hasher.component(b'f', &name));
+        }
+    }
+
+    Fingerprint(hasher.0)
+}
+
+/// 64 bit FNV-1a, which unlike std's hashers is guaranteed to stay the same
+/// across Rust versions
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Hash one tagged, length prefixed part of a report, so parts can't run
+    /// into each other
+    fn component(&mut self, tag: u8, text: &str) {
+        self.write(&[tag]);
+        self.write(&(text.len() as u64).to_le_bytes());
+        self.write(text.as_bytes());
+    }
+}
+
+/// Returns the leading type name of a `Debug` representation
+fn type_name(debug: &str) -> Option<&str> {
+    if !debug.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let end = debug
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(debug.len());
+
+    Some(&debug[..end])
+}
+
+/// Strip the `::h0123456789abcdef` suffix rustc adds to symbol names
+fn strip_hash_suffix(name: &str) -> &str {
+    let has_hash_suffix = name.len() > 19
+        && &name[name.len() - 19..name.len() - 16] == "::h"
+        && name[name.len() - 16..]
+            .chars()
+            .all(|x| x.is_ascii_hexdigit());
+
+    if has_hash_suffix {
+        &name[..name.len() - 19]
+    } else {
+        name
+    }
+}
+
+/// Mask the parts of an error message that vary between occurrences of the
+/// same failure, collapsing runs of whitespace
+fn normalize_message(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+
+    for word in message.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+
+        let start = word
+            .find(|c: char| !"\"'`([{<".contains(c))
+            .unwrap_or(word.len());
+        let end = word[start..]
+            .rfind(|c: char| !"\"'`)]}>,.;:!?".contains(c))
+            .map_or(start, |i| {
+                start + i + word[start + i..].chars().next().unwrap().len_utf8()
+            });
+        let (prefix, core, suffix) = (&word[..start], &word[start..end], &word[end..]);
+
+        out.push_str(prefix);
+        if looks_like_path(core) {
+            out.push_str("<path>");
+        } else {
+            mask_numbers(&mut out, core);
+        }
+        out.push_str(suffix);
+    }
+
+    out
+}
+
+fn looks_like_path(word: &str) -> bool {
+    word.contains('/') || word.contains('\\')
+}
+
+/// Push `word` to `out` with UUIDs, hex numbers, and decimal numbers masked
+fn mask_numbers(out: &mut String, word: &str) {
+    let mut rest = word;
+
+    while let Some(c) = rest.chars().next() {
+        let previous_is_word = matches!(
+            out.chars().next_back(),
+            Some(c) if c.is_ascii_alphanumeric() || c == '_'
+        );
+
+        if !previous_is_word && is_uuid(rest) {
+            out.push_str("<uuid>");
+            rest = &rest[36..];
+        } else if !previous_is_word && hex_run_len(rest) >= 8 {
+            out.push_str("<hex>");
+            rest = &rest[hex_run_len(rest)..];
+        } else if !previous_is_word && (rest.starts_with("0x") || rest.starts_with("0X")) {
+            let len = 2 + rest[2..]
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len() - 2);
+            if len > 2 {
+                out.push_str("<hex>");
+                rest = &rest[len..];
+            } else {
+                out.push_str("<n>");
+                rest = &rest[1..];
+            }
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            out.push_str("<n>");
+            rest = &rest[len..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+}
+
+/// Whether `s` starts with a UUID like `7f9c24e8-3b1a-4c2d-9e8f-0a1b2c3d4e5f`
+fn is_uuid(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.len() < 36 {
+        return false;
+    }
+
+    let well_formed = bytes[..36].iter().enumerate().all(|(i, b)| match i {
+        8 | 13 | 18 | 23 => *b == b'-',
+        _ => b.is_ascii_hexdigit(),
+    });
+
+    well_formed && ends_word(&s[36..])
+}
+
+/// The length of the run of hex digits `s` starts with, if the run contains a
+/// decimal digit and ends a word, e.g. the `7ffd5c3a9e10` of an address
+fn hex_run_len(s: &str) -> usize {
+    let len = s.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(s.len());
+
+    if s[..len].contains(|c: char| c.is_ascii_digit()) && ends_word(&s[len..]) {
+        len
+    } else {
+        0
+    }
+}
+
+/// Whether `rest`, the text after a token, doesn't continue the token's word
+fn ends_word(rest: &str) -> bool {
+    !matches!(rest.chars().next(), Some(c) if c.is_ascii_alphanumeric() || c == '_')
+}
@@ -1,11 +1,25 @@
-use crate::config::installed_printer;
-use crate::ColorExt;
-use crate::{section::help::HelpInfo, writers::HeaderWriter, Handler};
-use ansi_term::Color::*;
+use crate::config::{
+    installed_printer, lib_verbosity, BlockSet, ChainStyle, FrameInfo, LineEnding, PanicHook,
+    ReportOptions, Verbosity,
+};
+use crate::{
+    section::help::{ErrorGroup, HelpInfo},
+    stats::{BlockBytes, ReportSummary},
+    writers::{strip_ansi, ByteCounter, CountingWriter, HeaderWriter},
+    Backtrace, Fingerprint, FingerprintOptions, Handler, HumanDuration,
+};
+use crate::{ColorExt, SectionExt};
+use ansi_term::{Color::*, Style};
 use indenter::{indented, Format};
+use std::cell::{Cell, RefCell};
 use std::fmt::Write;
+use std::ops::Range;
+use std::sync::atomic::Ordering;
 #[cfg(feature = "capture-spantrace")]
-use tracing_error::{ExtractSpanTrace, SpanTrace};
+use {
+    crate::writers::TraceSpan,
+    tracing_error::{ExtractSpanTrace, SpanTrace},
+};
 
 impl anyhow::ReportHandler for Handler {
     #[cfg(backtrace)]
@@ -28,6 +42,509 @@ impl anyhow::ReportHandler for Handler {
             return core::fmt::Debug::fmt(error, f);
         }
 
+        let printer = installed_printer();
+        let width = f
+            .width()
+            .or_else(|| printer.report_width(atty::Stream::Stderr));
+        let options = ReportOptions::default().with_width(width);
+        let theme = self.theme();
+        if printer.colors_enabled(atty::Stream::Stderr)
+            && printer.user_paths().is_none()
+            && printer.line_ending() == LineEnding::Lf
+            && theme.is_none()
+        {
+            self.render(error, f, &options)?;
+        } else {
+            let mut buf = String::with_capacity(report_capacity_hint(error));
+            self.render(error, &mut buf, &options)?;
+            if let Some(theme) = theme {
+                buf = theme.apply(&buf);
+            }
+            f.write_str(&finish_report(buf))?;
+        }
+
+        if printer.auto_marks_reported() {
+            self.reported.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+}
+
+/// Render an error report with the given `ReportOptions`
+///
+/// # Details
+///
+/// The report is rendered the same way as its `Debug` output, applying the
+/// installed hook's color and redaction settings, with `options` adjusting the
+/// rendering of this one report.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::{anyhow, Error}, config::{ColorChoice, HookBuilder, ReportOptions}};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Always)
+///     .add_frame_filter(Box::new(|frames| {
+///         frames.retain(|frame| {
+///             !matches!(&frame.name, Some(name) if name.contains("load_config"))
+///         })
+///     }))
+///     .install()
+///     .unwrap();
+///
+/// #[inline(never)]
+/// fn load_config() -> Error {
+///     anyhow!("config could not be loaded")
+/// }
+///
+/// let report = load_config();
+/// let handler = report
+///     .handler()
+///     .downcast_ref::<color_anyhow::Handler>()
+///     .unwrap();
+///
+/// // the filtered frame is collapsed by default, but still recorded
+/// assert!(!format!("{:?}", report).contains("load_config"));
+/// let n = (1..)
+///     .map(|n| handler.frame(n))
+///     .take_while(Option::is_some)
+///     .flatten()
+///     .find(|frame| matches!(&frame.name, Some(name) if name.contains("load_config")))
+///     .unwrap()
+///     .n;
+/// assert!(handler.hidden_frame_ranges().iter().any(|range| range.contains(&n)));
+///
+/// // and shown dimmed when expanding hidden frames
+/// let expanded = color_anyhow::format_report(
+///     &report,
+///     &ReportOptions::new().expand_hidden_frames(true),
+/// );
+/// let line = expanded.lines().find(|line| line.contains("load_config")).unwrap();
+/// assert!(line.starts_with("  \x1b[2m"));
+/// ```
+pub fn format_report(error: &anyhow::Error, options: &ReportOptions) -> String {
+    let handler = match error.handler().downcast_ref::<Handler>() {
+        Some(handler) => handler,
+        None => return format!("{:?}", error),
+    };
+
+    let mut buf = String::new();
+    let source: &(dyn std::error::Error + 'static) = error.as_ref();
+    handler
+        .render(source, &mut buf, options)
+        .expect("formatting into a String cannot fail");
+    if options.is_partial() {
+        // the line breaks separating the rendered blocks from the omitted ones
+        buf = buf.trim_matches('\n').to_owned();
+    }
+    if let Some(theme) = handler.theme() {
+        buf = theme.apply(&buf);
+    }
+    finish_report(buf)
+}
+
+/// Render a one line summary of an error along with its full report
+///
+/// # Details
+///
+/// The summary is the chain of error messages joined with `: `, the full
+/// report is the same as the error's `Debug` output. The installed hook's
+/// color and redaction settings are applied to both.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::{anyhow, Context}, config::{ColorChoice, HookBuilder}, Section};
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), _>(anyhow!("permission denied"))
+///     .context("could not read config.toml")
+///     .suggestion("check the file's permissions")
+///     .unwrap_err();
+/// let (summary, full) = color_anyhow::render_both(&report);
+///
+/// assert_eq!(summary, "could not read config.toml: permission denied");
+/// assert!(!summary.contains('\n'));
+/// assert!(summary.split(": ").all(|message| full.contains(message)));
+/// assert!(full.contains("check the file's permissions"));
+/// ```
+pub fn render_both(error: &anyhow::Error) -> (String, String) {
+    render_both_with(error, &ReportOptions::default())
+}
+
+/// Render a one line summary of an error along with its full report, with the
+/// given `ReportOptions`
+///
+/// # Details
+///
+/// Both are rendered the same way as with [`render_both`], with `options`
+/// applied to the full report. With [`ReportOptions::prefer_localized`] the
+/// summary starts with the localized message as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::{anyhow, Context}, config::{ColorChoice, HookBuilder, ReportOptions}, Section};
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), _>(anyhow!("permission denied"))
+///     .context("could not read config.toml")
+///     .localized("config.toml konnte nicht gelesen werden")
+///     .unwrap_err();
+/// let options = ReportOptions::new().prefer_localized(true);
+/// let (summary, _) = color_anyhow::render_both_with(&report, &options);
+///
+/// assert_eq!(summary, "config.toml konnte nicht gelesen werden: permission denied");
+/// ```
+///
+/// [`render_both`]: fn.render_both.html
+/// [`ReportOptions::prefer_localized`]: config/struct.ReportOptions.html#method.prefer_localized
+pub fn render_both_with(error: &anyhow::Error, options: &ReportOptions) -> (String, String) {
+    let handler = error.handler().downcast_ref::<Handler>();
+    let localized = handler.and_then(|handler| handler.localized_message(options));
+
+    let mut summary = String::new();
+    for (n, error) in error.chain().enumerate() {
+        if n > 0 {
+            summary.push_str(": ");
+        }
+        let message = match localized {
+            Some(localized) if n == 0 => localized.to_owned(),
+            _ => error.to_string(),
+        };
+        write!(summary, "{}", Red.make_intense().paint(message))
+            .expect("formatting into a String cannot fail");
+    }
+
+    if let Some(theme) = handler.and_then(Handler::theme) {
+        summary = theme.apply(&summary);
+    }
+
+    (finish_report(summary), format_report(error, options))
+}
+
+/// Render the report of `error` with its colors as ANSI escape codes, applying
+/// the installed redaction settings
+pub(crate) fn render_redacted(error: &anyhow::Error) -> String {
+    let mut report = String::new();
+    match error.handler().downcast_ref::<Handler>() {
+        Some(handler) => {
+            let source: &(dyn std::error::Error + 'static) = error.as_ref();
+            handler
+                .render(source, &mut report, &ReportOptions::default())
+                .expect("formatting into a String cannot fail");
+            if let Some(theme) = handler.theme() {
+                report = theme.apply(&report);
+            }
+        }
+        None => write!(report, "{:?}", error).expect("formatting into a String cannot fail"),
+    }
+
+    if let Some(user_paths) = installed_printer().user_paths() {
+        report = user_paths.redact(&report);
+    }
+
+    report
+}
+
+/// Apply the installed redaction and color settings to a rendered report
+pub(crate) fn finish_report(mut report: String) -> String {
+    let printer = installed_printer();
+    if let Some(user_paths) = printer.user_paths() {
+        report = user_paths.redact(&report);
+    }
+
+    if !printer.colors_enabled(atty::Stream::Stderr) {
+        report = strip_ansi(&report);
+    }
+
+    printer.line_ending().apply(report)
+}
+
+impl Handler {
+    /// The theme the report is rendered with, its own or the installed one
+    pub(crate) fn theme(&self) -> Option<&crate::Theme> {
+        self.theme.as_ref().or_else(|| installed_printer().theme())
+    }
+
+    /// Returns where the error was converted into a report by a `Section` method, if it was
+    ///
+    /// # Details
+    ///
+    /// Calling a `Section` method on a `Result` whose error isn't an `anyhow::Error` yet converts
+    /// the error into a report at that call, so its backtrace and `SpanTrace` are captured there
+    /// rather than where the error was created. The location of the first such call is recorded
+    /// and rendered dimmed below the chain of errors, as `(context attached at file:line)`.
+    /// Errors that already were reports, converted with `?` or `.context()` for instance, have
+    /// no location recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Handler, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let io_error = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+    /// let line = line!() + 1;
+    /// let report = Err::<(), _>(io_error).note("the cache is written on exit").unwrap_err();
+    ///
+    /// let handler = report.handler().downcast_ref::<Handler>().unwrap();
+    /// let location = handler.converted_at().unwrap();
+    /// assert_eq!((location.file(), location.line()), (file!(), line));
+    /// assert!(format!("{:?}", report).contains(&format!("(context attached at {}:{})", file!(), line)));
+    ///
+    /// let report = Err::<(), Error>(anyhow!("disk full"))
+    ///     .note("the cache is written on exit")
+    ///     .unwrap_err();
+    ///
+    /// let handler = report.handler().downcast_ref::<Handler>().unwrap();
+    /// assert!(handler.converted_at().is_none());
+    /// assert!(!format!("{:?}", report).contains("context attached at"));
+    /// ```
+    pub fn converted_at(&self) -> Option<&'static std::panic::Location<'static>> {
+        self.converted_at
+    }
+
+    /// Returns the revision of the report, the number of sections attached to it so far
+    ///
+    /// # Details
+    ///
+    /// The revision increases by one with every section attached, so it can be passed to
+    /// [`ReportOptions::mark_since`] to highlight the sections attached after it when the
+    /// report is rendered again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, Handler, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), _>(anyhow!("the upload failed"))
+    ///     .note("attempt 1 timed out")
+    ///     .warning("retries are almost exhausted")
+    ///     .unwrap_err();
+    ///
+    /// let handler = report.handler().downcast_ref::<Handler>().unwrap();
+    /// assert_eq!(handler.revision(), 2);
+    /// ```
+    ///
+    /// [`ReportOptions::mark_since`]: config/struct.ReportOptions.html#method.mark_since
+    pub fn revision(&self) -> u64 {
+        // sections are only ever appended, so the index of each section is the revision the
+        // report had when it was attached
+        self.sections.len() as u64
+    }
+
+    /// Returns the ranges of backtrace frame indices hidden by the installed
+    /// frame filters
+    ///
+    /// # Details
+    ///
+    /// Indices match the ones shown in the rendered backtrace, each range
+    /// corresponds to one "N frames hidden" marker. Returns an empty list if
+    /// no backtrace was captured.
+    pub fn hidden_frame_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for frame in self.frame_infos().into_iter().filter(|frame| frame.hidden) {
+            match ranges.last_mut() {
+                Some(range) if range.end == frame.n => range.end += 1,
+                _ => ranges.push(frame.n..frame.n + 1),
+            }
+        }
+
+        ranges
+    }
+
+    /// Returns the backtrace frame with the index `idx`, including frames
+    /// hidden by the installed frame filters
+    pub fn frame(&self, idx: usize) -> Option<FrameInfo> {
+        self.frame_infos().into_iter().find(|frame| frame.n == idx)
+    }
+
+    pub(crate) fn frame_infos(&self) -> Vec<FrameInfo> {
+        self.backtrace
+            .as_ref()
+            .map(|backtrace| installed_printer().frame_infos(backtrace))
+            .unwrap_or_default()
+    }
+
+    /// The last transport status code attached with `Section::status_hint`
+    pub(crate) fn status_hint(&self) -> Option<u16> {
+        self.sections
+            .iter()
+            .rev()
+            .find_map(|section| match section {
+                HelpInfo::Status(code) => Some(*code),
+                _ => None,
+            })
+    }
+
+    /// Render the full report for `error` into `f` one block at a time, in reverse if the
+    /// layout is reversed, after a table of contents listing the line each block starts at
+    /// if there is one
+    fn render_by_block<W: Write>(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut W,
+        options: &ReportOptions,
+        bytes: &mut BlockBytes,
+        shared: &Shared,
+    ) -> core::fmt::Result {
+        const BLOCKS: [(BlockSet, &str); 5] = [
+            (BlockSet::CHAIN, "Error chain"),
+            (BlockSet::SECTIONS, "Sections"),
+            (BlockSet::SPAN_TRACE, "SpanTrace"),
+            (BlockSet::BACKTRACE, "Backtrace"),
+            (BlockSet::HELP, "Help"),
+        ];
+        // the help text first and the chain of errors last, the traces stay next to each other
+        const REVERSED: [(BlockSet, &str); 5] =
+            [BLOCKS[4], BLOCKS[1], BLOCKS[3], BLOCKS[2], BLOCKS[0]];
+
+        let printer = installed_printer();
+        let order = if printer.reverse_layout() {
+            &REVERSED
+        } else {
+            &BLOCKS
+        };
+
+        let mut blocks = vec![];
+        for &(block, title) in order {
+            let mut rendered = String::new();
+            let options = options.clone().include(block);
+            self.render_blocks(
+                error,
+                &mut rendered,
+                &options,
+                &mut BlockBytes::default(),
+                shared,
+            )?;
+            // the line breaks separating the blocks are added back below
+            let rendered = rendered.trim_matches('\n');
+            if !rendered.is_empty() {
+                blocks.push((block, title, rendered.to_owned()));
+            }
+        }
+
+        if !printer.table_of_contents() {
+            for (n, (block, _, rendered)) in blocks.iter().enumerate() {
+                let separator = if n == 0 { "\n" } else { "\n\n" };
+                bytes.add(*block, separator.len() + rendered.len());
+            }
+            let rendered: Vec<_> = blocks
+                .into_iter()
+                .map(|(_, _, rendered)| rendered)
+                .collect();
+            write!(f, "\n{}", rendered.join("\n\n"))?;
+            return write_footer(f, false, options);
+        }
+
+        // the report starts with a line break, then the contents and an empty line
+        let mut line = 4;
+        let mut contents = vec![];
+        for (n, (_, title, rendered)) in blocks.iter().enumerate() {
+            contents.push(format!("{}. {} (line {})", n + 1, title, line));
+            line += rendered.lines().count() + 1;
+        }
+
+        write!(
+            f,
+            "\n{}: {}",
+            Cyan.make_intense().paint("Contents"),
+            contents.join("  ")
+        )?;
+        for (block, _, rendered) in &blocks {
+            bytes.add(*block, 2 + rendered.len());
+            write!(f, "\n\n{}", rendered)?;
+        }
+
+        write_footer(f, false, options)
+    }
+
+    /// Render the full report for `error` into `f`
+    pub(crate) fn render<W: Write>(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut W,
+        options: &ReportOptions,
+    ) -> core::fmt::Result {
+        // partial renders are counted as part of the report they belong to, if any
+        if options.is_partial() {
+            return self.render_report(error, f, options, &mut BlockBytes::default());
+        }
+
+        let outer = crate::stats::start_report();
+        let start = crate::clock::instant();
+        let mut bytes = BlockBytes::default();
+        let mut counted = CountingWriter::new(f);
+        let result = self.render_report(error, &mut counted, options, &mut bytes);
+        let rendered = crate::stats::finish_report(outer);
+
+        if let Some(observer) = installed_printer().report_observer() {
+            #[cfg(feature = "capture-spantrace")]
+            let span_trace = self.span_trace.is_some() || get_deepest_spantrace(error).is_some();
+            #[cfg(not(feature = "capture-spantrace"))]
+            let span_trace = false;
+
+            observer(&ReportSummary::new(
+                (self.backtrace.is_some(), span_trace),
+                rendered,
+                bytes,
+                counted.written,
+                crate::clock::instant().saturating_duration_since(start),
+            ));
+        }
+
+        result
+    }
+
+    fn render_report<W: Write>(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut W,
+        options: &ReportOptions,
+        bytes: &mut BlockBytes,
+    ) -> core::fmt::Result {
+        let printer = installed_printer();
+        let shared = Shared::new(self, error);
+        if (printer.table_of_contents() || printer.reverse_layout()) && !options.is_partial() {
+            return self.render_by_block(error, f, options, bytes, &shared);
+        }
+
+        self.render_blocks(error, f, options, bytes, &shared)
+    }
+
+    /// Render the blocks of the report for `error` included in `options` into `f`, in order
+    fn render_blocks<W: Write>(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut W,
+        options: &ReportOptions,
+        bytes: &mut BlockBytes,
+        shared: &Shared,
+    ) -> core::fmt::Result {
+        let printer = installed_printer();
+        let f = &mut CountingWriter::new(f);
+
+        let _guard = match RenderGuard::enter(self) {
+            Ok(guard) => guard,
+            Err(marker) => return f.write_str(marker),
+        };
+        let deadline = printer.render_deadline();
+
         #[cfg(feature = "capture-spantrace")]
         let errors = anyhow::Chain::new(error)
             .filter(|e| e.span_trace().is_none())
@@ -36,12 +553,26 @@ impl anyhow::ReportHandler for Handler {
         #[cfg(not(feature = "capture-spantrace"))]
         let errors = anyhow::Chain::new(error).enumerate();
 
-        let mut buf = String::new();
-        for (n, error) in errors {
-            buf.clear();
-            write!(&mut buf, "{}", error).unwrap();
-            writeln!(f)?;
-            write!(indented(f).ind(n), "{}", Red.make_intense().paint(&buf))?;
+        let provided: Vec<_> = errors
+            .clone()
+            .filter_map(|(n, error)| Some((n, provided_backtrace(error)?)))
+            .collect();
+
+        // the entry of the chain each inline hint is rendered under, the others are notes
+        let hints_at: Vec<_> = self
+            .inline_hints
+            .iter()
+            .map(|hint| {
+                errors
+                    .clone()
+                    .find(|(_, error)| (hint.matches)(*error))
+                    .map(|(n, _)| n)
+                    .filter(|_| options.includes(BlockSet::CHAIN))
+            })
+            .collect();
+
+        if options.includes(BlockSet::CHAIN) {
+            self.render_chain(errors, &hints_at, f, options)?;
         }
 
         let separated = &mut HeaderWriter {
@@ -50,65 +581,544 @@ impl anyhow::ReportHandler for Handler {
             started: false,
         };
 
-        for section in self
-            .sections
-            .iter()
-            .filter(|s| matches!(s, HelpInfo::Error(_)))
-        {
-            write!(separated.ready(), "{}", section)?;
+        let chain = options.includes(BlockSet::CHAIN);
+        if let (true, Some(location)) = (chain, self.converted_at) {
+            let provenance = format!(
+                "(context attached at {}:{})",
+                location.file(),
+                location.line()
+            );
+            write!(
+                separated.ready(),
+                "{}",
+                Style::new().dimmed().paint(provenance)
+            )?;
         }
 
-        for section in self
-            .sections
-            .iter()
-            .filter(|s| matches!(s, HelpInfo::Custom(_)))
-        {
-            write!(separated.ready(), "{}", section)?;
+        #[cfg(feature = "highlight-patterns")]
+        if chain {
+            let highlights = printer.highlights(error);
+            if !highlights.is_empty() {
+                let bold = Style::new().bold();
+                let lines: Vec<_> = highlights
+                    .iter()
+                    .map(|line| bold.paint(line.as_str()).to_string())
+                    .collect();
+                write!(separated.ready(), "{}", lines.join("\n"))?;
+            }
         }
 
-        #[cfg(feature = "capture-spantrace")]
-        {
-            if let Some(span_trace) = self
-                .span_trace
-                .as_ref()
-                .or_else(|| get_deepest_spantrace(error))
+        if let (true, Some(uptime)) = (chain, printer.error_report_uptime()) {
+            let line = format!("process uptime: {}", HumanDuration(uptime));
+            write!(separated.ready(), "{}", Style::new().dimmed().paint(line))?;
+        }
+
+        if let (true, Some(build)) = (chain, printer.error_report_build_info()) {
+            let line = format!("Build: {}", build);
+            write!(separated.ready(), "{}", Style::new().dimmed().paint(line))?;
+        }
+
+        if chain && self.capture_skipped {
+            let note = "trace capture skipped (unsafe context)";
+            write!(separated.ready(), "{}", Style::new().dimmed().paint(note))?;
+        }
+
+        if let (true, Some(tree)) = (chain, crate::tree::find_tree(error)) {
+            crate::tree::write_causes(&mut separated.ready(), tree)?;
+        }
+        bytes.record(BlockSet::CHAIN, separated.inner.written);
+
+        let render_section = |revision: u64, section: &HelpInfo| {
+            let rendered = match options.width() {
+                Some(width) => format!("{:width$}", section, width = width),
+                None => section.to_string(),
+            };
+
+            if options.is_new(revision) {
+                badge_new(&rendered)
+            } else {
+                rendered
+            }
+        };
+
+        if options.includes(BlockSet::SECTIONS) {
+            let chain_messages: Vec<String> = if printer.dedups_error_sections_against_chain() {
+                anyhow::Chain::new(error).map(ToString::to_string).collect()
+            } else {
+                vec![]
+            };
+
+            // identical error sections are grouped in order of first occurrence
+            let mut groups: Vec<ErrorSectionGroup<'_>> = vec![];
+            for (revision, section) in (0..).zip(&self.sections) {
+                let (error, label, backtrace): (&(dyn std::error::Error + 'static), _, _) =
+                    match section {
+                        HelpInfo::Error(error, label, backtrace) => (
+                            error.as_ref(),
+                            label.as_deref(),
+                            backtrace
+                                .as_ref()
+                                .filter(|_| !printer.suppresses_backtrace(error.as_ref())),
+                        ),
+                        HelpInfo::Report(report) => (
+                            report.as_ref(),
+                            None,
+                            report
+                                .handler()
+                                .downcast_ref::<Handler>()
+                                .and_then(|handler| handler.backtrace.as_ref())
+                                .filter(|_| !printer.suppresses_backtrace(report.as_ref())),
+                        ),
+                        _ => continue,
+                    };
+
+                if !chain_messages.is_empty() && chain_messages.contains(&error.to_string()) {
+                    continue;
+                }
+
+                let group = ErrorGroup {
+                    error,
+                    count: 1,
+                    labels: &[],
+                };
+                let mut key = group.to_string();
+                if let Some(backtrace) = backtrace {
+                    write!(key, "{:?}", printer.frame_infos(backtrace))?;
+                }
+
+                let new = options.is_new(revision);
+                let existing = groups.iter_mut().find(|(other, ..)| *other == key);
+                match existing {
+                    Some((_, group, labels, _, group_new)) if printer.groups_errors() => {
+                        group.count += 1;
+                        labels.extend(label);
+                        *group_new |= new;
+                    }
+                    _ => groups.push((key, group, label.into_iter().collect(), backtrace, new)),
+                }
+            }
+
+            let shown = printer
+                .max_error_sections()
+                .map_or(groups.len(), |max| max.min(groups.len()));
+            let suppressed: usize = groups[shown..]
+                .iter()
+                .map(|(_, group, ..)| group.count)
+                .sum();
+
+            // backtraces of attached reports are rendered once per distinct backtrace when shared
+            let mut shared: Vec<(Vec<FrameInfo>, &Backtrace)> = vec![];
+            for (_, group, labels, backtrace, new) in &groups[..shown] {
+                let mut block = ErrorGroup { labels, ..*group }.to_string();
+
+                if let Some(backtrace) = backtrace {
+                    if printer.shares_backtraces() {
+                        let frames = printer.frame_infos(backtrace);
+                        let n = match shared.iter().position(|(other, _)| *other == frames) {
+                            Some(n) => n,
+                            None => {
+                                shared.push((frames, backtrace));
+                                shared.len() - 1
+                            }
+                        };
+                        write!(block, "\n   backtrace: shared backtrace #{}", n + 1)?;
+                    } else {
+                        write!(block, "\n\n")?;
+                        write!(
+                            indented(&mut block).with_format(Format::Uniform { indentation: "  " }),
+                            "{}",
+                            printer.format_backtrace(backtrace).deadline(deadline)
+                        )?;
+                    }
+                }
+
+                if *new {
+                    block = badge_new(&block);
+                }
+                write!(separated.ready(), "{}", block.trim_end_matches('\n'))?;
+            }
+
+            if suppressed > 0 {
+                crate::stats::truncated();
+                let line = format!("(and {} more errors suppressed)", suppressed);
+                write!(separated.ready(), "{}", Style::new().dimmed().paint(line))?;
+            }
+
+            for (n, (_, backtrace)) in shared.iter().enumerate() {
+                let mut block = format!("Shared backtrace #{}:\n\n", n + 1);
+                write!(
+                    indented(&mut block).with_format(Format::Uniform { indentation: "  " }),
+                    "{}",
+                    printer.format_backtrace(backtrace).deadline(deadline)
+                )?;
+                write!(separated.ready(), "{}", block.trim_end_matches('\n'))?;
+            }
+
+            for (revision, section) in (0..)
+                .zip(&self.sections)
+                .filter(|(_, s)| matches!(s, HelpInfo::Custom(_)))
             {
+                write!(separated.ready(), "{}", render_section(revision, section))?;
+            }
+
+            let snippets = lib_verbosity() == Verbosity::Full;
+            for location in self.source_locations.iter().take(MAX_SOURCE_LOCATIONS) {
+                location.write(&mut separated.ready(), snippets)?;
+            }
+            if self.source_locations.len() > MAX_SOURCE_LOCATIONS {
+                let more = self.source_locations.len() - MAX_SOURCE_LOCATIONS;
+                let line = format!("… and {} more source locations", more);
+                write!(separated.ready(), "{}", Style::new().dimmed().paint(line))?;
+            }
+
+            #[cfg(feature = "json")]
+            if let Some(json) = &self.json_source {
+                crate::snippet::write_json_snippet(&mut separated.ready(), error, json)?;
+            }
+
+            if crate::snapshot::debug_enabled() {
+                let snapshot = crate::current_config().header("color-anyhow config:");
+                write!(separated.ready(), "{}", snapshot)?;
+            }
+        }
+        bytes.record(BlockSet::SECTIONS, separated.inner.written);
+
+        #[cfg(feature = "capture-spantrace")]
+        if options.includes(BlockSet::SPAN_TRACE) {
+            // timestamps are only recorded for span traces captured by this handler
+            let (span_trace, timestamps) = match self.span_trace.as_ref() {
+                Some(span_trace) => (Some(span_trace), self.span_timestamps.as_deref()),
+                None => (get_deepest_spantrace(error), None),
+            };
+
+            if let Some(span_trace) = span_trace {
                 write!(
                     &mut separated.ready(),
                     "{}",
-                    crate::writers::FormattedSpanTrace(span_trace)
+                    crate::writers::FormattedSpanTrace {
+                        span_trace,
+                        timestamps,
+                        self_field: printer.spantrace_self_field(),
+                        max_path_width: printer.max_path_width(),
+                        collapse_same_target: printer.collapse_same_target_spans(),
+                    }
                 )?;
+                crate::stats::span_trace_displayed();
+            }
+        }
+        bytes.record(BlockSet::SPAN_TRACE, separated.inner.written);
+
+        // remediation steps are displayed after all other help text
+        let help = || {
+            let sections = || (0..).zip(&self.sections);
+            sections()
+                .filter(|(_, s)| s.is_help() && !matches!(s, HelpInfo::Remediation(_)))
+                .chain(
+                    sections()
+                        .filter(|(_, s)| s.is_help() && matches!(s, HelpInfo::Remediation(_))),
+                )
+        };
+
+        let status = if printer.displays_status_hints() {
+            (0..)
+                .zip(&self.sections)
+                .filter(|(_, s)| matches!(s, HelpInfo::Status(_)))
+                .last()
+        } else {
+            None
+        };
+
+        let unmatched_hints = || {
+            self.inline_hints
+                .iter()
+                .zip(&hints_at)
+                .filter(|(_, at)| at.is_none())
+                .map(|(hint, _)| &hint.hint)
+        };
+
+        let runbook = self
+            .category
+            .as_deref()
+            .and_then(|category| printer.runbook(category));
+
+        let fingerprint = shared.fingerprint.as_ref();
+
+        let report_id = if options.includes(BlockSet::HELP) {
+            printer.report_id()
+        } else {
+            None
+        };
+
+        let mut backtraces = vec![];
+        if options.includes(BlockSet::BACKTRACE) && !printer.suppresses_backtrace(error) {
+            backtraces.extend(self.backtrace.as_ref().map(|backtrace| {
+                let formatter = printer.format_backtrace(backtrace).deadline(deadline);
+                #[cfg(feature = "capture-spantrace")]
+                let formatter = formatter.spans(&shared.spans);
+                formatter
+            }));
+
+            if options.shows_all_backtraces() {
+                for (n, backtrace) in
+                    distinct_backtraces(printer, self.backtrace.as_ref(), provided)
+                {
+                    backtraces.push(
+                        printer
+                            .format_backtrace(backtrace)
+                            .cause(n)
+                            .deadline(deadline),
+                    );
+                }
+            }
+        }
+
+        let has_backtraces = !backtraces.is_empty();
+        if has_backtraces {
+            crate::stats::backtrace_displayed();
+        }
+        // rendered backtraces end with a line break
+        let mut ends_with_newline = has_backtraces;
+        for (n, fmted_bt) in backtraces.into_iter().enumerate() {
+            let fmted_bt = match options.filter_provenance() {
+                Some(cond) => fmted_bt.filter_provenance(cond),
+                None => fmted_bt,
+            };
+            let fmted_bt = match options.expands_hidden_frames() {
+                true => fmted_bt.expand_hidden(true),
+                false => fmted_bt,
+            };
+            if n == 0 {
+                write!(
+                    indented(&mut separated.ready())
+                        .with_format(Format::Uniform { indentation: "  " }),
+                    "{}",
+                    fmted_bt
+                )?;
+            } else {
+                writeln!(separated.inner)?;
+                write!(
+                    indented(&mut separated.inner)
+                        .with_format(Format::Uniform { indentation: "  " }),
+                    "{}",
+                    fmted_bt
+                )?;
+            }
+        }
+
+        let hints = if printer.displays_env_hints() && options.includes(BlockSet::BACKTRACE) {
+            self.env_hints(error, has_backtraces)
+        } else {
+            vec![]
+        };
+        if !hints.is_empty() {
+            if has_backtraces {
+                write!(separated.inner, "\n{}", hints.join("\n"))?;
+            } else {
+                write!(separated.ready(), "{}", hints.join("\n"))?;
             }
+            ends_with_newline = false;
         }
+        bytes.record(BlockSet::BACKTRACE, separated.inner.written);
 
-        if let Some(backtrace) = self.backtrace.as_ref() {
-            let fmted_bt = installed_printer().format_backtrace(backtrace);
+        if !options.includes(BlockSet::HELP) {
+            return Ok(());
+        }
+
+        let has_help = !self.breadcrumbs.is_empty()
+            || help().next().is_some()
+            || unmatched_hints().next().is_some()
+            || runbook.is_some()
+            || status.is_some()
+            || fingerprint.is_some()
+            || report_id.is_some();
+        if !ends_with_newline && has_help {
+            writeln!(f)?;
+        }
 
+        if !self.breadcrumbs.is_empty() {
+            let breadcrumbs: Vec<_> = self.breadcrumbs.iter().rev().map(String::as_str).collect();
             write!(
-                indented(&mut separated.ready()).with_format(Format::Uniform { indentation: "  " }),
-                "{}",
-                fmted_bt
+                f,
+                "\n{}: {}",
+                Cyan.make_intense().paint("Context"),
+                breadcrumbs.join(" > ")
             )?;
-        } else if self
-            .sections
-            .iter()
-            .any(|s| !matches!(s, HelpInfo::Custom(_) | HelpInfo::Error(_)))
+        }
+
+        // hints without a matching entry are notes, displayed before the remediation steps
+        let mut hints = Some(unmatched_hints());
+        for (revision, section) in help() {
+            if let (HelpInfo::Remediation(_), Some(hints)) = (section, hints.take()) {
+                write_notes(f, hints)?;
+            }
+            write!(f, "\n{}", render_section(revision, section))?;
+        }
+        if let Some(hints) = hints {
+            write_notes(f, hints)?;
+        }
+
+        if let Some(url) = runbook {
+            write!(f, "\n{}: {}", Cyan.make_intense().paint("Runbook"), url)?;
+        }
+
+        if let Some((revision, status)) = status {
+            write!(f, "\n{}", render_section(revision, status))?;
+        }
+
+        if let Some(fingerprint) = fingerprint {
+            write!(
+                f,
+                "\n{}: {}",
+                Cyan.make_intense().paint("Fingerprint"),
+                fingerprint
+            )?;
+        }
+
+        if let Some(report_id) = report_id {
+            write!(
+                f,
+                "\n{}: {}",
+                Cyan.make_intense().paint("Report ID"),
+                report_id
+            )?;
+        }
+
+        bytes.record(BlockSet::HELP, f.written);
+
+        write_footer(f, ends_with_newline && !has_help, options)
+    }
+
+    /// The hints for the environment variables that would add the traces missing from the
+    /// report of `error`
+    fn env_hints(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        has_backtraces: bool,
+    ) -> Vec<&'static str> {
+        let printer = installed_printer();
+        let mut hints = vec![];
+
+        if !has_backtraces
+            && self.backtrace.is_none()
+            && lib_verbosity() == Verbosity::Minimal
+            && !printer.suppresses_backtrace(error)
         {
-            writeln!(f)?;
+            hints.push(
+                "Run with RUST_LIB_BACKTRACE=1 environment variable to display the backtrace.",
+            );
         }
 
-        for section in self
-            .sections
-            .iter()
-            .filter(|s| !matches!(s, HelpInfo::Custom(_) | HelpInfo::Error(_)))
+        #[cfg(feature = "capture-spantrace")]
+        if self.span_trace.is_none()
+            && get_deepest_spantrace(error).is_none()
+            && !printer.spantrace_capture_enabled()
         {
-            write!(f, "\n{}", section)?;
+            hints.push("Run with RUST_SPANTRACE=1 environment variable to capture a SpanTrace.");
+        }
+
+        hints
+    }
+
+    /// The localized message rendered in place of the outermost error's, if it is preferred
+    fn localized_message(&self, options: &ReportOptions) -> Option<&str> {
+        self.localized
+            .as_deref()
+            .filter(|_| options.prefers_localized())
+    }
+
+    /// Render the chain of errors, headed by the report header, the severity banner and the
+    /// chain label
+    fn render_chain<'a, W: Write>(
+        &self,
+        errors: impl Iterator<Item = (usize, &'a (dyn std::error::Error + 'static))>,
+        hints_at: &[Option<usize>],
+        f: &mut W,
+        options: &ReportOptions,
+    ) -> core::fmt::Result {
+        let printer = installed_printer();
+        let label = match &self.chain_label {
+            Some(label) => Some(Some(label.as_str())),
+            None => printer.chain_label(),
+        };
+        let banner = self.severity.filter(|_| printer.severity_banner());
+        if let Some(header) = printer.report_header() {
+            write!(f, "{}", header)?;
+            // a leading line break ends the header's line, if the report starts with one
+            if banner.is_some() || label.is_some() {
+                writeln!(f)?;
+            }
+        }
+        if let Some(severity) = banner {
+            write!(f, "{}", crate::severity::banner(severity, options.width()))?;
+            // the chain starts on the next line unless a leading line break follows anyway
+            if label.is_some() {
+                writeln!(f)?;
+            }
+        }
+        if let Some(Some(label)) = label {
+            write!(f, "{}", Red.make_intense().bold().paint(label))?;
+        }
+
+        let localized = self.localized_message(options);
+        let mut buf = CHAIN_BUFFER.with(Cell::take);
+        for (n, error) in errors {
+            buf.clear();
+            match localized {
+                Some(localized) if n == 0 => buf.push_str(localized),
+                _ => write!(&mut buf, "{}", error).unwrap(),
+            }
+            // without a label the report starts with the chain itself
+            if n > 0 || label != Some(None) {
+                writeln!(f)?;
+            }
+            let message = Red.make_intense().paint(buf.as_str());
+            write_chain_entry(f, printer.chain_style(), n, &message)?;
+
+            if provided_backtrace(error).is_some() {
+                write!(f, " {}", Style::new().dimmed().paint("(has backtrace)"))?;
+            }
+
+            let hints = self.inline_hints.iter().zip(hints_at);
+            for (hint, _) in hints.filter(|(_, at)| **at == Some(n)) {
+                writeln!(f)?;
+                write!(
+                    indented(&mut *f).with_format(Format::Uniform {
+                        indentation: hint_indentation(printer.chain_style()),
+                    }),
+                    "{}: {}",
+                    Cyan.make_intense().paint("Hint"),
+                    hint.hint
+                )?;
+            }
+        }
+
+        if buf.capacity() <= MAX_CHAIN_BUFFER_CAPACITY {
+            CHAIN_BUFFER.with(|cell| cell.set(buf));
         }
 
         Ok(())
     }
 }
 
+/// An error section group while grouping: its key, the group, its labels, the backtrace of
+/// the attached report and whether any of its sections is marked as new
+type ErrorSectionGroup<'a> = (
+    String,
+    ErrorGroup<'a>,
+    Vec<&'a str>,
+    Option<&'a Backtrace>,
+    bool,
+);
+
+/// Add a dimmed `[new]` badge to the end of the first line of a rendered section
+fn badge_new(section: &str) -> String {
+    let badge = Style::new().dimmed().paint("[new]");
+    match section.find('\n') {
+        Some(end) => format!("{} {}{}", &section[..end], badge, &section[end..]),
+        None => format!("{} {}", section, badge),
+    }
+}
+
 impl ColorExt for ansi_term::Color {
     fn make_intense(self) -> Self {
         use ansi_term::Color::*;
@@ -146,3 +1156,194 @@ pub(crate) fn get_deepest_spantrace<'a>(
         .flat_map(|error| error.span_trace())
         .next()
 }
+
+/// What the blocks of a report render from the whole report, computed once per render
+struct Shared {
+    /// The fingerprint of the report, if it is displayed
+    fingerprint: Option<Fingerprint>,
+    /// The spans of the `SpanTrace` the frames of the backtrace are annotated with
+    #[cfg(feature = "capture-spantrace")]
+    spans: Vec<TraceSpan>,
+}
+
+impl Shared {
+    fn new(handler: &Handler, error: &(dyn std::error::Error + 'static)) -> Self {
+        let printer = installed_printer();
+        let fingerprint = if printer.displays_fingerprint() {
+            Some(crate::fingerprint::compute(
+                error,
+                Some(handler),
+                &FingerprintOptions::default(),
+            ))
+        } else {
+            None
+        };
+
+        Self {
+            fingerprint,
+            #[cfg(feature = "capture-spantrace")]
+            spans: printer.correlated_spans(
+                handler
+                    .span_trace
+                    .as_ref()
+                    .or_else(|| get_deepest_spantrace(error)),
+            ),
+        }
+    }
+}
+
+/// The indentation aligning the lines under an entry of the chain with its message
+fn hint_indentation(style: ChainStyle) -> &'static str {
+    match style {
+        ChainStyle::Numbered => "      ",
+        ChainStyle::Bulleted(_) => "     ",
+        ChainStyle::Plain => "    ",
+    }
+}
+
+/// Write each of `notes` on its own line, as a `Note:` help section
+fn write_notes<'a, W: Write>(
+    f: &mut W,
+    notes: impl Iterator<Item = &'a String>,
+) -> core::fmt::Result {
+    for note in notes {
+        write!(f, "\n{}: {}", Cyan.make_intense().paint("Note"), note)?;
+    }
+    Ok(())
+}
+
+/// Write the footer configured with `HookBuilder::support_footer` at the end of a full report,
+/// after an empty line
+fn write_footer<W: Write>(
+    f: &mut W,
+    ends_with_newline: bool,
+    options: &ReportOptions,
+) -> core::fmt::Result {
+    match installed_printer().support_footer() {
+        Some(footer) if !options.is_partial() => {
+            let separator = if ends_with_newline { "\n" } else { "\n\n" };
+            write!(f, "{}{}", separator, footer)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Write the message of the error at index `n` of the chain, aligning the
+/// following lines of the message with its first one
+fn write_chain_entry<W: Write>(
+    f: &mut W,
+    style: ChainStyle,
+    n: usize,
+    message: &dyn core::fmt::Display,
+) -> core::fmt::Result {
+    match style {
+        ChainStyle::Numbered => write!(indented(f).ind(n), "{}", message),
+        ChainStyle::Bulleted(bullet) => write!(
+            indented(f).with_format(Format::Custom {
+                inserter: &mut move |line, f| {
+                    if line == 0 {
+                        write!(f, "{: >4} ", bullet)
+                    } else {
+                        f.write_str("     ")
+                    }
+                },
+            }),
+            "{}",
+            message
+        ),
+        ChainStyle::Plain => write!(indented(f).with_str("    "), "{}", message),
+    }
+}
+
+/// The backtrace `error` provides itself, independently of the report's handler
+#[cfg(backtrace)]
+fn provided_backtrace<'a>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a Backtrace> {
+    error.backtrace()
+}
+
+#[cfg(not(backtrace))]
+fn provided_backtrace<'a>(_: &'a (dyn std::error::Error + 'static)) -> Option<&'a Backtrace> {
+    None
+}
+
+/// The backtraces provided by the errors of the chain whose frames differ from
+/// the ones of `primary` and of every earlier provided backtrace
+fn distinct_backtraces<'a>(
+    printer: &PanicHook,
+    primary: Option<&Backtrace>,
+    provided: Vec<(usize, &'a Backtrace)>,
+) -> Vec<(usize, &'a Backtrace)> {
+    let mut seen: Vec<_> = primary
+        .map(|backtrace| printer.frame_infos(backtrace))
+        .into_iter()
+        .collect();
+
+    provided
+        .into_iter()
+        .filter(|(_, backtrace)| {
+            let frames = printer.frame_infos(backtrace);
+            let distinct = !seen.contains(&frames);
+            if distinct {
+                seen.push(frames);
+            }
+            distinct
+        })
+        .collect()
+}
+
+/// The maximum number of source locations rendered in a report, the rest are only counted
+const MAX_SOURCE_LOCATIONS: usize = 3;
+
+/// The maximum number of reports rendered within each other, such as reports
+/// embedded in the sections of other reports
+const MAX_NESTED_RENDERS: usize = 8;
+
+thread_local! {
+    /// The handlers of the reports currently being rendered on this thread
+    static RENDERING: RefCell<Vec<*const Handler>> = const { RefCell::new(Vec::new()) };
+
+    /// The buffer chain messages are formatted into, kept between renders so
+    /// rendering a report doesn't allocate a new one each time
+    static CHAIN_BUFFER: Cell<String> = const { Cell::new(String::new()) };
+}
+
+/// Chain buffers that grew larger than this are dropped rather than kept for the next render
+const MAX_CHAIN_BUFFER_CAPACITY: usize = 4096;
+
+/// A guess at the length of the report of `error`, from the length of its message
+///
+/// Reports are mostly their chain of messages plus a few lines of hints, so
+/// buffers of this size rarely have to grow while a report is rendered into them.
+fn report_capacity_hint(error: &(dyn std::error::Error + 'static)) -> usize {
+    let mut counter = ByteCounter::default();
+    let _ = write!(counter, "{}", error);
+    counter.0 * 4 + 256
+}
+
+/// Marks a handler as being rendered, until dropped
+///
+/// A report that ends up in its own sections, e.g. through an `Arc` cycle,
+/// would otherwise recurse until the stack overflows.
+struct RenderGuard;
+
+impl RenderGuard {
+    fn enter(handler: &Handler) -> Result<Self, &'static str> {
+        RENDERING.with(|rendering| {
+            let mut rendering = rendering.borrow_mut();
+            if rendering.contains(&(handler as *const Handler)) {
+                Err("<recursive report omitted>")
+            } else if rendering.len() >= MAX_NESTED_RENDERS {
+                Err("<deeply nested report omitted>")
+            } else {
+                rendering.push(handler);
+                Ok(RenderGuard)
+            }
+        })
+    }
+}
+
+impl Drop for RenderGuard {
+    fn drop(&mut self) {
+        RENDERING.with(|rendering| rendering.borrow_mut().pop());
+    }
+}
@@ -0,0 +1,274 @@
+//! Rendering of error reports as HTML
+use crate::config::installed_printer;
+use crate::handler::render_redacted;
+use crate::section::help::HelpInfo;
+use crate::writers::escape_len;
+use std::fmt::Write;
+
+/// The standard xterm colors for the first 16 entries of the 256 color palette
+const BASE_COLORS: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+/// Render an error report as HTML, preserving its colors with inline styles
+///
+/// # Details
+///
+/// The report is rendered the same way as its `Debug` output, including
+/// sections, backtraces and span traces, and placed in a `<pre>` element. All
+/// text taken from the report is HTML escaped. When `collapsible` is `true` the
+/// report is wrapped in a `<details>` element whose `<summary>` is the top
+/// level error message, so long reports can be collapsed on web pages, and so
+/// is each section given a summary with `IndentedSection::summary`.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, Section};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), _>(anyhow!("expected <config> & found nothing"))
+///     .suggestion("create a <config> file")
+///     .unwrap_err();
+/// let html = color_anyhow::report_to_html(&report, true);
+///
+/// assert!(html.starts_with("<details><summary>expected &lt;config&gt; &amp; found nothing</summary>"));
+/// assert!(html.contains("create a &lt;config&gt; file"));
+/// assert!(html.contains("<span style=\"color:#ff0000\">"));
+/// assert!(!html.contains("<config>"));
+///
+/// let html = color_anyhow::report_to_html(&report, false);
+/// assert!(html.starts_with("<pre>"));
+/// assert!(!html.contains("<details>"));
+/// ```
+///
+/// The summary is redacted like the rest of the report:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+///
+/// std::env::set_var("HOME", "/home/alice");
+/// std::env::set_var("USER", "alice");
+/// HookBuilder::blank().redact_user_paths(true).install().unwrap();
+///
+/// let report = anyhow!("could not read /home/alice/app.toml");
+/// let html = color_anyhow::report_to_html(&report, true);
+///
+/// assert!(html.starts_with("<details><summary>could not read ~/app.toml</summary>"));
+/// assert!(!html.contains("alice"));
+/// ```
+pub fn report_to_html(error: &anyhow::Error, collapsible: bool) -> String {
+    let report = render_redacted(error);
+    let user_paths = installed_printer().user_paths();
+
+    let mut html = String::new();
+    if collapsible {
+        let mut summary = error.to_string();
+        if let Some(user_paths) = user_paths {
+            summary = user_paths.redact(&summary);
+        }
+        html.push_str("<details><summary>");
+        push_escaped(&mut html, &summary);
+        html.push_str("</summary>");
+    }
+
+    html.push_str("<pre>");
+    let mut rest = report.trim_start_matches('\n');
+    if collapsible {
+        for (section, summary) in summarized_sections(error) {
+            let start = match rest.find(&section) {
+                Some(start) => start,
+                None => continue,
+            };
+
+            push_ansi_as_html(&mut html, &rest[..start]);
+            html.push_str("</pre><details><summary>");
+            push_escaped(&mut html, &summary);
+            html.push_str("</summary><pre>");
+            push_ansi_as_html(&mut html, &section);
+            html.push_str("</pre></details><pre>");
+            rest = &rest[start + section.len()..];
+        }
+    }
+    push_ansi_as_html(&mut html, rest);
+    html.push_str("</pre>");
+
+    if collapsible {
+        html.push_str("</details>");
+    }
+
+    html
+}
+
+/// The custom sections of the report of `error` that have a summary, rendered as they are
+/// in the report, along with their summary
+fn summarized_sections(error: &anyhow::Error) -> Vec<(String, String)> {
+    let handler = match error.handler().downcast_ref::<crate::Handler>() {
+        Some(handler) => handler,
+        None => return vec![],
+    };
+
+    handler
+        .sections
+        .iter()
+        .filter_map(|section| match section {
+            HelpInfo::Custom(section) => {
+                let summary = crate::section::summary_of(section)?;
+                let mut rendered = section.to_string();
+                if let Some(theme) = handler.theme() {
+                    rendered = theme.apply(&rendered);
+                }
+                if let Some(user_paths) = installed_printer().user_paths() {
+                    rendered = user_paths.redact(&rendered);
+                }
+                Some((rendered, summary))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The subset of SGR state that has a CSS equivalent
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Style {
+    color: Option<String>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    fn apply(&mut self, params: &str) {
+        let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dimmed = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.color = Some(BASE_COLORS[(param - 30) as usize].to_owned()),
+                90..=97 => self.color = Some(BASE_COLORS[(param - 90 + 8) as usize].to_owned()),
+                39 => self.color = None,
+                38 => match params.next() {
+                    Some(5) => self.color = params.next().map(palette_color),
+                    Some(2) => {
+                        let mut rgb = || params.next().unwrap_or(0);
+                        self.color = Some(format!("#{:02x}{:02x}{:02x}", rgb(), rgb(), rgb()));
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn css(&self) -> String {
+        let mut css = vec![];
+        if let Some(color) = &self.color {
+            css.push(format!("color:{}", color));
+        }
+        if self.bold {
+            css.push("font-weight:bold".to_owned());
+        }
+        if self.dimmed {
+            css.push("opacity:0.7".to_owned());
+        }
+        if self.italic {
+            css.push("font-style:italic".to_owned());
+        }
+        if self.underline {
+            css.push("text-decoration:underline".to_owned());
+        }
+        css.join(";")
+    }
+}
+
+/// Convert an entry of the 256 color palette to a CSS color
+fn palette_color(n: u8) -> String {
+    match n {
+        0..=15 => BASE_COLORS[n as usize].to_owned(),
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u8| if v == 0 { 0 } else { v * 40 + 55 };
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(n / 36),
+                level(n / 6 % 6),
+                level(n % 6)
+            )
+        }
+        _ => {
+            let gray = (n - 232) * 10 + 8;
+            format!("#{:02x}{:02x}{:02x}", gray, gray, gray)
+        }
+    }
+}
+
+/// Push `text` to `html`, converting SGR escape sequences into styled `<span>`s
+fn push_ansi_as_html(html: &mut String, text: &str) {
+    let mut style = Style::default();
+    let mut open = false;
+    let mut rest = text;
+
+    while let Some(start) = rest.find('\x1b') {
+        push_escaped(html, &rest[..start]);
+        rest = &rest[start..];
+        let len = escape_len(rest);
+        let escape = &rest[..len];
+        rest = &rest[len..];
+
+        // Only SGR sequences affect how the text looks, drop everything else
+        let params = match escape
+            .strip_prefix("\x1b[")
+            .and_then(|s| s.strip_suffix('m'))
+        {
+            Some(params) => params,
+            None => continue,
+        };
+
+        let mut next = style.clone();
+        next.apply(params);
+        if next == style {
+            continue;
+        }
+
+        if open {
+            html.push_str("</span>");
+            open = false;
+        }
+
+        style = next;
+        if style != Style::default() {
+            write!(html, "<span style=\"{}\">", style.css()).unwrap();
+            open = true;
+        }
+    }
+
+    push_escaped(html, rest);
+    if open {
+        html.push_str("</span>");
+    }
+}
+
+fn push_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Turning error reports into HTTP problem responses
+use crate::config::installed_printer;
+use crate::extract::{extract, SectionData};
+use crate::handler::render_redacted;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// An error report returned by an HTTP handler, with the status code of the
+/// response and a correlation ID tying the response to the logged report
+///
+/// # Details
+///
+/// `HttpReport` converts from anything that converts into an `anyhow::Error`,
+/// so handlers returning `Result<_, HttpReport>` can use `?` directly. The
+/// status code is the one set with [`status`], or else the last one attached
+/// with `Section::status_hint`, or else `500`.
+///
+/// [`log_and_respond`] sends the full report to stderr and the output sinks
+/// configured with `HookBuilder::add_output_sink`, and returns the response as
+/// a JSON problem details body without any traces, so internal details like
+/// backtraces never reach clients. Web frameworks only need a few lines of
+/// glue, e.g. for axum:
+///
+/// ```rust,ignore
+/// use axum::{http::{header, StatusCode}, response::{IntoResponse, Response}};
+///
+/// struct AppError(color_anyhow::HttpReport);
+///
+/// impl<E: Into<color_anyhow::HttpReport>> From<E> for AppError {
+///     fn from(error: E) -> Self {
+///         Self(error.into())
+///     }
+/// }
+///
+/// impl IntoResponse for AppError {
+///     fn into_response(self) -> Response {
+///         let (status, body) = self.0.log_and_respond();
+///         let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+///         (status, [(header::CONTENT_TYPE, "application/problem+json")], body).into_response()
+///     }
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, HttpReport, Section};
+///
+/// color_anyhow::install().unwrap();
+///
+/// fn find_user(id: u32) -> Result<String, HttpReport> {
+///     let user = Err(anyhow!("no row with the id {}", id))
+///         .context("the user could not be loaded")
+///         .note("users are soft deleted after 30 days")
+///         .status_hint(404)?;
+///     Ok(user)
+/// }
+///
+/// let report = find_user(42).unwrap_err();
+/// assert_eq!(report.status_code(), 404);
+///
+/// let (status, body) = report.log_and_respond();
+/// let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+///
+/// assert_eq!(status, 404);
+/// assert_eq!(body["status"], 404);
+/// assert_eq!(body["title"], "the user could not be loaded");
+/// assert_eq!(body["chain"][1], "no row with the id 42");
+/// assert_eq!(body["sections"][0]["kind"], "note");
+/// assert_eq!(body["sections"][0]["message"], "users are soft deleted after 30 days");
+/// assert!(body["sections"][0].get("code").is_none());
+/// assert_eq!(body["correlation_id"], report.correlation_id());
+/// assert!(body.get("backtrace").is_none());
+///
+/// let report = HttpReport::from(
+///     Err::<(), _>(anyhow!("the index is corrupt"))
+///         .try_command("myapp reindex")
+///         .try_command("myapp reindex --full  ")
+///         .unwrap_err(),
+/// );
+/// let body: serde_json::Value = serde_json::from_str(&report.log_and_respond().1).unwrap();
+/// assert_eq!(body["try_commands"], serde_json::json!(["myapp reindex", "myapp reindex --full  "]));
+/// assert_eq!(color_anyhow::try_commands_of(report.error()), ["myapp reindex", "myapp reindex --full  "]);
+/// assert!(body["sections"].as_array().unwrap().is_empty());
+///
+/// // an explicit status takes precedence over the attached one
+/// let report = find_user(42).unwrap_err().status(410);
+/// assert_eq!(report.log_and_respond().0, 410);
+///
+/// let report = HttpReport::from(anyhow!("the database is unreachable"));
+/// assert_eq!(report.status_code(), 500);
+///
+/// // coded help text carries its code
+/// let report = HttpReport::from(
+///     Err::<(), _>(anyhow!("the session expired"))
+///         .suggestion_coded("AUTH-7", "sign in again")
+///         .unwrap_err(),
+/// );
+/// let body: serde_json::Value = serde_json::from_str(&report.log_and_respond().1).unwrap();
+/// assert_eq!(
+///     body["sections"][0],
+///     serde_json::json!({ "kind": "suggestion", "message": "sign in again", "code": "AUTH-7" })
+/// );
+/// ```
+///
+/// [`status`]: #method.status
+/// [`log_and_respond`]: #method.log_and_respond
+#[cfg_attr(docsrs, doc(cfg(feature = "http")))]
+pub struct HttpReport {
+    error: anyhow::Error,
+    status: Option<u16>,
+    correlation_id: String,
+}
+
+impl HttpReport {
+    /// Wrap an error report, with a freshly generated correlation ID
+    pub fn new(error: anyhow::Error) -> Self {
+        Self {
+            error,
+            status: None,
+            correlation_id: next_correlation_id(),
+        }
+    }
+
+    /// Set the status code of the response, in place of the one attached to the report
+    pub fn status(mut self, code: u16) -> Self {
+        self.status = Some(code);
+        self
+    }
+
+    /// Use the given correlation ID, e.g. the ID of the request the report is returned for
+    pub fn correlation_id_from(mut self, id: impl Into<String>) -> Self {
+        self.correlation_id = id.into();
+        self
+    }
+
+    /// Returns the status code of the response
+    pub fn status_code(&self) -> u16 {
+        self.status
+            .or_else(|| crate::status_hint(&self.error))
+            .unwrap_or(500)
+    }
+
+    /// Returns the correlation ID shared by the logged report and the response
+    pub fn correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+
+    /// Returns the wrapped error report
+    pub fn error(&self) -> &anyhow::Error {
+        &self.error
+    }
+
+    /// Unwrap the error report
+    pub fn into_inner(self) -> anyhow::Error {
+        self.error
+    }
+
+    /// Log the full report and return the status code and JSON body of the response
+    ///
+    /// # Details
+    ///
+    /// The report is written to stderr, with colors if stderr is a terminal, and to every
+    /// output sink, headed by the correlation ID. The body is a JSON problem details object
+    /// with the `status`, the outermost error message as the `title`, the `correlation_id`,
+    /// the `chain` of errors, the attached `sections` and `breadcrumbs`, and the commands
+    /// attached with `Section::try_command` as `try_commands`. Backtraces and
+    /// `SpanTrace`s are omitted from the body.
+    pub fn log_and_respond(&self) -> (u16, String) {
+        let printer = installed_printer();
+        let report = format!(
+            "request {} failed:\n{}\n",
+            self.correlation_id,
+            render_redacted(&self.error).trim_start_matches('\n')
+        );
+        let stderr = std::io::stderr();
+        let colors = printer.colors_enabled(atty::Stream::Stderr);
+        // failing to log must not keep the client from receiving a response
+        let _ = printer.emit(&report, &mut stderr.lock(), colors);
+
+        let status = self.status_code();
+        (status, self.problem_details(status).to_string())
+    }
+
+    fn problem_details(&self, status: u16) -> Value {
+        let data = extract(&self.error);
+        let sections: Vec<_> = data.sections.iter().filter_map(section_json).collect();
+
+        json!({
+            "type": "about:blank",
+            "title": data.chain.first(),
+            "status": status,
+            "correlation_id": self.correlation_id,
+            "chain": data.chain,
+            "sections": sections,
+            "breadcrumbs": data.breadcrumbs,
+            "try_commands": crate::try_commands_of(&self.error),
+        })
+    }
+}
+
+impl<E> From<E> for HttpReport
+where
+    E: Into<anyhow::Error>,
+{
+    fn from(error: E) -> Self {
+        Self::new(error.into())
+    }
+}
+
+impl fmt::Debug for HttpReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Display for HttpReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+/// The JSON form of a section in a problem details body, status codes are
+/// already reported as the response's status
+fn section_json(section: &SectionData) -> Option<Value> {
+    let message = |kind: &str, message: &str| json!({ "kind": kind, "message": message });
+
+    Some(match section {
+        SectionData::Error { chain, label } => {
+            json!({ "kind": "error", "chain": chain, "label": label })
+        }
+        SectionData::Custom(text) => message("custom", text),
+        SectionData::Note(text) => message("note", text),
+        SectionData::Warning(text) => message("warning", text),
+        SectionData::Suggestion(text) => message("suggestion", text),
+        SectionData::CodedNote { code, text } => {
+            json!({ "kind": "note", "message": text, "code": code })
+        }
+        SectionData::CodedSuggestion { code, text } => {
+            json!({ "kind": "suggestion", "message": text, "code": code })
+        }
+        SectionData::Recovery(text) => message("recovery", text),
+        SectionData::Remediation(steps) => json!({ "kind": "remediation", "steps": steps }),
+        SectionData::QuickFix(commands) => {
+            let commands: Vec<_> = commands
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect();
+            json!({ "kind": "quick_fix", "commands": commands })
+        }
+        // commands are reported on their own, as `try_commands`
+        SectionData::TryCommand(_) | SectionData::Status(_) => return None,
+    })
+}
+
+/// A correlation ID unique within the process: the current time in
+/// milliseconds and a counter, in hex
+fn next_correlation_id() -> String {
+    let millis = crate::clock::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let n = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", millis, n & 0xffff)
+}
@@ -305,6 +305,9 @@
 //! the `Section` trait.
 //!
 //! For an example of how to aggregate errors check out [`examples/multiple_errors.rs`].
+//! When the causes have causes of their own, e.g. a task whose subtasks failed,
+//! a [`CauseTree`] renders each of them with its own chain of errors and sections
+//! as a tree.
 //!
 //! ### Custom configuration for `color-backtrace` for setting custom filters and more
 //!
@@ -330,6 +333,7 @@
 //! [`anyhow::Error`]: https://docs.rs/anyhow/*/anyhow/struct.Error.html
 //! [`anyhow::Result`]: https://docs.rs/anyhow/*/anyhow/type.Result.html
 //! [`Handler`]: https://docs.rs/color-anyhow/*/color_anyhow/struct.Handler.html
+//! [`CauseTree`]: https://docs.rs/color-anyhow/*/color_anyhow/struct.CauseTree.html
 //! [`examples/usage.rs`]: https://github.com/yaahc/color-anyhow/blob/master/examples/usage.rs
 //! [`examples/custom_filter.rs`]: https://github.com/yaahc/color-anyhow/blob/master/examples/custom_filter.rs
 //! [`examples/custom_section.rs`]: https://github.com/yaahc/color-anyhow/blob/master/examples/custom_section.rs
@@ -361,20 +365,101 @@
 )]
 #![allow(clippy::try_err)]
 pub use anyhow;
+pub use artifact::{ArtifactFormat, ArtifactSpec};
 #[cfg(not(backtrace))]
-use backtrace::Backtrace;
+pub use backtrace::Backtrace;
+pub use build_info::BuildInfo;
+#[cfg(feature = "test-util")]
+pub use clock::ManualClock;
+pub use clock::ReportClock;
+pub use exit::report_to_exit_code;
+#[cfg(feature = "capture-spantrace")]
+pub use extract::SpanFrame;
+pub use extract::{extract, status_hint, try_commands_of, ReportData, SectionData};
+#[cfg(feature = "ffi")]
+pub use ffi::render_to_buffer;
+pub use fingerprint::{fingerprint, fingerprint_with, Fingerprint, FingerprintOptions};
+pub use handler::{format_report, render_both, render_both_with};
+pub use html::report_to_html;
+#[cfg(feature = "http")]
+pub use http::HttpReport;
+#[cfg(feature = "log")]
+pub use logging::log_error;
 use once_cell::sync::OnceCell;
-use section::help::HelpInfo;
+#[cfg(feature = "process")]
+pub use process::CommandExt;
+#[cfg(feature = "highlight-patterns")]
+#[cfg_attr(docsrs, doc(cfg(feature = "highlight-patterns")))]
+pub use regex_lite;
+pub use reported::{is_reported, mark_reported};
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use runtime::tokio_runtime_diagnostics;
+use section::help::Sections;
 pub use section::{IndentedSection, Section, SectionExt};
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use settings::RenderSettings;
+pub use severity::{severity, Severity};
+pub use snapshot::{current_config, ConfigSnapshot};
+pub use stats::{render_stats, RenderStats, ReportSummary};
 #[cfg(backtrace)]
-use std::backtrace::Backtrace;
+pub use std::backtrace::Backtrace;
+pub use stream::{stream_report, ReportStream};
+pub use styled::{styled_report, TextColor, TextStyle};
+pub use theme::Theme;
+#[cfg(feature = "capture-spantrace")]
+pub use timestamps::SpanTimestampLayer;
 #[cfg(feature = "capture-spantrace")]
 use tracing_error::SpanTrace;
+pub use tree::CauseTree;
+pub use unsafe_context::{enter_capture_unsafe_context, CaptureUnsafeContext};
+pub use uptime::{uptime, HumanDuration};
+pub use warnings::WarningsCollector;
 
+mod artifact;
+mod build_info;
+mod clock;
 pub mod config;
+mod exit;
+mod extract;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod fingerprint;
 mod handler;
+mod html;
+#[cfg(feature = "http")]
+mod http;
+mod locale;
+#[cfg(feature = "log")]
+mod logging;
 pub(crate) mod private;
+#[cfg(feature = "process")]
+mod process;
+mod redact;
+mod report_id;
+mod reported;
+#[cfg(feature = "tokio")]
+mod runtime;
 pub mod section;
+#[cfg(feature = "serde")]
+mod settings;
+mod severity;
+mod snapshot;
+mod snippet;
+#[cfg(feature = "capture-spantrace")]
+mod spantrace_renderer;
+mod stats;
+mod stream;
+mod stripped;
+mod styled;
+mod theme;
+mod timestamps;
+mod tree;
+mod unsafe_context;
+mod uptime;
+mod warnings;
+mod width;
 mod writers;
 
 /// A custom handler type for [`anyhow::Error`] which provides colorful error
@@ -385,6 +470,94 @@ mod writers;
 /// This type is not intended to be used directly, prefer using it via the
 /// [`color_anyhow::Error`] and [`color_anyhow::Result`] type aliases.
 ///
+/// A report that is rendered again while it's being rendered, e.g. because it
+/// ended up in one of its own sections, renders as `<recursive report omitted>`
+/// the second time. Reports nested more than a few levels deep in the sections
+/// of other reports render as `<deeply nested report omitted>`.
+///
+/// The first two sections attached to a report are stored inline in the
+/// handler, so attaching them only allocates the sections themselves.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+/// use once_cell::sync::OnceCell;
+/// use std::{fmt, sync::Arc};
+///
+/// struct Cause(Arc<OnceCell<Error>>);
+///
+/// impl fmt::Display for Cause {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         match self.0.get() {
+///             Some(report) => write!(f, "caused by {:?}", report),
+///             None => f.write_str("unknown cause"),
+///         }
+///     }
+/// }
+///
+/// color_anyhow::install().unwrap();
+///
+/// let slot = Arc::new(OnceCell::new());
+/// let report = Err::<(), Error>(anyhow!("the job failed"))
+///     .section(Cause(slot.clone()))
+///     .unwrap_err();
+/// slot.set(report).ok().unwrap();
+///
+/// let rendered = format!("{:?}", slot.get().unwrap());
+/// assert!(rendered.contains("caused by <recursive report omitted>"));
+/// ```
+///
+/// Counting the allocations made while attaching sections:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+/// use std::alloc::{GlobalAlloc, Layout, System};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// struct CountingAllocator;
+///
+/// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+///
+/// unsafe impl GlobalAlloc for CountingAllocator {
+///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+///         System.alloc(layout)
+///     }
+///
+///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+///         System.dealloc(ptr, layout)
+///     }
+/// }
+///
+/// #[global_allocator]
+/// static GLOBAL: CountingAllocator = CountingAllocator;
+///
+/// fn allocations<T>(f: impl FnOnce() -> T) -> (usize, T) {
+///     let before = ALLOCATIONS.load(Ordering::Relaxed);
+///     let value = f();
+///     (ALLOCATIONS.load(Ordering::Relaxed) - before, value)
+/// }
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("the upload failed"));
+///
+/// // each of the first two sections only allocates its own box
+/// let (first, report) = allocations(|| report.section("attempt 1 timed out"));
+/// assert_eq!(first, 1);
+/// let (second, report) = allocations(|| report.section("attempt 2 timed out"));
+/// assert_eq!(second, 1);
+///
+/// // the third section moves all of them to the heap
+/// let (third, report) = allocations(|| report.section("attempt 3 timed out"));
+/// assert_eq!(third, 2);
+///
+/// let report = format!("{:?}", report.unwrap_err());
+/// assert!(report.contains("attempt 1 timed out\n\nattempt 2 timed out\n\nattempt 3 timed out"));
+/// ```
+///
 /// [`anyhow::Error`]: https://docs.rs/anyhow/*/anyhow/struct.Error.html
 /// [`tracing-error`]: https://docs.rs/tracing-error
 /// [`color_anyhow::Error`]: type.Error.html
@@ -394,7 +567,22 @@ pub struct Handler {
     backtrace: Option<Backtrace>,
     #[cfg(feature = "capture-spantrace")]
     span_trace: Option<SpanTrace>,
-    sections: Vec<HelpInfo>,
+    #[cfg(feature = "capture-spantrace")]
+    span_timestamps: Option<Vec<Option<std::time::SystemTime>>>,
+    sections: Sections,
+    breadcrumbs: Vec<String>,
+    chain_label: Option<String>,
+    severity: Option<Severity>,
+    localized: Option<String>,
+    category: Option<String>,
+    source_locations: Vec<snippet::SourceLocation>,
+    #[cfg(feature = "json")]
+    json_source: Option<String>,
+    inline_hints: Vec<section::help::InlineHint>,
+    theme: Option<Theme>,
+    capture_skipped: bool,
+    reported: std::sync::atomic::AtomicBool,
+    converted_at: Option<&'static std::panic::Location<'static>>,
 }
 
 static CONFIG: OnceCell<config::PanicHook> = OnceCell::new();
@@ -432,3 +620,73 @@ trait ColorExt {
 pub fn install() -> Result<(), crate::anyhow::Error> {
     config::HookBuilder::default().install()
 }
+
+/// Returns true if the panic and error report hooks have been installed
+///
+/// # Details
+///
+/// Installation is atomic, once this returns true the report hook, the panic
+/// hook and the configuration used to render both are all in place. When
+/// several threads race to install the hooks exactly one of them succeeds and
+/// the others receive `InstallError::AlreadyInstalled` from `try_install`.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::{HookBuilder, InstallError}};
+///
+/// assert!(!color_anyhow::is_installed());
+///
+/// let threads: Vec<_> = (0..16)
+///     .map(|_| {
+///         std::thread::spawn(|| {
+///             let installed = HookBuilder::default().try_install();
+///             // rendering reports while other threads install must never panic
+///             let report = format!("{:?}", anyhow!("an error occurred"));
+///             assert!(report.contains("an error occurred"));
+///             installed
+///         })
+///     })
+///     .collect();
+///
+/// let results: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+/// assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+/// assert!(results
+///     .iter()
+///     .filter_map(|r| r.err())
+///     .all(|e| e == InstallError::AlreadyInstalled));
+/// assert!(color_anyhow::is_installed());
+/// ```
+pub fn is_installed() -> bool {
+    config::is_installed()
+}
+
+/// Install the default panic and error report hooks along with a global
+/// `tracing` subscriber that captures `SpanTrace`s
+///
+/// # Details
+///
+/// The subscriber logs events with `tracing_subscriber::fmt` and is filtered
+/// by `RUST_LOG`, defaulting to `info` when it is unset. Use
+/// [`HookBuilder::install_with_tracing`] to customize the default filter or
+/// the hooks.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::anyhow::Result;
+///
+/// fn main() -> Result<()> {
+///     color_anyhow::install_with_tracing()?;
+///
+///     // ...
+///     # Ok(())
+/// }
+/// ```
+///
+/// [`HookBuilder::install_with_tracing`]: config/struct.HookBuilder.html#method.install_with_tracing
+#[cfg(feature = "tracing-init")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing-init")))]
+pub fn install_with_tracing() -> Result<(), crate::anyhow::Error> {
+    config::HookBuilder::default().install_with_tracing("info")
+}
@@ -0,0 +1,155 @@
+//! Configurable formats for the numbers and dates shown in reports
+use std::fmt;
+#[cfg(feature = "capture-spantrace")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How numbers like frame counts are rendered, parsed from a pattern like `1,000`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NumberFormat {
+    pattern: String,
+    separator: Option<char>,
+}
+
+impl NumberFormat {
+    /// Parse a pattern of the form `1<separator>000`, or `1000` for no separator
+    pub(crate) fn parse(pattern: &str) -> Option<Self> {
+        let separator = pattern.strip_prefix('1')?.strip_suffix("000")?;
+        let mut chars = separator.chars();
+        let separator = match (chars.next(), chars.next()) {
+            (None, _) => None,
+            (Some(c), None) if !c.is_ascii_digit() => Some(c),
+            _ => return None,
+        };
+
+        Some(Self {
+            pattern: pattern.to_owned(),
+            separator,
+        })
+    }
+
+    pub(crate) fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Displays `n` in this format
+    pub(crate) fn display(&self, n: usize) -> FormattedNumber<'_> {
+        FormattedNumber { format: self, n }
+    }
+}
+
+pub(crate) struct FormattedNumber<'a> {
+    format: &'a NumberFormat,
+    n: usize,
+}
+
+impl fmt::Display for FormattedNumber<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.n.to_string();
+        let separator = match self.format.separator {
+            Some(separator) => separator,
+            None => return f.write_str(&digits),
+        };
+
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                write!(f, "{}", separator)?;
+            }
+            write!(f, "{}", digit)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How timestamps are rendered, parsed from a `strftime`-like format string
+#[cfg(feature = "capture-spantrace")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DateFormat {
+    format: String,
+    items: Vec<DateItem>,
+}
+
+#[cfg(feature = "capture-spantrace")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DateItem {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Millis,
+}
+
+#[cfg(feature = "capture-spantrace")]
+impl DateFormat {
+    /// Parse a format made of literal text and the specifiers `%Y`, `%m`, `%d`,
+    /// `%H`, `%M`, `%S`, `%3f` and `%%`
+    pub(crate) fn parse(format: &str) -> Option<Self> {
+        let mut items = vec![];
+        let mut literal = String::new();
+        let mut chars = format.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            let item = match chars.next()? {
+                '%' => {
+                    literal.push('%');
+                    continue;
+                }
+                'Y' => DateItem::Year,
+                'm' => DateItem::Month,
+                'd' => DateItem::Day,
+                'H' => DateItem::Hour,
+                'M' => DateItem::Minute,
+                'S' => DateItem::Second,
+                '3' if chars.next()? == 'f' => DateItem::Millis,
+                _ => return None,
+            };
+            if !literal.is_empty() {
+                items.push(DateItem::Literal(std::mem::take(&mut literal)));
+            }
+            items.push(item);
+        }
+        if !literal.is_empty() {
+            items.push(DateItem::Literal(literal));
+        }
+
+        Some(Self {
+            format: format.to_owned(),
+            items,
+        })
+    }
+
+    pub(crate) fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// Write `time`, in UTC, in this format
+    pub(crate) fn write(&self, f: &mut fmt::Formatter<'_>, time: SystemTime) -> fmt::Result {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs();
+        let (year, month, day) = crate::timestamps::civil_from_days((secs / 86400) as i64);
+        let secs_of_day = secs % 86400;
+
+        for item in &self.items {
+            match item {
+                DateItem::Literal(text) => f.write_str(text)?,
+                DateItem::Year => write!(f, "{:04}", year)?,
+                DateItem::Month => write!(f, "{:02}", month)?,
+                DateItem::Day => write!(f, "{:02}", day)?,
+                DateItem::Hour => write!(f, "{:02}", secs_of_day / 3600)?,
+                DateItem::Minute => write!(f, "{:02}", secs_of_day / 60 % 60)?,
+                DateItem::Second => write!(f, "{:02}", secs_of_day % 60)?,
+                DateItem::Millis => write!(f, "{:03}", since_epoch.subsec_millis())?,
+            }
+        }
+
+        Ok(())
+    }
+}
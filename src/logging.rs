@@ -0,0 +1,61 @@
+//! Emitting error reports through the `log` crate
+use crate::config::ReportOptions;
+use crate::writers::strip_ansi;
+
+/// Render an error report and emit it as an error level `log` record
+///
+/// # Details
+///
+/// The report is rendered the same way as its `Debug` output, with user paths
+/// redacted if configured, but always without colors, as log backends rarely
+/// write to a terminal. Nothing is rendered if error records for `target` are
+/// disabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context};
+/// use log::{LevelFilter, Log, Metadata, Record};
+/// use std::sync::Mutex;
+///
+/// struct Capture(Mutex<Vec<(String, String)>>);
+///
+/// impl Log for Capture {
+///     fn enabled(&self, _: &Metadata<'_>) -> bool {
+///         true
+///     }
+///
+///     fn log(&self, record: &Record<'_>) {
+///         let entry = (record.target().to_owned(), record.args().to_string());
+///         self.0.lock().unwrap().push(entry);
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+///
+/// color_anyhow::install().unwrap();
+/// log::set_logger(&LOGGER).unwrap();
+/// log::set_max_level(LevelFilter::Error);
+///
+/// let report = Err::<(), _>(anyhow!("file not found"))
+///     .context("config could not be loaded")
+///     .unwrap_err();
+/// color_anyhow::log_error("myapp::config", &report);
+///
+/// let logged = LOGGER.0.lock().unwrap();
+/// let (target, message) = &logged[0];
+/// assert_eq!(target, "myapp::config");
+/// assert!(message.contains("0: config could not be loaded\n   1: file not found"));
+/// assert!(!message.contains('\x1b'));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+pub fn log_error(target: &str, error: &anyhow::Error) {
+    if !log::log_enabled!(target: target, log::Level::Error) {
+        return;
+    }
+
+    let report = crate::format_report(error, &ReportOptions::default());
+    log::error!(target: target, "{}", strip_ansi(&report));
+}
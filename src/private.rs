@@ -2,3 +2,6 @@ use crate::anyhow::Error;
 pub trait Sealed {}
 
 impl<T, E> Sealed for std::result::Result<T, E> where E: Into<Error> {}
+
+#[cfg(feature = "process")]
+impl Sealed for std::process::Command {}
@@ -0,0 +1,142 @@
+//! Checked execution of `std::process::Command`s
+use crate::{
+    anyhow::{anyhow, Context, Error, Result},
+    Section, SectionExt,
+};
+use std::ffi::OsStr;
+use std::io;
+use std::process::{Command, ExitStatus, Output};
+
+/// Extension trait for running a `Command` and reporting its failure
+///
+/// # Details
+///
+/// A command fails if it can't be spawned or exits with a non-zero status. The
+/// report of a failed command has a `Command:` section with the full, shell
+/// quoted command line and, when its output was captured, `Stdout:` and
+/// `Stderr:` sections with the trimmed output of the command. Commands that
+/// couldn't be found get a suggestion to check the `PATH`.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{config::{ColorChoice, HookBuilder}, CommandExt};
+/// use std::process::Command;
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let output = Command::new("echo").arg("hello").output_checked().unwrap();
+/// assert_eq!(output.stdout, b"hello\n");
+///
+/// let report = Command::new("false").status_checked().unwrap_err();
+/// let report = format!("{:?}", report);
+/// assert!(report.contains("command exited unsuccessfully (exit status: 1)"));
+/// assert!(report.contains("Command:\n   false"));
+///
+/// let report = Command::new("sh")
+///     .args(&["-c", "echo 'partial results'; echo 'disk full' >&2; exit 3"])
+///     .output_checked()
+///     .unwrap_err();
+/// let report = format!("{:?}", report);
+/// assert!(report.contains("command exited unsuccessfully (exit status: 3)"));
+/// assert!(report.contains(
+///     "Command:\n   sh -c 'echo '\\''partial results'\\''; echo '\\''disk full'\\'' >&2; exit 3'"
+/// ));
+/// assert!(report.contains("Stdout:\n   partial results"));
+/// assert!(report.contains("Stderr:\n   disk full"));
+///
+/// let report = Command::new("definitely-not-installed")
+///     .arg("--version")
+///     .output_checked()
+///     .unwrap_err();
+/// let report = format!("{:?}", report);
+/// assert!(report.contains("could not run `definitely-not-installed`"));
+/// assert!(report.contains("Command:\n   definitely-not-installed --version"));
+/// assert!(report.contains(
+///     "Suggestion: make sure `definitely-not-installed` is installed and its directory is in your PATH"
+/// ));
+/// assert!(!report.contains("Stdout:"));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "process")))]
+pub trait CommandExt: crate::private::Sealed {
+    /// Run the command to completion, capturing its output, and return the
+    /// output if it exited successfully
+    fn output_checked(&mut self) -> Result<Output>;
+
+    /// Run the command to completion, inheriting its output streams, and
+    /// return its status if it exited successfully
+    fn status_checked(&mut self) -> Result<ExitStatus>;
+}
+
+impl CommandExt for Command {
+    fn output_checked(&mut self) -> Result<Output> {
+        let output = self.output().map_err(|e| spawn_error(self, e))?;
+        if output.status.success() {
+            return Ok(output);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+        Err(exit_error(self, output.status))
+            .section(stdout.header("Stdout:"))
+            .section(stderr.header("Stderr:"))
+    }
+
+    fn status_checked(&mut self) -> Result<ExitStatus> {
+        let status = self.status().map_err(|e| spawn_error(self, e))?;
+        if status.success() {
+            Ok(status)
+        } else {
+            Err(exit_error(self, status))
+        }
+    }
+}
+
+fn spawn_error(command: &Command, error: io::Error) -> Error {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let not_found = error.kind() == io::ErrorKind::NotFound;
+    let report = Err::<(), _>(error)
+        .with_context(|| format!("could not run `{}`", program))
+        .section(command_line(command).header("Command:"));
+
+    if not_found {
+        report.with_suggestion(|| {
+            format!(
+                "make sure `{}` is installed and its directory is in your PATH",
+                program
+            )
+        })
+    } else {
+        report
+    }
+    .unwrap_err()
+}
+
+fn exit_error(command: &Command, status: ExitStatus) -> Error {
+    Err::<(), _>(anyhow!("command exited unsuccessfully ({})", status))
+        .section(command_line(command).header("Command:"))
+        .unwrap_err()
+}
+
+/// The program and arguments of `command`, quoted for a POSIX shell
+fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn shell_quote(arg: &OsStr) -> String {
+    let arg = arg.to_string_lossy();
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%^".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.into_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,145 @@
+//! Redaction of the current user's home directory and username from rendered
+//! reports
+use crate::writers::escape_len;
+use std::env;
+
+/// The detected home directory and username of the current user
+#[derive(Debug, Clone)]
+pub(crate) struct UserPaths {
+    homes: Vec<String>,
+    user: Option<String>,
+}
+
+impl UserPaths {
+    /// Detect the current user's home directory and username from the
+    /// environment, returning `None` if neither could be found.
+    pub(crate) fn detect() -> Option<Self> {
+        let trim = |home: &str| home.trim_end_matches(is_separator).to_owned();
+        let candidates: Vec<_> = ["HOME", "USERPROFILE"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .map(|home| trim(&home))
+            .filter(|home| !home.is_empty())
+            .collect();
+        let home = candidates.first().cloned();
+
+        let user = env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .ok()
+            .or_else(|| {
+                home.as_ref()
+                    .and_then(|home| home.rsplit(is_separator).next())
+                    .map(ToOwned::to_owned)
+            })
+            .filter(|user| !user.is_empty() && !user.contains(is_separator));
+
+        let mut homes = vec![];
+        for home in candidates {
+            // paths in traces are resolved, so a home behind a symlink shows up as its target
+            let resolved = std::fs::canonicalize(&home)
+                .ok()
+                .and_then(|path| path.to_str().map(trim));
+            for home in std::iter::once(home).chain(resolved) {
+                // Windows resolves paths to their verbatim form, which traces rarely use
+                let plain = strip_verbatim(&home);
+                for home in std::iter::once(home).chain(plain) {
+                    // Windows paths frequently show up with either separator
+                    if home.contains('\\') {
+                        homes.push(home.replace('\\', "/"));
+                    }
+                    homes.push(home);
+                }
+            }
+        }
+        // the longest home is replaced first in case one is nested in another
+        homes.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        homes.dedup();
+
+        if homes.is_empty() && user.is_none() {
+            None
+        } else {
+            Some(Self { homes, user })
+        }
+    }
+
+    /// Replace the home directory with `~` and any path component equal to the
+    /// username with `<user>`.
+    ///
+    /// Matches are only made on whole path components, so text that merely
+    /// contains the username as part of a longer word is left untouched.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        let mut prev = None;
+
+        'outer: while let Some(c) = rest.chars().next() {
+            // Copy escape sequences through untouched, they act as a boundary
+            // between the colored text and whatever comes before them.
+            if c == '\x1b' {
+                let len = escape_len(rest);
+                out.push_str(&rest[..len]);
+                rest = &rest[len..];
+                prev = None;
+                continue;
+            }
+
+            if !matches!(prev, Some(c) if is_path_char(c)) {
+                for home in &self.homes {
+                    if let Some(tail) = rest.strip_prefix(home.as_str()) {
+                        if ends_component(tail) {
+                            out.push('~');
+                            rest = tail;
+                            prev = Some('~');
+                            continue 'outer;
+                        }
+                    }
+                }
+            }
+
+            if is_separator(c) {
+                if let Some(user) = &self.user {
+                    if let Some(tail) = rest[1..].strip_prefix(user.as_str()) {
+                        if ends_component(tail) {
+                            out.push(c);
+                            out.push_str("<user>");
+                            rest = tail;
+                            prev = Some('>');
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+            prev = Some(c);
+        }
+
+        out
+    }
+}
+
+/// The path `path` refers to without its Windows verbatim prefix, e.g. `C:\Users\alice`
+/// for `\\?\C:\Users\alice`, if it has one
+fn strip_verbatim(path: &str) -> Option<String> {
+    if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        Some(format!(r"\\{}", unc))
+    } else {
+        path.strip_prefix(r"\\?\").map(ToOwned::to_owned)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || is_separator(c) || matches!(c, '.' | '_' | '-' | '~')
+}
+
+fn ends_component(tail: &str) -> bool {
+    match tail.chars().next() {
+        Some(c) => is_separator(c) || !is_path_char(c),
+        None => true,
+    }
+}
@@ -0,0 +1,40 @@
+//! Unique identifiers of rendered reports, for correlating a report a user shares with the
+//! logs of the application
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+/// Crockford's base32 alphabet, which leaves out the letters easily confused with digits
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The number of identifiers generated so far
+static GENERATED: AtomicU64 = AtomicU64::new(0);
+
+/// Generate a new identifier in the format of a ULID
+///
+/// # Details
+///
+/// The identifier is 26 characters of Crockford's base32, encoding the milliseconds since
+/// the unix epoch in its first 48 bits followed by 80 unique bits, so identifiers sort by
+/// the time they were generated. The first 64 of those bits are drawn from the randomly seeded hasher
+/// of the standard library, the last 16 are a counter, so identifiers generated within the
+/// same millisecond always differ.
+pub(crate) fn generate() -> String {
+    let millis = crate::clock::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let count = GENERATED.fetch_add(1, Ordering::Relaxed);
+
+    let random = RandomState::new().hash_one((count, millis));
+
+    let bits = u128::from(millis & 0xFFFF_FFFF_FFFF) << 80
+        | u128::from(random) << 16
+        | u128::from(count & 0xFFFF);
+
+    // 26 characters of 5 bits each hold 130 bits, the leading two are always zero
+    (0..26)
+        .rev()
+        .map(|n| ALPHABET[(bits >> (n * 5)) as usize & 0x1F] as char)
+        .collect()
+}
@@ -0,0 +1,54 @@
+//! Keeping track of the error reports that were already reported, so layered applications
+//! report each of them once
+use std::sync::atomic::Ordering;
+
+/// Mark an error report as reported, e.g. after logging it
+///
+/// # Details
+///
+/// The mark is kept by the handler of the report, so it survives wrapping the report
+/// with `context`, which keeps the handler of the wrapped report, and can be set and
+/// read from any thread. Reports that weren't created by the `color-anyhow` handler can't
+/// be marked and are never reported as such by [`is_reported`]. With
+/// `HookBuilder::auto_mark_reported`, rendering a report with `Debug` marks it as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Context, anyhow::Error};
+///
+/// color_anyhow::install().unwrap();
+///
+/// fn log_once(report: &Error) {
+///     if !color_anyhow::is_reported(report) {
+///         eprintln!("{:?}", report);
+///         color_anyhow::mark_reported(report);
+///     }
+/// }
+///
+/// let report = anyhow!("the job timed out");
+/// assert!(!color_anyhow::is_reported(&report));
+///
+/// // the job queue logs the error, then the http layer sees it again, wrapped
+/// log_once(&report);
+/// let report = Err::<(), _>(report).context("the request failed").unwrap_err();
+/// assert!(color_anyhow::is_reported(&report));
+/// ```
+///
+/// [`is_reported`]: fn.is_reported.html
+pub fn mark_reported(error: &anyhow::Error) {
+    if let Some(handler) = error.handler().downcast_ref::<crate::Handler>() {
+        handler.reported.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Returns whether an error report was marked with [`mark_reported`], or rendered with
+/// `HookBuilder::auto_mark_reported` enabled
+///
+/// [`mark_reported`]: fn.mark_reported.html
+pub fn is_reported(error: &anyhow::Error) -> bool {
+    match error.handler().downcast_ref::<crate::Handler>() {
+        Some(handler) => handler.reported.load(Ordering::Relaxed),
+        None => false,
+    }
+}
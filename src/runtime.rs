@@ -0,0 +1,51 @@
+//! Diagnostics of the tokio runtime, for conditional sections
+use std::fmt::Write;
+use tokio::runtime::Handle;
+
+/// Describe the tokio runtime the current thread runs on, for use as the provider of
+/// [`HookBuilder::add_conditional_section`]
+///
+/// # Details
+///
+/// Lists the number of worker threads, of alive tasks and of tasks waiting in the
+/// global queue. When built with `--cfg tokio_unstable` the depth of the blocking
+/// pool's queue is listed as well. Outside of a runtime this says so instead.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use color_anyhow::config::HookBuilder;
+///
+/// HookBuilder::default()
+///     .add_conditional_section(
+///         Box::new(|error| error.to_string().contains("timed out")),
+///         Box::new(color_anyhow::tokio_runtime_diagnostics),
+///     )
+///     .install()
+///     .unwrap();
+/// ```
+///
+/// [`HookBuilder::add_conditional_section`]: config/struct.HookBuilder.html#method.add_conditional_section
+pub fn tokio_runtime_diagnostics() -> String {
+    let handle = match Handle::try_current() {
+        Ok(handle) => handle,
+        Err(_) => return "no tokio runtime is running on this thread".into(),
+    };
+
+    let metrics = handle.metrics();
+    let mut out = String::new();
+    let _ = write!(out, "workers: {}", metrics.num_workers());
+    let _ = write!(out, "\nalive tasks: {}", metrics.num_alive_tasks());
+    let _ = write!(
+        out,
+        "\nglobal queue depth: {}",
+        metrics.global_queue_depth()
+    );
+    #[cfg(tokio_unstable)]
+    let _ = write!(
+        out,
+        "\nblocking queue depth: {}",
+        metrics.blocking_queue_depth()
+    );
+    out
+}
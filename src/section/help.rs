@@ -1,54 +1,89 @@
 //! Provides an extension trait for attaching `Section` to error reports.
 use crate::{
-    anyhow::{Error, Result},
-    ColorExt, Section,
+    anyhow::{Context, Error, Result},
+    config::installed_printer,
+    section::{wrap_lines, Steps},
+    snippet::SourceLocation,
+    writers::truncate_visible,
+    ColorExt, Section, Severity,
 };
 use ansi_term::Color::*;
-use indenter::indented;
+use ansi_term::Style;
+use indenter::{indented, Format};
+use once_cell::sync::OnceCell;
+use std::any::TypeId;
 use std::fmt::Write;
 use std::fmt::{self, Display};
+use std::panic::Location;
 
 impl<T, E> Section<T> for std::result::Result<T, E>
 where
-    E: Into<Error>,
+    E: Into<Error> + 'static,
 {
+    #[track_caller]
     fn note<D>(self, note: D) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
-                handler.sections.push(HelpInfo::Note(Box::new(note)));
+                handler.sections.push(HelpInfo::Note(Box::new(note), None));
             }
 
             e
         })
     }
 
+    #[track_caller]
+    fn note_coded<C, D>(self, code: C, note: D) -> Result<T>
+    where
+        C: Display,
+        D: Display + Send + Sync + 'static,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let code = Some(code.to_string());
+                handler.sections.push(HelpInfo::Note(Box::new(note), code));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
     fn with_note<D, F>(self, note: F) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
-                handler.sections.push(HelpInfo::Note(Box::new(note())));
+                handler
+                    .sections
+                    .push(HelpInfo::Note(Box::new(note()), None));
             }
 
             e
         })
     }
 
+    #[track_caller]
     fn warning<D>(self, warning: D) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 handler.sections.push(HelpInfo::Warning(Box::new(warning)));
@@ -58,13 +93,15 @@ where
         })
     }
 
+    #[track_caller]
     fn with_warning<D, F>(self, warning: F) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 handler
@@ -76,48 +113,386 @@ where
         })
     }
 
+    #[track_caller]
     fn suggestion<D>(self, suggestion: D) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 handler
                     .sections
-                    .push(HelpInfo::Suggestion(Box::new(suggestion)));
+                    .push(HelpInfo::Suggestion(Box::new(suggestion), None));
             }
 
             e
         })
     }
 
+    #[track_caller]
+    fn suggestion_coded<C, D>(self, code: C, suggestion: D) -> Result<T>
+    where
+        C: Display,
+        D: Display + Send + Sync + 'static,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let code = Some(code.to_string());
+                handler
+                    .sections
+                    .push(HelpInfo::Suggestion(Box::new(suggestion), code));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
     fn with_suggestion<D, F>(self, suggestion: F) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 handler
                     .sections
-                    .push(HelpInfo::Suggestion(Box::new(suggestion())));
+                    .push(HelpInfo::Suggestion(Box::new(suggestion()), None));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn suggestion_did_you_mean(self, input: &str, candidates: &[&str]) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                if let Some(candidate) = closest_match(input, candidates) {
+                    let suggestion = format!("Did you mean '{}'?", candidate);
+                    handler
+                        .sections
+                        .push(HelpInfo::Suggestion(Box::new(suggestion), None));
+                }
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn inline_hint_for<C, D>(self, hint: D) -> Result<T>
+    where
+        C: std::error::Error + 'static,
+        D: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.inline_hints.push(InlineHint {
+                    matches: |error| error.is::<C>(),
+                    hint: hint.to_string(),
+                });
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn try_command<D>(self, command: D) -> Result<T>
+    where
+        D: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler
+                    .sections
+                    .push(HelpInfo::TryCommand(command.to_string()));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn quick_fix<D>(self, command: D) -> Result<T>
+    where
+        D: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler
+                    .sections
+                    .push(HelpInfo::QuickFix(command.to_string()));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn status_hint(self, code: u16) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.sections.push(HelpInfo::Status(code));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn breadcrumb(self, breadcrumb: &str) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.breadcrumbs.push(breadcrumb.to_owned());
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn chain_label(self, label: &str) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.chain_label = Some(label.to_owned());
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn source_location<P>(self, file: P, line: u32, column: u32) -> Result<T>
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.source_locations.push(SourceLocation {
+                    file: file.into(),
+                    line,
+                    column,
+                });
+            }
+
+            e
+        })
+    }
+
+    #[cfg(feature = "json")]
+    #[track_caller]
+    fn with_json_source<S>(self, json: S) -> Result<T>
+    where
+        S: Into<String>,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.json_source = Some(json.into());
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn category<D>(self, category: D) -> Result<T>
+    where
+        D: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.category = Some(category.to_string());
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn localized<D>(self, message: D) -> Result<T>
+    where
+        D: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.localized = Some(message.to_string());
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn severity(self, severity: Severity) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.severity = Some(severity);
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn theme(self, theme: crate::Theme) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.theme = Some(theme);
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn recovery<D>(self, recovery: D) -> Result<T>
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler
+                    .sections
+                    .push(HelpInfo::Recovery(Box::new(recovery)));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn reword<D>(self, message: D) -> Result<T>
+    where
+        D: Display + Send + Sync + 'static,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            // take the sections out so they survive even if wrapping installs a fresh handler
+            let (
+                mut sections,
+                breadcrumbs,
+                chain_label,
+                severity,
+                localized,
+                category,
+                locations,
+                inline_hints,
+                theme,
+            ) = e
+                .handler_mut()
+                .downcast_mut::<crate::Handler>()
+                .map(|handler| {
+                    (
+                        std::mem::take(&mut handler.sections),
+                        std::mem::take(&mut handler.breadcrumbs),
+                        handler.chain_label.take(),
+                        handler.severity.take(),
+                        handler.localized.take(),
+                        handler.category.take(),
+                        std::mem::take(&mut handler.source_locations),
+                        std::mem::take(&mut handler.inline_hints),
+                        handler.theme.take(),
+                    )
+                })
+                .unwrap_or_default();
+
+            let mut e = Err::<(), _>(e).context(message).unwrap_err();
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                sections.append(&mut handler.sections);
+                handler.sections = sections;
+                handler.breadcrumbs = breadcrumbs;
+                handler.chain_label = chain_label;
+                handler.severity = severity;
+                handler.localized = localized;
+                handler.category = category;
+                handler.source_locations = locations;
+                handler.inline_hints = inline_hints;
+                handler.theme = theme;
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn remediation(self, steps: Steps) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.sections.push(HelpInfo::Remediation(steps));
             }
 
             e
         })
     }
 
+    #[track_caller]
     fn with_section<D, F>(self, section: F) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 let section = Box::new(section());
@@ -128,12 +503,103 @@ where
         })
     }
 
+    #[track_caller]
+    fn deferred_section<D, F>(self, section: F) -> Result<T>
+    where
+        D: Display,
+        F: Fn() -> D + Send + Sync + 'static,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let section = DeferredSection {
+                    section: Box::new(move || section().to_string()),
+                    rendered: OnceCell::new(),
+                };
+                handler.sections.push(HelpInfo::Custom(Box::new(section)));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn with_invocation_context(self) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let section = crate::SectionExt::header(invocation_context(), "Invocation:");
+                handler
+                    .sections
+                    .push(HelpInfo::Custom(Box::new(section.overflow())));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn with_otel_ids<I, S>(self, trace_id: I, span_id: S) -> Result<T>
+    where
+        I: Display,
+        S: Display,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let ids = [
+                    ("Trace", trace_id.to_string(), 32),
+                    ("Span", span_id.to_string(), 16),
+                ];
+                let ids: Vec<_> = ids
+                    .iter()
+                    .filter(|(_, id, len)| is_otel_id(id, *len))
+                    .map(|(label, id, _)| format!("{}: {}", label, id))
+                    .collect();
+                if !ids.is_empty() {
+                    handler
+                        .sections
+                        .push(HelpInfo::Custom(Box::new(ids.join(" "))));
+                }
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn with_lazy_backtrace<F>(self, backtrace: F) -> Result<T>
+    where
+        F: FnOnce() -> crate::Backtrace,
+    {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                if handler.backtrace.is_none() {
+                    handler.backtrace = Some(backtrace());
+                }
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
     fn section<D>(self, section: D) -> Result<T>
     where
         D: Display + Send + Sync + 'static,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 let section = Box::new(section);
@@ -144,33 +610,80 @@ where
         })
     }
 
+    #[track_caller]
     fn error<E2>(self, error: E2) -> Result<T>
     where
         E2: std::error::Error + Send + Sync + 'static,
     {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                let error = error.into();
+                let backtrace = installed_printer().capture_error_section_backtrace();
+                handler
+                    .sections
+                    .push(HelpInfo::Error(error, None, backtrace));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn error_labeled<L, E2>(self, label: L, error: E2) -> Result<T>
+    where
+        L: Display,
+        E2: std::error::Error + Send + Sync + 'static,
+    {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 let error = error.into();
-                handler.sections.push(HelpInfo::Error(error));
+                let label = Some(label.to_string());
+                let backtrace = installed_printer().capture_error_section_backtrace();
+                handler
+                    .sections
+                    .push(HelpInfo::Error(error, label, backtrace));
+            }
+
+            e
+        })
+    }
+
+    #[track_caller]
+    fn error_report(self, report: Error) -> Result<T> {
+        let location = Location::caller();
+        self.map_err(|e| {
+            let mut e = into_report(e, location);
+
+            if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
+                handler.sections.push(HelpInfo::Report(report));
             }
 
             e
         })
     }
 
+    #[track_caller]
     fn with_error<E2, F>(self, error: F) -> Result<T>
     where
         F: FnOnce() -> E2,
         E2: std::error::Error + Send + Sync + 'static,
     {
+        let location = Location::caller();
         self.map_err(|e| {
-            let mut e = e.into();
+            let mut e = into_report(e, location);
 
             if let Some(handler) = e.handler_mut().downcast_mut::<crate::Handler>() {
                 let error = error().into();
-                handler.sections.push(HelpInfo::Error(error));
+                let backtrace = installed_printer().capture_error_section_backtrace();
+                handler
+                    .sections
+                    .push(HelpInfo::Error(error, None, backtrace));
             }
 
             e
@@ -178,70 +691,409 @@ where
     }
 }
 
+/// Convert the error of a `Section` method's receiver into a report, recording
+/// `location` as the place of the conversion unless it already was a report
+fn into_report<E: Into<Error> + 'static>(error: E, location: &'static Location<'static>) -> Error {
+    let converted = TypeId::of::<E>() != TypeId::of::<Error>();
+    let mut error = error.into();
+
+    if converted {
+        if let Some(handler) = error.handler_mut().downcast_mut::<crate::Handler>() {
+            handler.converted_at.get_or_insert(location);
+        }
+    }
+
+    error
+}
+
+/// The command line of the program and its current working directory
+fn invocation_context() -> String {
+    let args: Vec<_> = std::env::args_os()
+        .map(|arg| shell_quote(&arg.to_string_lossy()))
+        .collect();
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd.display().to_string(),
+        Err(error) => format!("<unknown: {}>", error),
+    };
+
+    format!("Command:   {}\nDirectory: {}", args.join(" "), cwd)
+}
+
+/// Whether `id` is a valid OpenTelemetry ID of `len` hex digits, which aren't all zero
+fn is_otel_id(id: &str, len: usize) -> bool {
+    id.len() == len && id.chars().all(|c| c.is_ascii_hexdigit()) && id.chars().any(|c| c != '0')
+}
+
+/// Quote `arg` for a POSIX shell if it's empty or contains whitespace or quotes
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_whitespace() || c == '\'' || c == '"' || c == '\\');
+    if plain {
+        return arg.to_owned();
+    }
+
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// The candidate closest to `input` by edit distance, if it's close enough to
+/// be a likely typo of `input`
+pub(crate) fn closest_match<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (levenshtein(input, candidate), *candidate))
+        .filter(|(distance, candidate)| {
+            let threshold = (input.chars().count().max(candidate.chars().count()) / 3).max(1);
+            *distance > 0 && *distance <= threshold
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of single character insertions, deletions, and substitutions
+/// needed to turn `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// A section created by a closure when the report is first rendered, reused by later renders
+struct DeferredSection {
+    section: Box<dyn Fn() -> String + Send + Sync + 'static>,
+    rendered: OnceCell<String>,
+}
+
+impl Display for DeferredSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.rendered.get_or_init(|| (self.section)()))
+    }
+}
+
+/// A hint attached with `Section::inline_hint_for`, rendered under the first entry of the
+/// chain of errors it matches
+#[derive(Debug)]
+pub(crate) struct InlineHint {
+    pub(crate) matches: fn(&(dyn std::error::Error + 'static)) -> bool,
+    pub(crate) hint: String,
+}
+
 pub(crate) enum HelpInfo {
-    Error(Box<dyn std::error::Error + Send + Sync + 'static>),
+    Error(
+        Box<dyn std::error::Error + Send + Sync + 'static>,
+        Option<String>,
+        Option<crate::Backtrace>,
+    ),
+    Report(Error),
     Custom(Box<dyn Display + Send + Sync + 'static>),
-    Note(Box<dyn Display + Send + Sync + 'static>),
+    /// A note and the code attached with it for tooling, if any
+    Note(Box<dyn Display + Send + Sync + 'static>, Option<String>),
     Warning(Box<dyn Display + Send + Sync + 'static>),
-    Suggestion(Box<dyn Display + Send + Sync + 'static>),
+    /// A suggestion and the code attached with it for tooling, if any
+    Suggestion(Box<dyn Display + Send + Sync + 'static>, Option<String>),
+    Recovery(Box<dyn Display + Send + Sync + 'static>),
+    Remediation(Steps),
+    TryCommand(String),
+    QuickFix(String),
+    Status(u16),
+}
+
+impl HelpInfo {
+    /// Returns true for the help text displayed at the end of a report
+    pub(crate) fn is_help(&self) -> bool {
+        match self {
+            HelpInfo::Note(..)
+            | HelpInfo::Warning(_)
+            | HelpInfo::Suggestion(..)
+            | HelpInfo::Recovery(_)
+            | HelpInfo::TryCommand(_)
+            | HelpInfo::QuickFix(_) => true,
+            HelpInfo::Remediation(steps) => !steps.is_empty(),
+            HelpInfo::Custom(_)
+            | HelpInfo::Error(..)
+            | HelpInfo::Report(..)
+            | HelpInfo::Status(_) => false,
+        }
+    }
+}
+
+/// The sections attached to a report, stored inline while there are at most two
+///
+/// Most reports have no sections or just one, so they are kept out of the heap
+/// until a third section spills them into a `Vec`.
+pub(crate) enum Sections {
+    Empty,
+    One(HelpInfo),
+    Two([HelpInfo; 2]),
+    Spilled(Vec<HelpInfo>),
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for Sections {
+    fn default() -> Self {
+        Sections::Empty
+    }
+}
+
+impl Sections {
+    pub(crate) fn push(&mut self, section: HelpInfo) {
+        *self = match std::mem::take(self) {
+            Sections::Empty => Sections::One(section),
+            Sections::One(first) => Sections::Two([first, section]),
+            Sections::Two([first, second]) => {
+                let mut spilled = Vec::with_capacity(4);
+                spilled.extend([first, second, section]);
+                Sections::Spilled(spilled)
+            }
+            Sections::Spilled(mut spilled) => {
+                spilled.push(section);
+                Sections::Spilled(spilled)
+            }
+        }
+    }
+
+    /// Moves all the sections of `other` to the end of `self`, leaving `other` empty
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        match std::mem::take(other) {
+            Sections::Empty => {}
+            Sections::One(section) => self.push(section),
+            Sections::Two([first, second]) => {
+                self.push(first);
+                self.push(second);
+            }
+            Sections::Spilled(spilled) => {
+                spilled.into_iter().for_each(|section| self.push(section))
+            }
+        }
+    }
+}
+
+impl std::ops::Deref for Sections {
+    type Target = [HelpInfo];
+
+    fn deref(&self) -> &[HelpInfo] {
+        match self {
+            Sections::Empty => &[],
+            Sections::One(section) => std::slice::from_ref(section),
+            Sections::Two(sections) => sections,
+            Sections::Spilled(sections) => sections,
+        }
+    }
+}
+
+impl fmt::Debug for Sections {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a Sections {
+    type Item = &'a HelpInfo;
+    type IntoIter = std::slice::Iter<'a, HelpInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 impl Display for HelpInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            HelpInfo::Note(note) => write!(f, "{}: {}", Cyan.make_intense().paint("Note"), note),
-            HelpInfo::Warning(warning) => {
-                write!(f, "{}: {}", Yellow.make_intense().paint("Warning"), warning)
+            HelpInfo::Note(note, code) => {
+                write_help(f, Cyan.make_intense(), "Note", note)?;
+                write_code(f, code.as_deref())
             }
-            HelpInfo::Suggestion(suggestion) => write!(
-                f,
-                "{}: {}",
-                Cyan.make_intense().paint("Suggestion"),
-                suggestion
-            ),
-            HelpInfo::Custom(section) => write!(f, "{}", section),
-            HelpInfo::Error(error) => {
-                // a lot here
-                let errors = std::iter::successors(
-                    Some(error.as_ref() as &(dyn std::error::Error + 'static)),
-                    |e| e.source(),
-                );
-
-                write!(f, "Error:")?;
-                let mut buf = String::new();
-                for (n, error) in errors.enumerate() {
+            HelpInfo::Warning(warning) => write_help(f, Yellow.make_intense(), "Warning", warning),
+            HelpInfo::Suggestion(suggestion, code) => {
+                write_help(f, Cyan.make_intense(), "Suggestion", suggestion)?;
+                write_code(f, code.as_deref())
+            }
+            HelpInfo::Recovery(recovery) => {
+                write_help(f, Green.make_intense(), "Recovery", recovery)
+            }
+            HelpInfo::Remediation(steps) => {
+                write!(f, "{}:", Cyan.make_intense().paint("Remediation"))?;
+                if !steps.is_empty() {
                     writeln!(f)?;
-                    buf.clear();
-                    write!(&mut buf, "{}", error).unwrap();
-                    write!(indented(f).ind(n), "{}", Red.make_intense().paint(&buf))?;
                 }
-
+                let width = f.width().map(|width| width.saturating_sub(3));
+                let mut f = indented(f).with_format(Format::Uniform { indentation: "   " });
+                match width {
+                    Some(width) => write!(f, "{:width$}", steps, width = width),
+                    None => write!(f, "{}", steps),
+                }
+            }
+            HelpInfo::TryCommand(command) => write_help(f, Cyan.make_intense(), "Try", command),
+            HelpInfo::QuickFix(commands) => {
+                // commands are never wrapped, so each line can be copied as is
+                write!(f, "{}:", Green.make_intense().paint("Quick fix"))?;
+                let commands = commands.lines().filter(|line| !line.trim().is_empty());
+                for command in commands {
+                    write!(
+                        f,
+                        "\n   {}{}",
+                        Style::new().dimmed().paint("$ "),
+                        Style::new().bold().paint(command)
+                    )?;
+                }
                 Ok(())
             }
+            HelpInfo::Status(code) => write_help(f, Cyan.make_intense(), "Status", code),
+            HelpInfo::Custom(section) => Display::fmt(section, f),
+            HelpInfo::Error(error, ..) => write!(
+                f,
+                "{}",
+                ErrorGroup {
+                    error: error.as_ref(),
+                    count: 1,
+                    labels: &[],
+                }
+            ),
+            HelpInfo::Report(report) => write!(
+                f,
+                "{}",
+                ErrorGroup {
+                    error: report.as_ref(),
+                    count: 1,
+                    labels: &[],
+                }
+            ),
+        }
+    }
+}
+
+/// Write a help section of the form `Label: message`
+///
+/// The formatter's width word wraps the message, with continuation lines
+/// aligned after the label, and its precision caps the whole section.
+fn write_help(
+    f: &mut fmt::Formatter<'_>,
+    color: ansi_term::Color,
+    label: &str,
+    message: &dyn Display,
+) -> fmt::Result {
+    if f.width().is_none() && f.precision().is_none() {
+        return write!(f, "{}: {}", color.paint(label), message);
+    }
+
+    let mut message = message.to_string();
+    if let Some(width) = f.width() {
+        let prefix = label.chars().count() + 2;
+        let continuation = format!("\n{}", " ".repeat(prefix));
+        message = wrap_lines(&message, Some(width.saturating_sub(prefix)))
+            .collect::<Vec<_>>()
+            .join(&continuation);
+    }
+
+    let out = format!("{}: {}", color.paint(label), message);
+    match f.precision() {
+        Some(max) => f.write_str(&truncate_visible(&out, max)),
+        None => f.write_str(&out),
+    }
+}
+
+/// Write the code of a coded help section as a dimmed `[CODE]` suffix, unless codes are hidden
+fn write_code(f: &mut fmt::Formatter<'_>, code: Option<&str>) -> fmt::Result {
+    match code {
+        Some(code) if installed_printer().displays_help_codes() => {
+            let code = format!("[{}]", code);
+            write!(f, " {}", Style::new().dimmed().paint(code))
         }
+        _ => Ok(()),
     }
 }
 
 impl fmt::Debug for HelpInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            HelpInfo::Note(note) => f
+            HelpInfo::Note(note, code) => f
                 .debug_tuple("Note")
                 .field(&format_args!("{}", note))
+                .field(code)
                 .finish(),
             HelpInfo::Warning(warning) => f
                 .debug_tuple("Warning")
                 .field(&format_args!("{}", warning))
                 .finish(),
-            HelpInfo::Suggestion(suggestion) => f
+            HelpInfo::Suggestion(suggestion, code) => f
                 .debug_tuple("Suggestion")
                 .field(&format_args!("{}", suggestion))
+                .field(code)
+                .finish(),
+            HelpInfo::Recovery(recovery) => f
+                .debug_tuple("Recovery")
+                .field(&format_args!("{}", recovery))
                 .finish(),
+            HelpInfo::Remediation(steps) => f.debug_tuple("Remediation").field(steps).finish(),
+            HelpInfo::TryCommand(command) => f.debug_tuple("TryCommand").field(command).finish(),
+            HelpInfo::QuickFix(commands) => f.debug_tuple("QuickFix").field(commands).finish(),
+            HelpInfo::Status(code) => f.debug_tuple("Status").field(code).finish(),
             HelpInfo::Custom(custom) => f
                 .debug_tuple("CustomSection")
                 .field(&format_args!("{}", custom))
                 .finish(),
-            HelpInfo::Error(error) => f.debug_tuple("Error").field(error).finish(),
+            HelpInfo::Error(error, label, _) => {
+                f.debug_tuple("Error").field(error).field(label).finish()
+            }
+            HelpInfo::Report(report) => f
+                .debug_tuple("Report")
+                .field(&format_args!("{}", report))
+                .finish(),
+        }
+    }
+}
+
+/// An error section along with the number of identical error sections grouped
+/// into it and their labels
+pub(crate) struct ErrorGroup<'a> {
+    pub(crate) error: &'a (dyn std::error::Error + 'static),
+    pub(crate) count: usize,
+    pub(crate) labels: &'a [&'a str],
+}
+
+impl Display for ErrorGroup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MAX_LABELS: usize = 3;
+
+        // a lot here
+        let errors = std::iter::successors(Some(self.error), |e| e.source());
+
+        write!(f, "Error:")?;
+        if self.count > 1 {
+            write!(f, " ×{}", self.count)?;
+        }
+
+        let mut buf = String::new();
+        for (n, error) in errors.enumerate() {
+            writeln!(f)?;
+            buf.clear();
+            write!(&mut buf, "{}", error).unwrap();
+            write!(indented(f).ind(n), "{}", Red.make_intense().paint(&buf))?;
+        }
+
+        if !self.labels.is_empty() {
+            write!(
+                f,
+                "\n   affected: {}",
+                self.labels[..self.labels.len().min(MAX_LABELS)].join(", ")
+            )?;
+            if self.labels.len() > MAX_LABELS {
+                write!(f, ", … {} more", self.labels.len() - MAX_LABELS)?;
+            }
         }
+
+        Ok(())
     }
 }
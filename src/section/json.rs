@@ -0,0 +1,128 @@
+//! Field level diffs of serializable values
+use crate::ColorExt;
+use ansi_term::Color::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// The differences between two serializable values, rendered one field per line
+///
+/// # Details
+///
+/// Both values are serialized to JSON and compared field by field, recursing
+/// into nested objects. Each differing field is rendered with its dotted path:
+/// fields only present in the actual value are prefixed with a green `+`,
+/// fields missing from it with a red `-`, and fields whose value changed with a
+/// yellow `~` followed by both values. Arrays and other values are compared as
+/// a whole. Equal values render nothing.
+///
+/// A `JsonDiff` is constructed with [`SectionExt::json_diff`].
+///
+/// [`SectionExt::json_diff`]: trait.SectionExt.html#tymethod.json_diff
+#[derive(Debug, Clone)]
+pub struct JsonDiff {
+    changes: Result<Vec<Change>, String>,
+}
+
+#[derive(Debug, Clone)]
+enum Change {
+    Added(String, Value),
+    Removed(String, Value),
+    Changed(String, Value, Value),
+}
+
+impl JsonDiff {
+    pub(crate) fn new<T, U>(expected: &T, actual: &U) -> Self
+    where
+        T: Serialize + ?Sized,
+        U: Serialize + ?Sized,
+    {
+        let changes = serde_json::to_value(expected)
+            .and_then(|expected| Ok((expected, serde_json::to_value(actual)?)))
+            .map(|(expected, actual)| {
+                let mut changes = vec![];
+                diff_values(&mut changes, String::new(), expected, actual);
+                changes
+            })
+            .map_err(|e| e.to_string());
+
+        Self { changes }
+    }
+}
+
+fn diff_values(changes: &mut Vec<Change>, path: String, expected: Value, actual: Value) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            diff_objects(changes, &path, expected, actual)
+        }
+        (expected, actual) if expected != actual => {
+            let path = if path.is_empty() { ".".into() } else { path };
+            changes.push(Change::Changed(path, expected, actual));
+        }
+        _ => {}
+    }
+}
+
+fn diff_objects(
+    changes: &mut Vec<Change>,
+    path: &str,
+    mut expected: Map<String, Value>,
+    actual: Map<String, Value>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+
+    for (key, actual) in actual {
+        match expected.remove(&key) {
+            Some(expected) => diff_values(changes, join(&key), expected, actual),
+            None => changes.push(Change::Added(join(&key), actual)),
+        }
+    }
+
+    for (key, expected) in expected {
+        changes.push(Change::Removed(join(&key), expected));
+    }
+}
+
+impl fmt::Display for JsonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let changes = match &self.changes {
+            Ok(changes) => changes,
+            Err(error) => return write!(f, "could not serialize the values: {}", error),
+        };
+
+        for (n, change) in changes.iter().enumerate() {
+            if n > 0 {
+                f.write_str("\n")?;
+            }
+
+            match change {
+                Change::Added(path, value) => write!(
+                    f,
+                    "{}",
+                    Green.make_intense().paint(format!("+ {}: {}", path, value))
+                )?,
+                Change::Removed(path, value) => write!(
+                    f,
+                    "{}",
+                    Red.make_intense().paint(format!("- {}: {}", path, value))
+                )?,
+                Change::Changed(path, expected, actual) => write!(
+                    f,
+                    "{} {}: {} → {}",
+                    Yellow.make_intense().paint("~"),
+                    path,
+                    Red.make_intense().paint(expected.to_string()),
+                    Green.make_intense().paint(actual.to_string())
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
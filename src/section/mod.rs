@@ -2,6 +2,11 @@
 use std::fmt::{self, Display, Write};
 
 pub(crate) mod help;
+#[cfg(feature = "json")]
+mod json;
+
+#[cfg(feature = "json")]
+pub use json::JsonDiff;
 
 /// An indenteted section with a header for an error report
 ///
@@ -43,10 +48,245 @@ pub(crate) mod help;
 ///     }
 /// }
 /// ```
+///
+/// # Formatting
+///
+/// A width, e.g. `{:60}`, word wraps each line of the body so the indented
+/// body fits within that many columns, and a precision, e.g. `{:.200}`, caps
+/// the whole section at that many characters, ending it with `…` if anything
+/// was cut off. Escape sequences are neither counted nor split. Fill and
+/// alignment flags are ignored.
+///
+/// ```rust
+/// use color_anyhow::SectionExt;
+///
+/// let section = "the quick brown fox jumps over the lazy dog".header("Story:");
+///
+/// assert_eq!(
+///     format!("{}", section),
+///     "Story:\n   the quick brown fox jumps over the lazy dog"
+/// );
+/// assert_eq!(
+///     format!("{:24}", section),
+///     "Story:\n   the quick brown fox\n   jumps over the lazy\n   dog"
+/// );
+/// assert_eq!(format!("{:.16}", section), "Story:\n   the q…");
+/// assert_eq!(format!("{:>60}", section), format!("{:60}", section));
+/// ```
+///
+/// Preformatted bodies, like tables with aligned columns, can opt out of word
+/// wrapping with [`no_wrap`], which instead clips lines wider than the width
+/// with a trailing `…`, or with [`overflow`], which leaves them as they are.
+/// [`wrap_at`] wraps a section at its own width regardless of the width of the
+/// report.
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, SectionExt};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let table = "\
+/// name       | size  | modified
+/// ---------- | ----- | -------------------
+/// config.toml| 1.2kB | 2020-06-01 12:00:00";
+///
+/// let report = Err::<(), Error>(anyhow!("could not load the config"))
+///     .section(table.header("Files:").no_wrap())
+///     .section(table.header("Files (overflowing):").overflow())
+///     .section("the config is read from the first file that exists".header("Note:").wrap_at(30))
+///     .unwrap_err();
+/// let report = format!("{:32?}", report);
+///
+/// assert!(report.contains(
+///     "Files:\n   name       | size  | modified\n   ---------- | ----- | -------…\n   config.toml| 1.2kB | 2020-06…"
+/// ));
+/// assert!(report.contains(
+///     "Files (overflowing):\n   name       | size  | modified\n   ---------- | ----- | -------------------\n"
+/// ));
+/// assert!(report.contains(
+///     "Note:\n   the config is read from the\n   first file that exists"
+/// ));
+/// ```
+///
+/// # Writing the body
+///
+/// The header is written once, right before the first write of the body that isn't empty,
+/// however the body splits its output into writes. `\r\n` line endings are rendered as `\n`,
+/// so the output of Windows commands is indented and trimmed like any other.
+///
+/// ```rust
+/// use color_anyhow::SectionExt;
+/// use std::fmt::{self, Write};
+///
+/// /// Writes its text one byte at a time, with empty writes in between
+/// struct Trickle(&'static str);
+///
+/// impl fmt::Display for Trickle {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("")?;
+///         for i in 0..self.0.len() {
+///             f.write_str(&self.0[i..i + 1])?;
+///             f.write_str("")?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// assert_eq!(Trickle("").header("Stdout:").to_string(), "");
+/// assert_eq!(Trickle(" \r\n\r\n").header("Stdout:").to_string(), "");
+/// assert_eq!(
+///     Trickle("built\r\n\r\nok\r\n").header("Stdout:").to_string(),
+///     "Stdout:\n   built\n\n   ok\n"
+/// );
+/// assert_eq!(
+///     "built\r\n\r\nok\r\n".header("Stdout:").to_string(),
+///     Trickle("built\r\n\r\nok\r\n").header("Stdout:").to_string()
+/// );
+///
+/// // a write that is retried after the writer failed doesn't repeat the header
+/// struct Retry;
+///
+/// impl fmt::Display for Retry {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         if f.write_str("ok").is_err() {
+///             f.write_str("ok")?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// /// Fails its second write that isn't empty
+/// #[derive(Default)]
+/// struct Flaky(String, usize);
+///
+/// impl Write for Flaky {
+///     fn write_str(&mut self, s: &str) -> fmt::Result {
+///         self.1 += !s.is_empty() as usize;
+///         if self.1 == 2 {
+///             return Err(fmt::Error);
+///         }
+///         self.0.push_str(s);
+///         Ok(())
+///     }
+/// }
+///
+/// let mut out = Flaky::default();
+/// write!(out, "{}", Retry.header(Trickle("Stdout:"))).unwrap();
+/// assert_eq!(out.0.matches('S').count(), 1);
+/// assert!(out.0.ends_with("ok"));
+/// ```
+///
+/// [`no_wrap`]: #method.no_wrap
+/// [`overflow`]: #method.overflow
+/// [`wrap_at`]: #method.wrap_at
 #[allow(missing_debug_implementations)]
 pub struct IndentedSection<H, B> {
     header: H,
     body: B,
+    layout: BodyLayout,
+    trim_empty: bool,
+    summary: Option<String>,
+}
+
+/// How the body of an `IndentedSection` is fit to the width it's formatted with
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BodyLayout {
+    Wrap,
+    WrapAt(usize),
+    Clip,
+    Overflow,
+}
+
+impl<H, B> IndentedSection<H, B> {
+    /// Mark the body as preformatted, lines are never wrapped and lines wider
+    /// than the width are clipped with a trailing `…`
+    pub fn no_wrap(mut self) -> Self {
+        self.layout = BodyLayout::Clip;
+        self
+    }
+
+    /// Mark the body as preformatted, lines are never wrapped and lines wider
+    /// than the width are left as they are
+    pub fn overflow(mut self) -> Self {
+        self.layout = BodyLayout::Overflow;
+        self
+    }
+
+    /// Word wrap the body to fit within `cols` columns, instead of the width of
+    /// the report
+    pub fn wrap_at(mut self, cols: usize) -> Self {
+        self.layout = BodyLayout::WrapAt(cols);
+        self
+    }
+
+    /// Treat a body that renders only whitespace as empty, omitting the section, defaults
+    /// to true
+    ///
+    /// # Details
+    ///
+    /// With `false` the header is printed as soon as the body produces any output, so
+    /// whitespace written on purpose, e.g. blank lines standing for the empty output of a
+    /// command, is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::SectionExt;
+    ///
+    /// assert_eq!("  \n\n".header("Stdout:").to_string(), "");
+    /// assert_eq!("  \n\n".header("Stdout:").trim_empty(false).to_string(), "Stdout:\n     \n\n");
+    /// assert_eq!(" ok\n".header("Stdout:").to_string(), "Stdout:\n    ok\n");
+    /// ```
+    pub fn trim_empty(mut self, cond: bool) -> Self {
+        self.trim_empty = cond;
+        self
+    }
+
+    /// Summarize the section in a single line, shown instead of the section where it's
+    /// collapsed
+    ///
+    /// # Details
+    ///
+    /// Renderers that can collapse sections show the summary while the section is collapsed,
+    /// e.g. `report_to_html` renders the section in a `<details>` element with the summary
+    /// as its `<summary>` when the report is collapsible. Plain text reports ignore the
+    /// summary and always show the full section.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, Section, SectionExt};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let stderr = "warning: unused variable `x`\nerror: expected `;`, found `}`";
+    /// let report = Err::<(), _>(anyhow!("the build failed"))
+    ///     .section(stderr.header("Stderr:").summary("1 error, 1 warning"))
+    ///     .unwrap_err();
+    ///
+    /// let html = color_anyhow::report_to_html(&report, true);
+    /// assert!(html.contains("<details><summary>1 error, 1 warning</summary><pre>Stderr:\n"));
+    ///
+    /// let plain = format!("{:?}", report);
+    /// assert!(plain.contains("Stderr:\n   warning: unused variable `x`"));
+    /// assert!(!plain.contains("1 error, 1 warning"));
+    /// ```
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+}
+
+thread_local! {
+    /// The summary of the outermost section formatted by [`summary_of`], while it runs
+    static SUMMARY: std::cell::RefCell<Option<Option<String>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// The summary of `section`, if it's an `IndentedSection` with a summary
+pub(crate) fn summary_of(section: &dyn Display) -> Option<String> {
+    SUMMARY.with(|summary| *summary.borrow_mut() = Some(None));
+    let _ = write!(crate::writers::ByteCounter::default(), "{}", section);
+    SUMMARY.with(|summary| summary.borrow_mut().take().flatten())
 }
 
 impl<H, B> fmt::Display for IndentedSection<H, B>
@@ -55,6 +295,54 @@ where
     B: Display + Send + Sync + 'static,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(summary) = &self.summary {
+            SUMMARY.with(|collected| {
+                if let Some(collected @ None) = &mut *collected.borrow_mut() {
+                    *collected = Some(summary.clone());
+                }
+            });
+        }
+
+        let width = match self.layout {
+            BodyLayout::WrapAt(cols) => Some(cols),
+            BodyLayout::Overflow => None,
+            BodyLayout::Wrap | BodyLayout::Clip => f.width(),
+        };
+
+        if width.is_none() && f.precision().is_none() {
+            return self.write_to(f, &self.body);
+        }
+
+        let mut body = self.body.to_string();
+        if let Some(width) = width {
+            let width = width.saturating_sub(3);
+            body = match self.layout {
+                BodyLayout::Clip => body
+                    .lines()
+                    .map(|line| crate::writers::truncate_visible(line, width))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => wrap_lines(&body, Some(width))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+        }
+
+        let mut out = String::new();
+        self.write_to(&mut out, &body)?;
+
+        match f.precision() {
+            Some(max) => f.write_str(&crate::writers::truncate_visible(&out, max)),
+            None => f.write_str(&out),
+        }
+    }
+}
+
+impl<H, B> IndentedSection<H, B>
+where
+    H: Display,
+{
+    fn write_to<W: fmt::Write>(&self, f: &mut W, body: &dyn Display) -> fmt::Result {
         let mut headered = crate::writers::HeaderWriter {
             inner: f,
             header: &self.header,
@@ -67,17 +355,131 @@ where
             started: false,
         };
 
-        let mut headered = headered.ready();
+        let mut headered = match self.trim_empty {
+            true => headered.ready_unless_blank(),
+            false => headered.ready(),
+        };
 
         let mut indented = indenter::indented(&mut headered)
             .with_format(indenter::Format::Uniform { indentation: "   " });
 
-        write!(&mut indented, "{}", self.body)?;
+        // bodies with `\r\n` line endings, e.g. the output of Windows commands, would
+        // otherwise keep a `\r` at the end of each indented line
+        let mut body_writer = crate::writers::NormalizeCrlf::new(&mut indented);
+        write!(&mut body_writer, "{}", body)?;
+        body_writer.finish()
+    }
+}
+
+/// An ordered list of steps for remediating an error
+///
+/// # Details
+///
+/// Steps are rendered numbered, with any continuation lines aligned under the
+/// text of their step. When formatted with a width, e.g. `{:60}`, each step is
+/// word wrapped to fit within that many columns. An empty list renders nothing.
+///
+/// Steps can be attached as a regular section with a custom header, or via
+/// [`Section::remediation`], which renders them after all suggestions.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::section::Steps;
+///
+/// let steps = Steps::new()
+///     .push("check that the config file exists")
+///     .push("run `myapp doctor`");
+///
+/// assert_eq!(
+///     format!("{:24}", steps),
+///     "1. check that the config\n   file exists\n2. run `myapp doctor`"
+/// );
+/// ```
+///
+/// [`Section::remediation`]: trait.Section.html#tymethod.remediation
+#[derive(Debug, Clone, Default)]
+pub struct Steps {
+    steps: Vec<String>,
+}
+
+impl Steps {
+    /// Construct an empty list of steps
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step to the end of the list
+    pub fn push<D>(mut self, step: D) -> Self
+    where
+        D: Display,
+    {
+        self.steps.push(step.to_string());
+        self
+    }
+
+    /// Returns true if the list contains no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub(crate) fn steps(&self) -> &[String] {
+        &self.steps
+    }
+}
+
+impl fmt::Display for Steps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let num_width = self.steps.len().to_string().len();
+        let text_width = f.width().map(|width| width.saturating_sub(num_width + 2));
+        let continuation = " ".repeat(num_width + 2);
+
+        for (n, step) in self.steps.iter().enumerate() {
+            if n > 0 {
+                f.write_str("\n")?;
+            }
+
+            write!(f, "{:>width$}. ", n + 1, width = num_width)?;
+            for (i, line) in wrap_lines(step, text_width).enumerate() {
+                if i > 0 {
+                    write!(f, "\n{}", continuation)?;
+                }
+                f.write_str(line)?;
+            }
+        }
 
         Ok(())
     }
 }
 
+/// Split `text` into its lines, word wrapping each to `width` columns if given
+pub(crate) fn wrap_lines(text: &str, width: Option<usize>) -> impl Iterator<Item = &str> {
+    text.lines().flat_map(move |line| {
+        let mut lines = vec![];
+        let mut rest = line;
+
+        if let Some(width) = width {
+            while rest.chars().count() > width {
+                // break at the last space that fits, or the first one after
+                // if a single word is wider than the line
+                let limit = rest
+                    .char_indices()
+                    .nth(width + 1)
+                    .map_or(rest.len(), |(i, _)| i);
+                let split = match rest[..limit].rfind(' ').or_else(|| rest.find(' ')) {
+                    Some(split) => split,
+                    None => break,
+                };
+                lines.push(&rest[..split]);
+                rest = rest[split..].trim_start();
+            }
+        }
+
+        lines.push(rest);
+        lines
+    })
+}
+
 /// Extension trait for constructing sections with commonly used formats
 pub trait SectionExt: Sized {
     /// Add a header to a `Section` and indent the body
@@ -86,7 +488,8 @@ pub trait SectionExt: Sized {
     ///
     /// Bodies are always indented to the same level as error messages and spans.
     /// The header is not printed if the display impl of the body produces no
-    /// output.
+    /// output, or only whitespace, unless whitespace is kept with
+    /// [`IndentedSection::trim_empty`].
     ///
     /// # Examples
     ///
@@ -106,9 +509,68 @@ pub trait SectionExt: Sized {
     ///
     /// assert_eq!(format!("{:?}", report), format!("{:?}", report2))
     /// ```
+    ///
+    /// [`IndentedSection::trim_empty`]: struct.IndentedSection.html#method.trim_empty
     fn header<C>(self, header: C) -> IndentedSection<C, Self>
     where
         C: Display + Send + Sync + 'static;
+
+    /// Use `self` as the header of a section listing the fields that differ between two
+    /// serializable values
+    ///
+    /// # Details
+    ///
+    /// See [`JsonDiff`] for how the differences are computed and rendered. The section is
+    /// omitted from the report when the values are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::SectionExt;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Server {
+    ///     host: &'static str,
+    ///     port: u16,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Config {
+    ///     name: &'static str,
+    ///     server: Server,
+    ///     retries: Option<u32>,
+    /// }
+    ///
+    /// let default = Config {
+    ///     name: "myapp",
+    ///     server: Server { host: "localhost", port: 8080 },
+    ///     retries: None,
+    /// };
+    /// let actual = Config {
+    ///     name: "myapp",
+    ///     server: Server { host: "localhost", port: 80 },
+    ///     retries: Some(3),
+    /// };
+    ///
+    /// let section = "Changed from default:".json_diff(&default, &actual).to_string();
+    ///
+    /// // changed fields are marked with a yellow `~`, the old value red and the new value green
+    /// assert!(section.contains("\u{1b}[38;5;11m~\u{1b}[0m retries: \u{1b}[38;5;9mnull\u{1b}[0m → \u{1b}[38;5;10m3"));
+    /// assert!(section.contains("\u{1b}[38;5;11m~\u{1b}[0m server.port: \u{1b}[38;5;9m8080\u{1b}[0m → \u{1b}[38;5;10m80"));
+    /// assert!(!section.contains("name"));
+    /// assert!(!section.contains("host"));
+    ///
+    /// assert_eq!("Unchanged:".json_diff(&default, &default).to_string(), "");
+    /// ```
+    ///
+    /// [`JsonDiff`]: struct.JsonDiff.html
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    fn json_diff<T, U>(self, expected: &T, actual: &U) -> IndentedSection<Self, JsonDiff>
+    where
+        T: serde::Serialize + ?Sized,
+        U: serde::Serialize + ?Sized;
 }
 
 impl<T> SectionExt for T
@@ -119,7 +581,28 @@ where
     where
         C: Display + Send + Sync + 'static,
     {
-        IndentedSection { body: self, header }
+        IndentedSection {
+            body: self,
+            header,
+            layout: BodyLayout::Wrap,
+            trim_empty: true,
+            summary: None,
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn json_diff<U, V>(self, expected: &U, actual: &V) -> IndentedSection<Self, JsonDiff>
+    where
+        U: serde::Serialize + ?Sized,
+        V: serde::Serialize + ?Sized,
+    {
+        IndentedSection {
+            header: self,
+            body: JsonDiff::new(expected, actual),
+            layout: BodyLayout::Wrap,
+            trim_empty: true,
+            summary: None,
+        }
     }
 }
 
@@ -136,6 +619,83 @@ where
 /// sections are displayed after all other sections with no extra newlines between subsequent Section
 /// sections. They consist only of a header portion and are prepended with a colored string
 /// indicating the kind of section, e.g. `Note: This might have failed due to ..."
+///
+/// When a report is formatted with a width, e.g. `format!("{:40?}", report)`, custom and help
+/// sections are word wrapped to that many columns.
+///
+/// Calling these methods on a `Result` whose error isn't an `anyhow::Error` yet converts the error
+/// into a report first, so its backtrace and `SpanTrace` are captured at the call rather than where
+/// the error was created, unlike with `?` or `.context()` at the same place. The location of that
+/// call is recorded and rendered as a dimmed `(context attached at file:line)` below the chain of
+/// errors, see [`Handler::converted_at`].
+///
+/// Sections are attached in the order the calls of these methods complete on the error path. The
+/// closure of a lazy method such as `with_section` runs when its call is reached, and its section
+/// is attached once the closure returns, so sections the closure attaches to other reports, or
+/// reports it renders, never interleave with the sections of this report. Sections added when the
+/// report is created, like the ones from `HookBuilder::add_conditional_section` and collected
+/// warnings, come before all explicitly attached ones. The order is the same in every build
+/// profile and is the order [`extract`] returns the sections in, help sections are rendered in
+/// this order after all custom sections.
+///
+/// [`Handler::converted_at`]: struct.Handler.html#method.converted_at
+/// [`extract`]: fn.extract.html
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("config could not be loaded"))
+///     .suggestion("create the config with `myapp init` or pass one with --config")
+///     .unwrap_err();
+///
+/// assert!(format!("{:?}", report)
+///     .contains("Suggestion: create the config with `myapp init` or pass one with --config"));
+/// assert!(format!("{:40?}", report).contains(
+///     "Suggestion: create the config with\n            `myapp init` or pass one\n            with --config"
+/// ));
+/// ```
+///
+/// The order of sections attached explicitly, lazily and when the report is created:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::HookBuilder, Section, SectionData};
+///
+/// HookBuilder::default()
+///     .add_conditional_section(Box::new(|_| true), Box::new(|| "on creation".to_owned()))
+///     .install()
+///     .unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("the sync failed"))
+///     .note("explicit")
+///     .with_section(|| {
+///         let other = Err::<(), Error>(anyhow!("unrelated"))
+///             .note("attached to another report")
+///             .unwrap_err();
+///         assert!(format!("{:?}", other).contains("attached to another report"));
+///         "lazy section"
+///     })
+///     .with_note(|| "lazy note")
+///     .warning("explicit warning")
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     color_anyhow::extract(&report).sections,
+///     [
+///         SectionData::Custom("Diagnostics:\n   on creation".into()),
+///         SectionData::Note("explicit".into()),
+///         SectionData::Custom("lazy section".into()),
+///         SectionData::Note("lazy note".into()),
+///         SectionData::Warning("explicit warning".into()),
+///     ]
+/// );
+/// ```
 pub trait Section<T>: crate::private::Sealed {
     /// Add a section to an error report, to be displayed after the chain of errors.
     ///
@@ -186,6 +746,152 @@ pub trait Section<T>: crate::private::Sealed {
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D;
 
+    /// Add a Section to an error report, to be displayed after the chain of errors. The closure to
+    /// create the Section is evaluated only once the report is rendered.
+    ///
+    /// # Details
+    ///
+    /// Unlike `with_section`, which runs its closure as soon as it is called on an `Err`, the
+    /// closure is stored in the report and only runs when the report is displayed, so expensive
+    /// sections like the tail of a log file are skipped for errors that are handled without ever
+    /// being reported. The closure runs at most once, later renders reuse its output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let calls = Arc::new(AtomicUsize::new(0));
+    /// let counter = calls.clone();
+    /// let report = Err::<(), Error>(anyhow!("the worker crashed"))
+    ///     .deferred_section(move || {
+    ///         counter.fetch_add(1, Ordering::SeqCst);
+    ///         "last log line: out of memory"
+    ///     })
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(calls.load(Ordering::SeqCst), 0);
+    /// assert!(format!("{:?}", report).contains("last log line: out of memory"));
+    /// assert!(format!("{:?}", report).contains("last log line: out of memory"));
+    /// assert_eq!(calls.load(Ordering::SeqCst), 1);
+    /// ```
+    fn deferred_section<D, F>(self, section: F) -> anyhow::Result<T>
+    where
+        D: Display,
+        F: Fn() -> D + Send + Sync + 'static;
+
+    /// Add a section with the command line the program was invoked with and its current
+    /// working directory, for reproducing the failure
+    ///
+    /// # Details
+    ///
+    /// Both are read when the error occurs. Arguments that are empty or contain whitespace
+    /// or quotes are single quoted, like a shell would need them to be, and arguments that
+    /// aren't valid unicode are rendered lossily. The lines are never wrapped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the config could not be loaded"))
+    ///     .with_invocation_context()
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// let args: Vec<_> = std::env::args().collect();
+    /// let cwd = std::env::current_dir().unwrap();
+    /// assert!(report.contains(&format!(
+    ///     "Invocation:\n   Command:   {}\n   Directory: {}",
+    ///     args.join(" "),
+    ///     cwd.display()
+    /// )));
+    /// ```
+    fn with_invocation_context(self) -> anyhow::Result<T>;
+
+    /// Add a section with the OpenTelemetry trace and span IDs of the operation that failed,
+    /// for finding its trace in a tracing backend like Jaeger or Tempo
+    ///
+    /// # Details
+    ///
+    /// The IDs are rendered as `Trace: <trace_id> Span: <span_id>`. Only valid IDs are
+    /// rendered, i.e. 32 hex digits for the trace ID and 16 for the span ID, not all of them
+    /// zero, and nothing is added if neither is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    /// let span_id = "00f067aa0ba902b7";
+    /// let report = Err::<(), Error>(anyhow!("the checkout failed"))
+    ///     .with_otel_ids(trace_id, span_id)
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report)
+    ///     .contains("Trace: 4bf92f3577b34da6a3ce929d0e0e4736 Span: 00f067aa0ba902b7"));
+    ///
+    /// // an unsampled span has no valid span ID
+    /// let report = Err::<(), Error>(anyhow!("the checkout failed"))
+    ///     .with_otel_ids(trace_id, "0000000000000000")
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("Trace: 4bf92f3577b34da6a3ce929d0e0e4736"));
+    /// assert!(!report.contains("Span:"));
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the checkout failed"))
+    ///     .with_otel_ids("00000000000000000000000000000000", "not an id")
+    ///     .unwrap_err();
+    /// assert!(!format!("{:?}", report).contains("Trace:"));
+    /// ```
+    fn with_otel_ids<I, S>(self, trace_id: I, span_id: S) -> anyhow::Result<T>
+    where
+        I: Display,
+        S: Display;
+
+    /// Attach a backtrace to an error report, captured by a closure that is lazily evaluated only
+    /// in the case of an error.
+    ///
+    /// # Details
+    ///
+    /// Use this on hot paths instead of capturing backtraces eagerly with `RUST_LIB_BACKTRACE`,
+    /// the backtrace is then only captured when it ends up in a report. Reports that already
+    /// carry a backtrace keep theirs, without evaluating the closure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Backtrace, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let mut captured = false;
+    /// Ok::<_, Error>(())
+    ///     .with_lazy_backtrace(|| {
+    ///         captured = true;
+    ///         Backtrace::new()
+    ///     })
+    ///     .unwrap();
+    /// assert!(!captured);
+    ///
+    /// let report = Err::<(), _>(anyhow!("cache miss"))
+    ///     .with_lazy_backtrace(Backtrace::new)
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report).contains("BACKTRACE"));
+    /// ```
+    fn with_lazy_backtrace<F>(self, backtrace: F) -> anyhow::Result<T>
+    where
+        F: FnOnce() -> crate::Backtrace;
+
     /// Add an error section to an error report, to be displayed after the primary error message
     /// section.
     ///
@@ -208,39 +914,108 @@ pub trait Section<T>: crate::private::Sealed {
     where
         E: std::error::Error + Send + Sync + 'static;
 
-    /// Add an error section to an error report, to be displayed after the primary error message
-    /// section. The closure to create the Section is lazily evaluated only in the case of an error.
+    /// Add an error section labeled with what it affected, such as the file being processed, to
+    /// an error report.
+    ///
+    /// # Details
+    ///
+    /// With [`HookBuilder::group_errors`] enabled, error sections whose rendered text is identical
+    /// are grouped into one block annotated with the number of occurrences, the labels of the
+    /// grouped errors are listed under the block.
     ///
     /// # Examples
     ///
-    /// ```rust,should_panic
-    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
     /// use thiserror::Error;
     ///
     /// #[derive(Debug, Error)]
     /// #[error("{0}")]
-    /// struct StringError(String);
+    /// struct StrError(&'static str);
     ///
-    /// Err(anyhow!("command failed"))
-    ///     .with_error(|| StringError("got one error".into()))
-    ///     .with_error(|| StringError("got a second error".into()))?;
-    /// # Ok::<_, Error>(())
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .group_errors(true)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("batch failed"))
+    ///     .error_labeled("a.txt", StrError("permission denied"))
+    ///     .error_labeled("b.txt", StrError("file too large"))
+    ///     .error_labeled("c.txt", StrError("permission denied"))
+    ///     .error(StrError("disk full"))
+    ///     .error_labeled("d.txt", StrError("permission denied"))
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert_eq!(report.matches("permission denied").count(), 1);
+    /// assert!(report.contains("Error: ×3\n   0: permission denied\n   affected: a.txt, c.txt, d.txt"));
+    /// let denied = report.find("permission denied").unwrap();
+    /// let too_large = report.find("file too large").unwrap();
+    /// let disk_full = report.find("disk full").unwrap();
+    /// assert!(denied < too_large && too_large < disk_full);
     /// ```
-    fn with_error<E, F>(self, error: F) -> anyhow::Result<T>
+    ///
+    /// [`HookBuilder::group_errors`]: config/struct.HookBuilder.html#method.group_errors
+    fn error_labeled<L, E>(self, label: L, error: E) -> anyhow::Result<T>
     where
-        F: FnOnce() -> E,
+        L: Display,
         E: std::error::Error + Send + Sync + 'static;
 
-    /// Add a Note to an error report, to be displayed after the chain of errors.
+    /// Add another error report as an error section, to be displayed after the primary error
+    /// message section along with the backtrace captured by that report.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// # use std::{error::Error, fmt::{self, Display}};
-    /// # use color_anyhow::anyhow::Result;
-    /// # #[derive(Debug)]
-    /// # struct FakeErr;
-    /// # impl Display for FakeErr {
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("batch failed"))
+    ///     .error_report(anyhow!("could not read a.txt"))
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains("could not read a.txt"));
+    /// assert_eq!(report.matches("BACKTRACE").count(), 2);
+    /// ```
+    fn error_report(self, report: anyhow::Error) -> anyhow::Result<T>;
+
+    /// Add an error section to an error report, to be displayed after the primary error message
+    /// section. The closure to create the Section is lazily evaluated only in the case of an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,should_panic
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    /// use thiserror::Error;
+    ///
+    /// #[derive(Debug, Error)]
+    /// #[error("{0}")]
+    /// struct StringError(String);
+    ///
+    /// Err(anyhow!("command failed"))
+    ///     .with_error(|| StringError("got one error".into()))
+    ///     .with_error(|| StringError("got a second error".into()))?;
+    /// # Ok::<_, Error>(())
+    /// ```
+    fn with_error<E, F>(self, error: F) -> anyhow::Result<T>
+    where
+        F: FnOnce() -> E,
+        E: std::error::Error + Send + Sync + 'static;
+
+    /// Add a Note to an error report, to be displayed after the chain of errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::{error::Error, fmt::{self, Display}};
+    /// # use color_anyhow::anyhow::Result;
+    /// # #[derive(Debug)]
+    /// # struct FakeErr;
+    /// # impl Display for FakeErr {
     /// #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     /// #         write!(f, "FakeErr")
     /// #     }
@@ -293,6 +1068,47 @@ pub trait Section<T>: crate::private::Sealed {
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D;
 
+    /// Add a Note identified by a stable `code`, for tools that act on notes without matching
+    /// their text, to be displayed after the chain of errors with a dimmed `[CODE]` suffix.
+    ///
+    /// # Details
+    ///
+    /// The code is returned by [`extract`] as a `SectionData::CodedNote`, and included in
+    /// the JSON of an `HttpReport`. The suffix can be hidden with
+    /// `HookBuilder::display_help_codes`. Notes with the same text and different codes are
+    /// separate notes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, SectionData};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the cache is stale"))
+    ///     .note_coded("C001", "the cache is rebuilt on the next start")
+    ///     .note("the cache is rebuilt on the next start")
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     color_anyhow::extract(&report).sections,
+    ///     [
+    ///         SectionData::CodedNote {
+    ///             code: "C001".into(),
+    ///             text: "the cache is rebuilt on the next start".into(),
+    ///         },
+    ///         SectionData::Note("the cache is rebuilt on the next start".into()),
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`extract`]: fn.extract.html
+    fn note_coded<C, D>(self, code: C, note: D) -> anyhow::Result<T>
+    where
+        C: Display,
+        D: Display + Send + Sync + 'static;
+
     /// Add a Warning to an error report, to be displayed after the chain of errors.
     fn warning<D>(self, warning: D) -> anyhow::Result<T>
     where
@@ -310,10 +1126,630 @@ pub trait Section<T>: crate::private::Sealed {
     where
         D: Display + Send + Sync + 'static;
 
+    /// Add a Suggestion identified by a stable `code`, for tools that offer fixes keyed by it
+    /// instead of by the text, to be displayed after the chain of errors with a dimmed
+    /// `[CODE]` suffix.
+    ///
+    /// # Details
+    ///
+    /// The code is returned by [`extract`] as a `SectionData::CodedSuggestion`, and included
+    /// in the JSON of an `HttpReport`. The suffix can be hidden with
+    /// `HookBuilder::display_help_codes`. Suggestions with the same text and different codes
+    /// are separate suggestions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}};
+    /// use color_anyhow::{Section, SectionData};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the lockfile is out of date"))
+    ///     .suggestion_coded("E1042", "run cargo update")
+    ///     .suggestion_coded("E1043", "run cargo update")
+    ///     .unwrap_err();
+    ///
+    /// let rendered = format!("{:?}", report);
+    /// assert!(rendered.contains("run cargo update \x1b[2m[E1042]\x1b[0m\n"));
+    /// assert!(rendered.ends_with("run cargo update \x1b[2m[E1043]\x1b[0m"));
+    ///
+    /// let codes: Vec<_> = color_anyhow::extract(&report)
+    ///     .sections
+    ///     .into_iter()
+    ///     .filter_map(|section| match section {
+    ///         SectionData::CodedSuggestion { code, .. } => Some(code),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(codes, ["E1042", "E1043"]);
+    /// ```
+    ///
+    /// [`extract`]: fn.extract.html
+    fn suggestion_coded<C, D>(self, code: C, suggestion: D) -> anyhow::Result<T>
+    where
+        C: Display,
+        D: Display + Send + Sync + 'static;
+
     /// Add a Suggestion to an error report, to be displayed after the chain of errors. The closure
     /// to create the Suggestion is lazily evaluated only in the case of an error.
     fn with_suggestion<D, F>(self, f: F) -> anyhow::Result<T>
     where
         D: Display + Send + Sync + 'static,
         F: FnOnce() -> D;
+
+    /// Add a `Did you mean '<candidate>'?` Suggestion for the candidate closest to a mistyped
+    /// input, to be displayed after the chain of errors.
+    ///
+    /// # Details
+    ///
+    /// Candidates are compared to `input` by their edit distance. The closest one is suggested
+    /// if it is within a third of the length of the longer of the two, rounded down but at
+    /// least one edit, ties go to the earlier candidate. Nothing is attached if no candidate is
+    /// close enough or `input` matches a candidate exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let commands = ["build", "check", "install", "publish"];
+    ///
+    /// let report = Err::<(), Error>(anyhow!("no such command: `instal`"))
+    ///     .suggestion_did_you_mean("instal", &commands)
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report).contains("Suggestion: Did you mean 'install'?"));
+    ///
+    /// let report = Err::<(), Error>(anyhow!("no such command: `deploy`"))
+    ///     .suggestion_did_you_mean("deploy", &commands)
+    ///     .unwrap_err();
+    /// assert!(!format!("{:?}", report).contains("Suggestion"));
+    /// ```
+    fn suggestion_did_you_mean(self, input: &str, candidates: &[&str]) -> anyhow::Result<T>;
+
+    /// Add a hint about the error of type `E` in the chain of errors, to be displayed directly
+    /// beneath its entry.
+    ///
+    /// # Details
+    ///
+    /// The hint is rendered under the first entry of the chain whose error is an `E`. If no
+    /// entry is an `E`, or the chain isn't rendered, it's displayed as a note with the other
+    /// help text instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::{anyhow, Context, Error}, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let io_error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "permission denied");
+    /// let report = Err::<(), _>(io_error)
+    ///     .context("could not open data/cache.db")
+    ///     .context("the cache could not be loaded")
+    ///     .inline_hint_for::<std::io::Error, _>("the cache is owned by root, check its permissions")
+    ///     .inline_hint_for::<std::num::ParseIntError, _>("sizes are given in bytes")
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains(
+    ///     "   2: permission denied\n      Hint: the cache is owned by root, check its permissions\n"
+    /// ));
+    /// assert!(report.ends_with("Note: sizes are given in bytes"));
+    /// ```
+    fn inline_hint_for<E, D>(self, hint: D) -> anyhow::Result<T>
+    where
+        E: std::error::Error + 'static,
+        D: Display;
+
+    /// Add a command the user can run to fix or diagnose the error, to be displayed as a
+    /// `Try:` line with the other help text.
+    ///
+    /// # Details
+    ///
+    /// The command is stored exactly as given, without any quoting or trimming, and is read
+    /// back with [`try_commands_of`], e.g. to offer it as an action in a tool's UI.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the lockfile is out of date"))
+    ///     .try_command("cargo update --workspace")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).contains("Try: cargo update --workspace"));
+    /// ```
+    ///
+    /// [`try_commands_of`]: ../fn.try_commands_of.html
+    fn try_command<D>(self, command: D) -> anyhow::Result<T>
+    where
+        D: Display;
+
+    /// Add the exact shell commands that fix the error, to be displayed as a `Quick fix:`
+    /// with the other help text, each on its own line after a `$ ` prompt.
+    ///
+    /// # Details
+    ///
+    /// Every non-blank line of `command` is rendered as a separate command. Commands are
+    /// never wrapped or truncated, so each line can be copied into a shell as is. The prompt is
+    /// dimmed and the command is bold, unless colors are disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the lockfile is out of date"))
+    ///     .quick_fix("cargo update --workspace")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).ends_with(
+    ///     "\x1b[38;5;10mQuick fix\x1b[0m:\n   \x1b[2m$ \x1b[0m\x1b[1mcargo update --workspace\x1b[0m"
+    /// ));
+    /// ```
+    ///
+    /// Fixes that take several commands, without colors:
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the database schema is outdated"))
+    ///     .quick_fix("myapp db backup\nmyapp db migrate --latest\n")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).ends_with(
+    ///     "Quick fix:\n   $ myapp db backup\n   $ myapp db migrate --latest"
+    /// ));
+    /// ```
+    fn quick_fix<D>(self, command: D) -> anyhow::Result<T>
+    where
+        D: Display;
+
+    /// Attach a transport status code to an error report, for mapping errors to HTTP or gRPC
+    /// statuses in a response layer.
+    ///
+    /// # Details
+    ///
+    /// The code is read back with [`status_hint`]. If several codes are attached the last one
+    /// wins, so a caller can override the code of the errors it propagates. The code is only
+    /// rendered, as a `Status:` line after the help text, with
+    /// `HookBuilder::display_status_hints`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("no user with the id 42"))
+    ///     .status_hint(404)
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(color_anyhow::status_hint(&report), Some(404));
+    /// assert!(!format!("{:?}", report).contains("Status"));
+    /// ```
+    ///
+    /// [`status_hint`]: ../fn.status_hint.html
+    fn status_hint(self, code: u16) -> anyhow::Result<T>;
+
+    /// Add a breadcrumb describing what was being done when the error occurred
+    ///
+    /// # Details
+    ///
+    /// Breadcrumbs are a lightweight alternative to sections and contexts for recording the
+    /// steps that led to an error as it propagates up the call stack. All breadcrumbs of a
+    /// report are rendered on a single `Context:` line before the help text, outermost
+    /// first, i.e. in the reverse of the order they were attached, like the chain of errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Result, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// fn read_field() -> Result<()> {
+    ///     Err(anyhow!("unexpected end of input")).breadcrumb("reading field `len`")
+    /// }
+    ///
+    /// fn parse_header() -> Result<()> {
+    ///     read_field().breadcrumb("parsing the header")
+    /// }
+    ///
+    /// fn load() -> Result<()> {
+    ///     parse_header().breadcrumb("loading `archive.tar`")
+    /// }
+    ///
+    /// let report = format!("{:?}", load().unwrap_err());
+    /// assert!(report.contains(
+    ///     "Context: loading `archive.tar` > parsing the header > reading field `len`"
+    /// ));
+    /// ```
+    fn breadcrumb(self, breadcrumb: &str) -> anyhow::Result<T>;
+
+    /// Override the leading label rendered before the chain of errors of this report
+    ///
+    /// # Details
+    ///
+    /// The label is rendered in bold red before the chain, in place of the label configured
+    /// with `HookBuilder::chain_label`, if any. If several labels are attached the last one
+    /// wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the disk is full"))
+    ///     .chain_label("fatal:")
+    ///     .unwrap_err();
+    /// assert!(format!("{:?}", report).starts_with("fatal:\n   0: the disk is full"));
+    /// ```
+    fn chain_label(self, label: &str) -> anyhow::Result<T>;
+
+    /// Attach how severe the failure is to an error report
+    ///
+    /// # Details
+    ///
+    /// The severity is read back with [`severity`]. If several severities are attached the
+    /// last one wins, so a caller can escalate or downgrade the errors it propagates. The
+    /// severity is only rendered, as a colored banner above the chain of errors, with
+    /// `HookBuilder::severity_banner`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, Severity};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the cache could not be warmed"))
+    ///     .severity(Severity::Warn)
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(color_anyhow::severity(&report), Some(Severity::Warn));
+    /// assert!(!format!("{:?}", report).contains("WARN"));
+    /// ```
+    ///
+    /// [`severity`]: ../fn.severity.html
+    fn severity(self, severity: crate::Severity) -> anyhow::Result<T>;
+
+    /// Recolor this error report with a `Theme`, instead of the theme installed with
+    /// `HookBuilder::theme`
+    ///
+    /// # Details
+    ///
+    /// The theme applies to the whole report, backtrace and `SpanTrace` included. Other
+    /// reports are still rendered with the installed theme, if there is one. If several
+    /// themes are attached the last one wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, Section, TextColor, Theme};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let render = |color| {
+    ///     std::thread::spawn(move || {
+    ///         let report = Err::<(), _>(anyhow!("the cache could not be warmed"))
+    ///             .theme(Theme::new().color(TextColor::BrightRed, color))
+    ///             .unwrap_err();
+    ///         format!("{:?}", report)
+    ///     })
+    /// };
+    /// let (orange, blue) = (render(TextColor::Rgb(255, 85, 0)), render(TextColor::Blue));
+    /// let (orange, blue) = (orange.join().unwrap(), blue.join().unwrap());
+    ///
+    /// assert!(orange.contains("\x1b[38;2;255;85;0mthe cache could not be warmed"));
+    /// assert!(blue.contains("\x1b[34mthe cache could not be warmed"));
+    /// assert_ne!(orange, blue);
+    ///
+    /// // reports without a theme keep the installed colors
+    /// let report = format!("{:?}", anyhow!("the cache could not be warmed"));
+    /// assert!(report.contains("\x1b[38;5;9mthe cache could not be warmed"));
+    /// ```
+    fn theme(self, theme: crate::Theme) -> anyhow::Result<T>;
+
+    /// Attach a localized version of the outermost error message, to be shown to end users
+    ///
+    /// # Details
+    ///
+    /// The report keeps rendering the original chain of errors, the localized message is only
+    /// rendered in place of the outermost message when rendering with
+    /// `ReportOptions::prefer_localized`, e.g. with `format_report` or `render_both_with`. If
+    /// several localized messages are attached the last one wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::ReportOptions, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the disk is full"))
+    ///     .localized("le disque est plein")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).contains("the disk is full"));
+    /// let user = color_anyhow::format_report(&report, &ReportOptions::new().prefer_localized(true));
+    /// assert!(user.contains("le disque est plein"));
+    /// ```
+    fn localized<D>(self, message: D) -> anyhow::Result<T>
+    where
+        D: Display;
+
+    /// Attach a location in a source file to an error report, e.g. where a parser error occurred
+    ///
+    /// # Details
+    ///
+    /// `line` and `column` start at 1, the column counting characters. Each location is
+    /// rendered as a `Location: file:line:column` line after the custom sections, and with
+    /// `RUST_LIB_BACKTRACE=full` the surrounding source lines follow, with a `^` under the
+    /// column. Tabs are expanded to the next multiple of 4 columns and wide characters count
+    /// as two columns, so the caret lines up with the rendered line. Files that can't be read
+    /// are rendered without a snippet. The source is read with the installed
+    /// `HookBuilder::source_reader`, if any.
+    ///
+    /// Locations are rendered in the order they were attached, the ones after the first 3 are
+    /// only counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    /// use std::io;
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .source_reader(Box::new(|path| match path.to_str() {
+    ///         Some("app.toml") => Ok("[app]\n\tname = \"日本\" x\nport = 80\n".to_string()),
+    ///         _ => Err(io::ErrorKind::NotFound.into()),
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("unexpected `x`"))
+    ///     .source_location("app.toml", 2, 14)
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// // the tab expands to 4 columns and each of the wide characters takes 2
+    /// let snippet = [
+    ///     "Location: app.toml:2:14",
+    ///     "       1 │ [app]",
+    ///     "       2 >     name = \"日本\" x",
+    ///     &format!("         │ {}^", " ".repeat(18)),
+    ///     "       3 │ port = 80",
+    /// ]
+    /// .join("\n");
+    /// assert!(report.contains(&snippet));
+    ///
+    /// // files that can't be read only render the location
+    /// let report = Err::<(), Error>(anyhow!("unexpected end of file"))
+    ///     .source_location("missing.toml", 3, 1)
+    ///     .source_location("app.toml", 1, 6)
+    ///     .unwrap_err();
+    /// let report = format!("{:?}", report);
+    ///
+    /// assert!(report.contains("Location: missing.toml:3:1\n\nLocation: app.toml:1:6\n"));
+    /// assert!(report.contains("       1 > [app]\n         │      ^\n"));
+    /// ```
+    fn source_location<P>(self, file: P, line: u32, column: u32) -> anyhow::Result<T>
+    where
+        P: Into<std::path::PathBuf>;
+
+    /// Attach the JSON text a `serde_json::Error` in the chain was parsed from, to render
+    /// the lines around the error with a caret under its column
+    ///
+    /// # Details
+    ///
+    /// The snippet is rendered after the other sections, as a `JSON:` line with the
+    /// position of the first `serde_json::Error` in the chain of errors. Errors without a
+    /// position, like io errors while reading the input, and reports without a
+    /// `serde_json::Error` are rendered as if no text was attached. If the text is attached
+    /// several times, the last one is used.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::Context, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let json = "{\n  \"name\": \"myapp\",\n  \"port\": 80,\n}\n";
+    /// let report = serde_json::from_str::<serde_json::Value>(json)
+    ///     .context("the config is malformed")
+    ///     .with_json_source(json)
+    ///     .unwrap_err();
+    ///
+    /// let snippet = [
+    ///     "JSON: line 4, column 1",
+    ///     "       2 │   \"name\": \"myapp\",",
+    ///     "       3 │   \"port\": 80,",
+    ///     "       4 > }",
+    ///     "         │ ^",
+    /// ]
+    /// .join("\n");
+    /// assert!(format!("{:?}", report).ends_with(&snippet));
+    ///
+    /// // the caret is under the character, however many bytes and columns the ones before take
+    /// let json = "{\"name\": \"日本\", \"port\": 8o}";
+    /// let report = serde_json::from_str::<serde_json::Value>(json)
+    ///     .with_json_source(json)
+    ///     .unwrap_err();
+    ///
+    /// let snippet = format!("JSON: line 1, column 25\n       1 > {}\n         │ {}^", json, " ".repeat(26));
+    /// assert!(format!("{:?}", report).ends_with(&snippet));
+    ///
+    /// // errors without a position render no snippet
+    /// let eof = serde_json::Error::io(std::io::ErrorKind::UnexpectedEof.into());
+    /// let report = Err::<(), _>(eof)
+    ///     .with_json_source(json)
+    ///     .unwrap_err();
+    /// assert!(!format!("{:?}", report).contains("JSON:"));
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    fn with_json_source<S>(self, json: S) -> anyhow::Result<T>
+    where
+        S: Into<String>;
+
+    /// Attach the category of the failure to an error report, e.g. `database` or `network`
+    ///
+    /// # Details
+    ///
+    /// The category isn't rendered itself, it selects the runbook registered for it with
+    /// `HookBuilder::register_runbook`. If several categories are attached the last one wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .register_runbook("network", "https://runbooks.example.com/network".to_string())
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("the upstream timed out"))
+    ///     .category("network")
+    ///     .unwrap_err();
+    ///
+    /// assert!(format!("{:?}", report).contains("Runbook: https://runbooks.example.com/network"));
+    /// ```
+    fn category<D>(self, category: D) -> anyhow::Result<T>
+    where
+        D: Display;
+
+    /// Add a note describing a recovery action that was already attempted, to be displayed after
+    /// the chain of errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Always)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("upstream unavailable"))
+    ///     .recovery("Retried 3 times before failing")
+    ///     .recovery("Switched to the fallback mirror")
+    ///     .suggestion("check the upstream status page")
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("\x1b[38;5;10mRecovery\x1b[0m: Retried 3 times before failing\n"));
+    /// assert!(report.contains("\n\x1b[38;5;10mRecovery\x1b[0m: Switched to the fallback mirror"));
+    /// assert!(report.contains("\x1b[38;5;14mSuggestion\x1b[0m"));
+    /// ```
+    fn recovery<D>(self, recovery: D) -> anyhow::Result<T>
+    where
+        D: Display + Send + Sync + 'static;
+
+    /// Wrap the error with a new top level message, carrying forward all of the notes,
+    /// suggestions and other sections already attached to it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("ENOENT: /etc/myapp/config.toml"))
+    ///     .suggestion("run `myapp init` to create a default config")
+    ///     .reword("could not load the configuration")
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("0: could not load the configuration"));
+    /// assert!(report.contains("Suggestion: run `myapp init` to create a default config"));
+    /// ```
+    fn reword<D>(self, message: D) -> anyhow::Result<T>
+    where
+        D: Display + Send + Sync + 'static;
+
+    /// Add an ordered list of remediation steps to an error report, to be displayed after all
+    /// suggestions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, anyhow::Error, section::Steps, Section};
+    ///
+    /// color_anyhow::install().unwrap();
+    ///
+    /// let report = Err::<(), Error>(anyhow!("config could not be loaded"))
+    ///     .remediation(
+    ///         Steps::new()
+    ///             .push("check the config exists")
+    ///             .push("run `myapp doctor`"),
+    ///     )
+    ///     .suggestion("try a different config")
+    ///     .unwrap_err();
+    ///
+    /// let report = format!("{:?}", report);
+    /// let suggestion = report.find("try a different config").unwrap();
+    /// let steps = report.find("1. check the config exists\n   2. run `myapp doctor`").unwrap();
+    /// assert!(suggestion < steps);
+    /// ```
+    fn remediation(self, steps: Steps) -> anyhow::Result<T>;
 }
@@ -0,0 +1,372 @@
+//! Render settings that can be loaded from a configuration file
+use crate::config::{BlockSet, ChainStyle, ColorChoice, HookBuilder, LineEnding, ReportOptions};
+use crate::section::help::closest_match;
+use crate::{Section, WarningsCollector};
+use anyhow::anyhow;
+use serde::de::IgnoredAny;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const COLORS: [&str; 4] = ["auto", "always", "always_ansi", "never"];
+const CHAIN_STYLES: [&str; 3] = ["numbered", "bulleted", "plain"];
+const LINE_ENDINGS: [&str; 2] = ["lf", "crlf"];
+const BLOCKS: [&str; 5] = ["chain", "sections", "span_trace", "backtrace", "help"];
+
+/// The names of the settings, for suggesting one in place of an unknown field
+const FIELDS: [&str; 15] = [
+    "color",
+    "width",
+    "max_path_width",
+    "max_error_sections",
+    "chain_style",
+    "line_ending",
+    "redact_user_paths",
+    "severity_banner",
+    "table_of_contents",
+    "reverse_layout",
+    "display_fingerprint",
+    "display_help_codes",
+    "display_env_hints",
+    "blocks",
+    "support_footer",
+];
+
+/// Settings for how reports are rendered, for loading from a configuration file with serde
+///
+/// # Details
+///
+/// Every field is optional, the settings that are left out keep the value of the
+/// `HookBuilder` they're applied to with [`HookBuilder::apply_settings`]. The names of the
+/// variants are written in snake case, e.g. `"always_ansi"`, and `blocks` lists the blocks
+/// rendered by [`report_options`] by the names `chain`, `sections`, `span_trace`, `backtrace`
+/// and `help`.
+///
+/// Fields the settings don't have don't fail deserialization, `apply_settings` records a
+/// warning for each of them with the global [`WarningsCollector`] instead, so a config file
+/// written for a newer version still loads.
+///
+/// The verbosity of backtraces isn't a setting, it's still read from `RUST_BACKTRACE` and
+/// `RUST_LIB_BACKTRACE`.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::HookBuilder, RenderSettings};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+///
+/// let settings: RenderSettings = serde_json::from_str(
+///     r#"{
+///         "color": "never",
+///         "chain_style": "bulleted",
+///         "line_ending": "crlf",
+///         "support_footer": "Need help? support@example.com"
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(settings.color.as_deref(), Some("never"));
+/// assert_eq!(settings.width, None);
+///
+/// HookBuilder::blank().apply_settings(settings).unwrap().install().unwrap();
+///
+/// let report = format!("{:?}", anyhow!("the config could not be loaded"));
+/// assert_eq!(
+///     report,
+///     "\r\n   - the config could not be loaded\r\n\r\nNeed help? support@example.com"
+/// );
+/// ```
+///
+/// Settings that are left out keep their defaults:
+///
+/// ```rust
+/// use color_anyhow::{config::HookBuilder, RenderSettings};
+///
+/// let settings: RenderSettings = serde_json::from_str("{}").unwrap();
+/// assert_eq!(settings, RenderSettings::default());
+///
+/// let builder = HookBuilder::new().apply_settings(settings).unwrap();
+/// assert_eq!(format!("{:?}", builder), format!("{:?}", HookBuilder::new()));
+/// ```
+///
+/// [`HookBuilder::apply_settings`]: config/struct.HookBuilder.html#method.apply_settings
+/// [`report_options`]: #method.report_options
+/// [`WarningsCollector`]: struct.WarningsCollector.html
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct RenderSettings {
+    /// Whether reports are rendered with colors: `auto`, `always`, `always_ansi` or `never`
+    pub color: Option<String>,
+    /// The width reports are rendered at, see `HookBuilder::width`
+    pub width: Option<usize>,
+    /// The width paths are shortened to, see `HookBuilder::max_path_width`
+    pub max_path_width: Option<usize>,
+    /// The number of error sections rendered, see `HookBuilder::max_error_sections`
+    pub max_error_sections: Option<usize>,
+    /// How the chain of errors is rendered: `numbered`, `bulleted`, with a `-` bullet, or
+    /// `plain`
+    pub chain_style: Option<String>,
+    /// The line endings of reports: `lf` or `crlf`
+    pub line_ending: Option<String>,
+    /// See `HookBuilder::redact_user_paths`
+    pub redact_user_paths: Option<bool>,
+    /// See `HookBuilder::severity_banner`
+    pub severity_banner: Option<bool>,
+    /// See `HookBuilder::table_of_contents`
+    pub table_of_contents: Option<bool>,
+    /// See `HookBuilder::reverse_layout`
+    pub reverse_layout: Option<bool>,
+    /// See `HookBuilder::display_fingerprint`
+    pub display_fingerprint: Option<bool>,
+    /// See `HookBuilder::display_help_codes`
+    pub display_help_codes: Option<bool>,
+    /// See `HookBuilder::display_env_hints`
+    pub display_env_hints: Option<bool>,
+    /// The blocks rendered by the `ReportOptions` returned by `report_options`
+    pub blocks: Option<Vec<String>>,
+    /// See `HookBuilder::support_footer`
+    pub support_footer: Option<String>,
+    /// The fields that aren't settings, warned about when the settings are applied
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+impl RenderSettings {
+    /// The `ReportOptions` rendering the blocks listed in `blocks`, or all of them if it's
+    /// left out
+    ///
+    /// # Details
+    ///
+    /// Names that aren't blocks are skipped, `HookBuilder::apply_settings` reports them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, RenderSettings};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// let settings: RenderSettings =
+    ///     serde_json::from_str(r#"{ "blocks": ["chain", "help"] }"#).unwrap();
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .apply_settings(settings.clone())
+    ///     .unwrap()
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let report = anyhow!("the cache is stale");
+    /// let rendered = color_anyhow::format_report(&report, &settings.report_options());
+    /// assert_eq!(rendered, "   0: the cache is stale");
+    /// ```
+    pub fn report_options(&self) -> ReportOptions {
+        let blocks = match &self.blocks {
+            Some(blocks) => blocks,
+            None => return ReportOptions::new(),
+        };
+
+        let include = blocks
+            .iter()
+            .filter_map(|name| block(name))
+            .fold(BlockSet::empty(), |set, block| set | block);
+        ReportOptions::new().include(include)
+    }
+
+    /// Returns a report with an error section for each setting with an invalid value
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        let mut invalid = vec![];
+        let mut check = |field, value: Option<&String>, expected: &'static [&'static str]| {
+            if let Some(value) = value.filter(|value| !expected.contains(&value.as_str())) {
+                invalid.push(InvalidSetting {
+                    field,
+                    value: value.clone(),
+                    expected,
+                });
+            }
+        };
+        check("color", self.color.as_ref(), &COLORS);
+        check("chain_style", self.chain_style.as_ref(), &CHAIN_STYLES);
+        check("line_ending", self.line_ending.as_ref(), &LINE_ENDINGS);
+        for name in self.blocks.iter().flatten() {
+            check("blocks", Some(name), &BLOCKS);
+        }
+
+        if invalid.is_empty() {
+            return Ok(());
+        }
+
+        let mut report = Err(anyhow!("invalid render settings"));
+        for invalid in invalid {
+            let (value, expected) = (invalid.value.clone(), invalid.expected);
+            report = report
+                .error(invalid)
+                .suggestion_did_you_mean(&value, expected);
+        }
+        report
+    }
+}
+
+impl HookBuilder {
+    /// Apply the settings loaded into `settings`, e.g. from a configuration file
+    ///
+    /// # Details
+    ///
+    /// The settings that are left out keep their current value. When any setting has an
+    /// invalid value, none are applied and the returned report has an error section for each
+    /// of them. Each field that isn't a setting is recorded as a warning with the global
+    /// [`WarningsCollector`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{config::{ColorChoice, HookBuilder}, RenderSettings, WarningsCollector};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let settings: RenderSettings = serde_json::from_str(
+    ///     r#"{ "color": "alway", "blocks": ["chain", "traces"], "colour": "never" }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let report = HookBuilder::blank().apply_settings(settings).unwrap_err();
+    /// let report = format!("{:?}", report);
+    /// assert!(report.contains("invalid render settings"));
+    /// assert!(report.contains(
+    ///     "`color` can't be \"alway\", expected `auto`, `always`, `always_ansi` or `never`"
+    /// ));
+    /// assert!(report.contains("Suggestion: Did you mean 'always'?"));
+    /// assert!(report.contains("`blocks` can't be \"traces\""));
+    ///
+    /// let mut warnings = vec![];
+    /// WarningsCollector::global().flush_to(&mut warnings).unwrap();
+    /// let warnings = String::from_utf8(warnings).unwrap();
+    /// assert!(warnings.contains("unknown render setting `colour`, did you mean `color`?"));
+    /// ```
+    ///
+    /// [`WarningsCollector`]: ../struct.WarningsCollector.html
+    pub fn apply_settings(self, settings: RenderSettings) -> Result<Self, anyhow::Error> {
+        for name in settings.unknown.keys() {
+            let warning = match closest_match(name, &FIELDS) {
+                Some(field) => format!(
+                    "unknown render setting `{}`, did you mean `{}`?",
+                    name, field
+                ),
+                None => format!("unknown render setting `{}`", name),
+            };
+            WarningsCollector::global().warn(warning);
+        }
+        settings.validate()?;
+
+        let mut builder = self;
+        if let Some(choice) = settings.color.as_deref().and_then(color_choice) {
+            builder = builder.color_choice(choice);
+        }
+        if let Some(width) = settings.width {
+            builder = builder.width(width);
+        }
+        if let Some(width) = settings.max_path_width {
+            builder = builder.max_path_width(width);
+        }
+        if let Some(max) = settings.max_error_sections {
+            builder = builder.max_error_sections(max);
+        }
+        if let Some(style) = settings.chain_style.as_deref().and_then(chain_style) {
+            builder = builder.chain_style(style);
+        }
+        if let Some(line_ending) = settings.line_ending.as_deref().and_then(line_ending) {
+            builder = builder.line_ending(line_ending);
+        }
+        if let Some(cond) = settings.redact_user_paths {
+            builder = builder.redact_user_paths(cond);
+        }
+        if let Some(cond) = settings.severity_banner {
+            builder = builder.severity_banner(cond);
+        }
+        if let Some(cond) = settings.table_of_contents {
+            builder = builder.table_of_contents(cond);
+        }
+        if let Some(cond) = settings.reverse_layout {
+            builder = builder.reverse_layout(cond);
+        }
+        if let Some(cond) = settings.display_fingerprint {
+            builder = builder.display_fingerprint(cond);
+        }
+        if let Some(cond) = settings.display_help_codes {
+            builder = builder.display_help_codes(cond);
+        }
+        if let Some(cond) = settings.display_env_hints {
+            builder = builder.display_env_hints(cond);
+        }
+        if let Some(footer) = settings.support_footer {
+            builder = builder.support_footer(footer);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// A setting whose value isn't one of the values it can have
+#[derive(Debug)]
+struct InvalidSetting {
+    field: &'static str,
+    value: String,
+    expected: &'static [&'static str],
+}
+
+impl fmt::Display for InvalidSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` can't be {:?}, expected ", self.field, self.value)?;
+        for (n, expected) in self.expected.iter().enumerate() {
+            let separator = match n {
+                0 => "",
+                n if n + 1 == self.expected.len() => " or ",
+                _ => ", ",
+            };
+            write!(f, "{}`{}`", separator, expected)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidSetting {}
+
+fn color_choice(name: &str) -> Option<ColorChoice> {
+    match name {
+        "auto" => Some(ColorChoice::Auto),
+        "always" => Some(ColorChoice::Always),
+        "always_ansi" => Some(ColorChoice::AlwaysAnsi),
+        "never" => Some(ColorChoice::Never),
+        _ => None,
+    }
+}
+
+fn chain_style(name: &str) -> Option<ChainStyle> {
+    match name {
+        "numbered" => Some(ChainStyle::Numbered),
+        "bulleted" => Some(ChainStyle::Bulleted('-')),
+        "plain" => Some(ChainStyle::Plain),
+        _ => None,
+    }
+}
+
+fn line_ending(name: &str) -> Option<LineEnding> {
+    match name {
+        "lf" => Some(LineEnding::Lf),
+        "crlf" => Some(LineEnding::CrLf),
+        _ => None,
+    }
+}
+
+fn block(name: &str) -> Option<BlockSet> {
+    match name {
+        "chain" => Some(BlockSet::CHAIN),
+        "sections" => Some(BlockSet::SECTIONS),
+        "span_trace" => Some(BlockSet::SPAN_TRACE),
+        "backtrace" => Some(BlockSet::BACKTRACE),
+        "help" => Some(BlockSet::HELP),
+        _ => None,
+    }
+}
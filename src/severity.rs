@@ -0,0 +1,88 @@
+//! How severe an error report is, and the banner rendered for it
+use crate::ColorExt;
+use ansi_term::{Color::*, Style};
+use std::fmt;
+
+/// How severe the failure described by an error report is, attached with
+/// [`Section::severity`]
+///
+/// [`Section::severity`]: trait.Section.html#tymethod.severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Severity {
+    /// The failure is unrecoverable and the program is about to exit
+    Fatal,
+    /// The operation failed
+    Error,
+    /// The operation failed, but the program can carry on
+    Warn,
+}
+
+impl Severity {
+    /// The style the banner for this severity is rendered in
+    fn style(self) -> Style {
+        match self {
+            Severity::Fatal => Red.make_intense().bold(),
+            Severity::Error => Style::from(Red.make_intense()),
+            Severity::Warn => Style::from(Yellow.make_intense()),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Fatal => "FATAL",
+            Severity::Error => "ERROR",
+            Severity::Warn => "WARN",
+        })
+    }
+}
+
+/// Returns the severity attached to an error report with `Section::severity`
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, Severity};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), Error>(anyhow!("the index is corrupted"))
+///     .severity(Severity::Warn)
+///     .severity(Severity::Fatal)
+///     .unwrap_err();
+///
+/// assert_eq!(color_anyhow::severity(&report), Some(Severity::Fatal));
+/// assert_eq!(color_anyhow::severity(&anyhow!("unclassified")), None);
+/// ```
+pub fn severity(error: &anyhow::Error) -> Option<Severity> {
+    error
+        .handler()
+        .downcast_ref::<crate::Handler>()
+        .and_then(|handler| handler.severity)
+}
+
+/// The banner rendered above a report of the given severity, filling `width`
+/// columns if the width is known
+pub(crate) fn banner(severity: Severity, width: Option<usize>) -> String {
+    const FILL: char = '▰';
+    const MIN_FILL: usize = 3;
+
+    let label = severity.to_string();
+    // the label is padded by a space on either side
+    let fill = width
+        .map(|width| width.saturating_sub(label.len() + 2))
+        .unwrap_or(0)
+        .max(MIN_FILL * 2);
+    let left = fill / 2;
+    let right = fill - left;
+
+    let banner = format!(
+        "{} {} {}",
+        FILL.to_string().repeat(left),
+        label,
+        FILL.to_string().repeat(right)
+    );
+    severity.style().paint(banner).to_string()
+}
@@ -0,0 +1,171 @@
+//! A snapshot of the effective configuration, for diagnosing how reports are rendered
+use crate::config::{installed_printer, is_installed, lib_verbosity, panic_verbosity};
+use crate::config::{ColorChoice, Verbosity};
+use std::env;
+use std::fmt;
+
+/// The environment variables color-anyhow consults whose values are shown in a
+/// `ConfigSnapshot`
+const ENV_VARS: [&str; 6] = [
+    "RUST_BACKTRACE",
+    "RUST_LIB_BACKTRACE",
+    "RUST_SPANTRACE",
+    "COLORBT_SHOW_HIDDEN",
+    "NO_COLOR",
+    "COLOR_ANYHOW_DEBUG",
+];
+
+/// The effective configuration of the installed hooks, for diagnosing why
+/// reports look the way they do
+///
+/// # Details
+///
+/// `ConfigSnapshot` is constructed with [`current_config`]. It is rendered one
+/// setting per line, and can be attached to a report as a section, e.g. with
+/// `.section(current_config().header("color-anyhow config:"))`. With the
+/// `serde` feature it can be serialized as well.
+///
+/// Only the environment variables color-anyhow reads to decide how reports are
+/// rendered are recorded. `HOME`, `USERPROFILE`, `USER` and `USERNAME`, which
+/// are read to redact user paths, are never recorded.
+///
+/// [`current_config`]: fn.current_config.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct ConfigSnapshot {
+    /// Whether the hooks have been installed, the defaults are shown otherwise
+    pub installed: bool,
+    /// The environment variables color-anyhow consults and their values
+    pub env: Vec<(String, Option<String>)>,
+    /// Which backtraces error reports render: `off`, `short` or `full`
+    pub error_backtraces: &'static str,
+    /// Which backtraces panic reports render: `off`, `short` or `full`
+    pub panic_backtraces: &'static str,
+    /// Whether a `SpanTrace` is captured for new error reports
+    pub span_trace_capture: bool,
+    /// The configured `ColorChoice`
+    pub color_choice: ColorChoice,
+    /// Whether error reports are rendered with colors, as decided for stderr
+    pub error_colors: bool,
+    /// Whether panic reports are rendered with colors, as decided for stdout
+    pub panic_colors: bool,
+    /// The number of installed frame filters
+    pub frame_filters: usize,
+    /// Whether `COLORBT_SHOW_HIDDEN` disables the frame filters
+    pub hidden_frames_shown: bool,
+    /// Whether the current user's home directory and username are redacted
+    pub user_paths_redacted: bool,
+}
+
+/// Take a snapshot of the effective configuration of the installed hooks and
+/// the environment variables they consult
+///
+/// # Details
+///
+/// Setting `COLOR_ANYHOW_DEBUG=1` appends this snapshot to every error report,
+/// under a `color-anyhow config:` header.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, anyhow::Error, Section, SectionExt};
+/// use color_anyhow::config::{ColorChoice, HookBuilder};
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+/// std::env::remove_var("RUST_SPANTRACE");
+/// HookBuilder::blank()
+///     .color_choice(ColorChoice::Never)
+///     .add_frame_filter(Box::new(|frames| frames.retain(|frame| frame.n > 2)))
+///     .install()
+///     .unwrap();
+///
+/// let config = color_anyhow::current_config();
+/// assert!(config.installed);
+/// assert_eq!(config.color_choice, ColorChoice::Never);
+/// assert!(!config.error_colors);
+/// assert_eq!(config.frame_filters, 1);
+/// assert_eq!(config.error_backtraces, "full");
+/// assert!(config.env.contains(&("RUST_LIB_BACKTRACE".to_owned(), Some("full".to_owned()))));
+/// assert!(config.env.contains(&("RUST_SPANTRACE".to_owned(), None)));
+/// assert!(!config.env.iter().any(|(name, _)| name == "HOME"));
+///
+/// let report = Err::<(), Error>(anyhow!("the report has no backtrace"))
+///     .section(config.header("color-anyhow config:"))
+///     .unwrap_err();
+/// let report = format!("{:?}", report);
+/// assert!(report.contains("color-anyhow config:\n   installed: yes"));
+/// assert!(report.contains("RUST_LIB_BACKTRACE=\"full\""));
+/// assert!(report.contains("error report backtraces: full"));
+///
+/// // every report gets the snapshot with COLOR_ANYHOW_DEBUG
+/// std::env::set_var("COLOR_ANYHOW_DEBUG", "1");
+/// let report = format!("{:?}", anyhow!("the report has no backtrace"));
+/// assert!(report.contains("color-anyhow config:\n   installed: yes"));
+/// ```
+pub fn current_config() -> ConfigSnapshot {
+    let printer = installed_printer();
+    let verbosity = |verbosity| match verbosity {
+        Verbosity::Minimal => "off",
+        Verbosity::Medium => "short",
+        Verbosity::Full => "full",
+    };
+
+    ConfigSnapshot {
+        installed: is_installed(),
+        env: ENV_VARS
+            .iter()
+            .map(|name| (name.to_string(), env::var(name).ok()))
+            .collect(),
+        error_backtraces: verbosity(lib_verbosity()),
+        panic_backtraces: verbosity(panic_verbosity()),
+        span_trace_capture: cfg!(feature = "capture-spantrace")
+            && printer.spantrace_capture_enabled(),
+        color_choice: printer.color_choice(),
+        error_colors: printer.colors_enabled(atty::Stream::Stderr),
+        panic_colors: printer.colors_enabled(atty::Stream::Stdout),
+        frame_filters: printer.frame_filters(),
+        hidden_frames_shown: crate::config::shows_hidden_frames(),
+        user_paths_redacted: printer.user_paths().is_some(),
+    }
+}
+
+/// Whether `COLOR_ANYHOW_DEBUG` asks for the snapshot to be appended to every report
+pub(crate) fn debug_enabled() -> bool {
+    matches!(env::var("COLOR_ANYHOW_DEBUG").as_deref(), Ok("1"))
+}
+
+impl fmt::Display for ConfigSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |cond| if cond { "yes" } else { "no" };
+
+        writeln!(f, "installed: {}", yes_no(self.installed))?;
+        for (name, value) in &self.env {
+            match value {
+                Some(value) => writeln!(f, "{}={:?}", name, value)?,
+                None => writeln!(f, "{} is not set", name)?,
+            }
+        }
+        writeln!(f, "error report backtraces: {}", self.error_backtraces)?;
+        writeln!(f, "panic report backtraces: {}", self.panic_backtraces)?;
+        writeln!(f, "span trace capture: {}", yes_no(self.span_trace_capture))?;
+        writeln!(
+            f,
+            "colors: {:?} (error reports: {}, panic reports: {})",
+            self.color_choice,
+            yes_no(self.error_colors),
+            yes_no(self.panic_colors)
+        )?;
+        writeln!(
+            f,
+            "frame filters: {} (disabled by COLORBT_SHOW_HIDDEN: {})",
+            self.frame_filters,
+            yes_no(self.hidden_frames_shown)
+        )?;
+        write!(
+            f,
+            "user paths redacted: {}",
+            yes_no(self.user_paths_redacted)
+        )
+    }
+}
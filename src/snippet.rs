@@ -0,0 +1,145 @@
+//! Source snippets for explicit source locations attached to error reports
+use crate::config::installed_printer;
+use crate::ColorExt;
+use ansi_term::Color::*;
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+/// The number of columns between tab stops when rendering source lines
+const TAB_WIDTH: usize = 4;
+
+/// A location in a source file attached with `Section::source_location`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceLocation {
+    pub(crate) file: PathBuf,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+}
+
+impl SourceLocation {
+    /// Write the location, followed by the surrounding source lines and a caret under the
+    /// column if `snippet` is set and the file can be read
+    pub(crate) fn write<W: Write>(&self, f: &mut W, snippet: bool) -> fmt::Result {
+        let location = format!("{}:{}:{}", self.file.display(), self.line, self.column);
+        write!(
+            f,
+            "{}: {}",
+            Cyan.make_intense().paint("Location"),
+            Purple.paint(location)
+        )?;
+
+        if !snippet {
+            return Ok(());
+        }
+
+        // unreadable files are rendered without a snippet
+        match installed_printer().read_source(&self.file) {
+            Ok(source) => write_snippet(f, &source, self.line as usize, self.column as usize),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Write the lines of `source` around `line`, with a caret under the character at `column`
+///
+/// Both are counted from 1, lines past the end of `source` are rendered without a snippet.
+pub(crate) fn write_snippet<W: Write>(
+    f: &mut W,
+    source: &str,
+    line: usize,
+    column: usize,
+) -> fmt::Result {
+    let lines: Vec<_> = source.lines().collect();
+    if line == 0 || line > lines.len() {
+        return Ok(());
+    }
+
+    for n in line.saturating_sub(2).max(1)..=(line + 2).min(lines.len()) {
+        let text = expand_tabs(lines[n - 1]);
+        if n != line {
+            write!(f, "\n{:>8} │ {}", n, text)?;
+            continue;
+        }
+
+        let target = format!("{:>8} > {}", n, text);
+        write!(f, "\n{}", White.bold().paint(target))?;
+
+        // tabs only expand to the next tab stop, so the prefix is expanded on its own
+        let prefix: String = lines[n - 1]
+            .chars()
+            .take(column.saturating_sub(1))
+            .collect();
+        let offset = expand_tabs(&prefix).width();
+        write!(
+            f,
+            "\n{:>8} │ {:offset$}{}",
+            "",
+            "",
+            Red.make_intense().paint("^"),
+            offset = offset
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write the lines of `json` around the position of the first `serde_json::Error` in the chain
+/// of `error`, if it has one
+#[cfg(feature = "json")]
+pub(crate) fn write_json_snippet<W: Write>(
+    f: &mut W,
+    error: &(dyn std::error::Error + 'static),
+    json: &str,
+) -> fmt::Result {
+    let position = anyhow::Chain::new(error)
+        .filter_map(|error| error.downcast_ref::<serde_json::Error>())
+        .map(|error| (error.line(), error.column()))
+        .next();
+    // errors that aren't about the input, e.g. io errors, have no position
+    let (line, column) = match position {
+        Some((line, column)) if line > 0 => (line, column),
+        _ => return Ok(()),
+    };
+
+    // serde_json counts columns in bytes, snippets count them in characters
+    let column = match json.lines().nth(line - 1) {
+        Some(text) => {
+            let mut end = column.saturating_sub(1).min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text[..end].chars().count() + 1
+        }
+        None => column,
+    };
+
+    let position = format!("line {}, column {}", line, column);
+    write!(
+        f,
+        "{}: {}",
+        Cyan.make_intense().paint("JSON"),
+        Purple.paint(position)
+    )?;
+    write_snippet(f, json, line, column)
+}
+
+/// `line` with its tabs replaced by spaces up to the next tab stop
+fn expand_tabs(line: &str) -> Cow<'_, str> {
+    if !line.contains('\t') {
+        return line.into();
+    }
+
+    let mut expanded = String::with_capacity(line.len() + TAB_WIDTH);
+    for c in line.chars() {
+        if c == '\t' {
+            let width = expanded.width();
+            expanded.extend(std::iter::repeat(' ').take(TAB_WIDTH - width % TAB_WIDTH));
+        } else {
+            expanded.push(c);
+        }
+    }
+
+    expanded.into()
+}
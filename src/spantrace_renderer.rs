@@ -0,0 +1,268 @@
+//! Rendering of the frames of a `SpanTrace`
+//!
+//! With the default settings the output is byte for byte the output of
+//! `color_spantrace::colorize` with its default dark theme, which rendered the `SPANTRACE`
+//! section before, so installing a theme or changing the settings of the section only changes
+//! what the settings cover.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}};
+//! use tracing_error::{ErrorLayer, SpanTrace};
+//! use tracing_subscriber::prelude::*;
+//!
+//! /// The `SPANTRACE` section as `color_spantrace` renders it, indented like the section of
+//! /// a report
+//! fn expected(span_trace: &SpanTrace) -> String {
+//!     let rendered = color_spantrace::colorize(span_trace).to_string();
+//!     let lines: Vec<_> = rendered
+//!         .lines()
+//!         .map(|line| if line.is_empty() { String::new() } else { format!("  {}", line) })
+//!         .collect();
+//!     lines.join("\n")
+//! }
+//!
+//! std::env::set_var("RUST_LIB_BACKTRACE", "0");
+//! tracing_subscriber::registry().with(ErrorLayer::default()).init();
+//! HookBuilder::default()
+//!     .color_choice(ColorChoice::Always)
+//!     .install()
+//!     .unwrap();
+//!
+//! // outside of any span, the trace is empty and no section is rendered
+//! assert!(!format!("{:?}", anyhow!("the config was not found")).contains("SPANTRACE"));
+//!
+//! // one span without fields
+//! let span = tracing::info_span!("load_config");
+//! let _entered = span.enter();
+//! let report = format!("{:?}", anyhow!("the config was not found"));
+//! assert!(report.contains(&expected(&SpanTrace::capture())));
+//!
+//! // nested spans with fields
+//! let span = tracing::info_span!("parse", path = "config.toml", line = 12);
+//! let _entered = span.enter();
+//! let report = format!("{:?}", anyhow!("the config is malformed"));
+//! assert!(report.contains(&expected(&SpanTrace::capture())));
+//! assert!(report.contains("with \x1b[96mpath=\"config.toml\" line=12\x1b[0m"));
+//!
+//! // full verbosity adds a snippet of the source of each span
+//! std::env::set_var("RUST_LIB_BACKTRACE", "full");
+//! let report = format!("{:?}", anyhow!("the config is malformed"));
+//! let expected = expected(&SpanTrace::capture());
+//! assert!(expected.contains(" > "));
+//! assert!(report.contains(&expected));
+//! ```
+use crate::config::{lib_verbosity, source_snippet, SourceFiles, SpanTraceSelfField, Verbosity};
+use crate::styled::TextColor;
+use crate::timestamps::Timestamp;
+use crate::writers::{ellipsize_path, truncate_visible};
+use ansi_term::Style;
+use indenter::indented;
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+use std::time::SystemTime;
+use tracing_error::SpanTrace;
+
+/// The color of the targets and names of spans
+const TARGET: TextColor = TextColor::BrightRed;
+/// The color of the fields of spans
+const FIELDS: TextColor = TextColor::BrightCyan;
+/// The color of the source locations of spans
+const LOCATION: TextColor = TextColor::Purple;
+
+/// Renders the frames of a `SpanTrace`, with the time each span was entered added below its
+/// source location, and the `self` field, source paths and runs of frames from the same
+/// target rendered as configured
+pub(crate) struct SpanTraceRenderer<'a> {
+    pub(crate) span_trace: &'a SpanTrace,
+    /// Entry timestamps for each frame of `span_trace`, innermost first
+    pub(crate) timestamps: &'a [Option<SystemTime>],
+    pub(crate) self_field: &'a SpanTraceSelfField,
+    pub(crate) max_path_width: Option<usize>,
+    pub(crate) collapse_same_target: bool,
+}
+
+impl fmt::Display for SpanTraceRenderer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut spans = vec![];
+        self.span_trace.with_spans(|metadata, fields| {
+            spans.push((metadata, fields.to_owned()));
+            true
+        });
+
+        let same_target = |a: usize, b: usize| {
+            self.collapse_same_target
+                && matches!(
+                    (spans.get(a), spans.get(b)),
+                    (Some((a, _)), Some((b, _))) if a.target() == b.target()
+                )
+        };
+
+        // spans entered in a loop point into the same files, so each is read once
+        let files = SourceFiles::default();
+
+        writeln!(f, "{:━^80}\n", " SPANTRACE ")?;
+        for (span, (metadata, fields)) in spans.iter().enumerate() {
+            if span > 0 {
+                writeln!(f)?;
+            }
+
+            let continues_group = span > 0 && same_target(span - 1, span);
+            if !continues_group && !same_target(span, span + 1) {
+                self.write_span(f, &files, span, metadata, fields, true)?;
+                continue;
+            }
+
+            if !continues_group {
+                writeln!(f, "{}", Paint(TARGET, metadata.target()))?;
+            }
+            let mut f = indented(f).with_str("   ");
+            self.write_span(&mut f, &files, span, metadata, fields, false)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SpanTraceRenderer<'_> {
+    /// Write a single frame, with its target unless it is shown in a group header
+    fn write_span(
+        &self,
+        f: &mut dyn fmt::Write,
+        files: &SourceFiles,
+        span: usize,
+        metadata: &tracing_core::Metadata<'_>,
+        fields: &str,
+        show_target: bool,
+    ) -> fmt::Result {
+        write!(f, "{:>2}: ", span)?;
+        if show_target {
+            write!(
+                f,
+                "{}{}",
+                Paint(TARGET, metadata.target()),
+                Paint(TARGET, "::")
+            )?;
+        }
+        write!(f, "{}", Paint(TARGET, metadata.name()))?;
+
+        let fields = style_self_field(fields, self.self_field);
+        if !fields.is_empty() {
+            write!(f, " with {}", Paint(FIELDS, &fields))?;
+        }
+
+        match (metadata.file(), metadata.line()) {
+            (Some(file), lineno) => {
+                let file = match self.max_path_width {
+                    Some(max) => ellipsize_path(file, max),
+                    None => file.into(),
+                };
+                let lineno = lineno.map_or("<unknown line>".to_owned(), |x| x.to_string());
+                write!(
+                    f,
+                    "\n    at {}:{}",
+                    Paint(LOCATION, &file),
+                    Paint(LOCATION, &lineno)
+                )?;
+            }
+            (None, _) => write!(f, "\n    at <unknown source file>")?,
+        }
+
+        if let Some(Some(timestamp)) = self.timestamps.get(span) {
+            let entered = format!("entered at {}", Timestamp(*timestamp));
+            write!(f, "\n    {}", Style::new().dimmed().paint(entered))?;
+        }
+
+        if lib_verbosity() == Verbosity::Full {
+            if let (Some(file), Some(lineno)) = (metadata.file(), metadata.line()) {
+                write_source_snippet(f, files, file, lineno)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Text painted in a color, with the escape sequences `color_spantrace` emits
+struct Paint<'a>(TextColor, &'a str);
+
+impl fmt::Display for Paint<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{}m{}\x1b[0m", self.0.sgr_params(false), self.1)
+    }
+}
+
+/// Apply `self_field` to the `self=...` field of the formatted `fields` of a span
+fn style_self_field<'a>(fields: &'a str, self_field: &SpanTraceSelfField) -> Cow<'a, str> {
+    if *self_field == SpanTraceSelfField::Show {
+        return fields.into();
+    }
+
+    let start = match fields.find("self=") {
+        Some(start) if start == 0 || fields[..start].ends_with(' ') => start,
+        _ => return fields.into(),
+    };
+
+    // the value ends where the next `name=` field starts
+    let value_start = start + "self=".len();
+    let value_end = fields[value_start..]
+        .match_indices(' ')
+        .map(|(i, _)| value_start + i)
+        .find(|&i| {
+            let next = &fields[i + 1..];
+            let name_len = next
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(next.len());
+            name_len > 0 && next[name_len..].starts_with('=')
+        })
+        .unwrap_or(fields.len());
+    let (before, value, after) = (
+        &fields[..start],
+        &fields[value_start..value_end],
+        &fields[value_end..],
+    );
+
+    match self_field {
+        SpanTraceSelfField::Show => fields.into(),
+        SpanTraceSelfField::Hide => format!("{}{}", before, after.trim_start_matches(' '))
+            .trim_end()
+            .to_owned()
+            .into(),
+        SpanTraceSelfField::Rename(name) => format!("{}{}={}{}", before, name, value, after).into(),
+        SpanTraceSelfField::Truncate(max) => {
+            format!("{}self={}{}", before, truncate_visible(value, *max), after).into()
+        }
+    }
+}
+
+/// Write the lines around `lineno` of `file`, highlighting it
+fn write_source_snippet(
+    f: &mut dyn fmt::Write,
+    files: &SourceFiles,
+    file: &str,
+    lineno: u32,
+) -> fmt::Result {
+    let path = Path::new(file);
+    let file = files.get(path);
+    let source = match file.source(path) {
+        Some(source) => source,
+        None => return Ok(()),
+    };
+
+    for (line, is_lineno) in source_snippet(source, lineno) {
+        if is_lineno {
+            write!(
+                f,
+                "\n\x1b[{}m\x1b[1m{}\x1b[0m",
+                TextColor::White.sgr_params(false),
+                line
+            )?;
+        } else {
+            write!(f, "\n{}", line)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+//! Process wide counters of rendered error reports and the summaries of single renders
+use crate::config::BlockSet;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+static REPORTS_RENDERED: AtomicU64 = AtomicU64::new(0);
+static BACKTRACES_DISPLAYED: AtomicU64 = AtomicU64::new(0);
+static TRUNCATIONS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// What the report being rendered on this thread displayed and left out so far
+    static CURRENT: Cell<Rendered> = const { Cell::new(Rendered::NONE) };
+}
+
+/// What a report displayed and left out while it was rendered
+#[derive(Clone, Copy)]
+pub(crate) struct Rendered {
+    pub(crate) backtrace: bool,
+    pub(crate) span_trace: bool,
+    pub(crate) truncated: bool,
+}
+
+impl Rendered {
+    const NONE: Self = Self {
+        backtrace: false,
+        span_trace: false,
+        truncated: false,
+    };
+}
+
+/// The blocks of a report in the order they are rendered in, the order of
+/// the byte counts of [`BlockBytes`]
+const BLOCKS: [BlockSet; 5] = [
+    BlockSet::CHAIN,
+    BlockSet::SECTIONS,
+    BlockSet::SPAN_TRACE,
+    BlockSet::BACKTRACE,
+    BlockSet::HELP,
+];
+
+/// The bytes written for each block of a report while it is rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct BlockBytes {
+    bytes: [usize; 5],
+    recorded: usize,
+}
+
+impl BlockBytes {
+    /// Count the bytes written since the previous call, `written` in total, as part of `block`
+    pub(crate) fn record(&mut self, block: BlockSet, written: usize) {
+        self.add(block, written.saturating_sub(self.recorded));
+        self.recorded = written;
+    }
+
+    /// Count `bytes` more bytes as part of `block`
+    pub(crate) fn add(&mut self, block: BlockSet, bytes: usize) {
+        if let Some(n) = BLOCKS.iter().position(|other| *other == block) {
+            self.bytes[n] += bytes;
+        }
+    }
+}
+
+/// A summary of a single rendered error report, passed to the observer installed with
+/// [`HookBuilder::on_report`]
+///
+/// # Details
+///
+/// A trace can be captured without being displayed, the verbosity or options like
+/// `HookBuilder::skip_backtrace_in_reports` can hide it, and it can be displayed
+/// without being captured by the report itself, like the backtrace provided by the error
+/// or the `SpanTrace` of one of its causes.
+///
+/// [`HookBuilder::on_report`]: config/struct.HookBuilder.html#method.on_report
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReportSummary {
+    /// Whether the report captured a backtrace
+    pub backtrace_captured: bool,
+    /// Whether the rendered report displayed a backtrace
+    pub backtrace_displayed: bool,
+    /// Whether the report captured a `SpanTrace` or one of its causes carries one
+    pub span_trace_captured: bool,
+    /// Whether the rendered report displayed a `SpanTrace`
+    pub span_trace_displayed: bool,
+    /// Whether the rendered report left something out because of a limit, see
+    /// [`RenderStats::truncations`]
+    ///
+    /// [`RenderStats::truncations`]: struct.RenderStats.html#structfield.truncations
+    pub truncated: bool,
+    /// The number of bytes of the rendered report, as it is rendered before the escape codes
+    /// are stripped when colors are disabled and before the user paths are redacted, the line
+    /// endings converted and the theme applied
+    pub bytes: usize,
+    /// The time it took to render the report, measured with the installed clock
+    pub render_time: Duration,
+    block_bytes: BlockBytes,
+}
+
+impl ReportSummary {
+    pub(crate) fn new(
+        captured: (bool, bool),
+        rendered: Rendered,
+        block_bytes: BlockBytes,
+        bytes: usize,
+        render_time: Duration,
+    ) -> Self {
+        Self {
+            backtrace_captured: captured.0,
+            backtrace_displayed: rendered.backtrace,
+            span_trace_captured: captured.1,
+            span_trace_displayed: rendered.span_trace,
+            truncated: rendered.truncated,
+            bytes,
+            render_time,
+            block_bytes,
+        }
+    }
+
+    /// Returns the number of bytes of the rendered report taken by `blocks`
+    ///
+    /// The line breaks separating a block from the previous one count as part of the block.
+    /// The table of contents and the support footer aren't part of any block.
+    pub fn block_bytes(&self, blocks: BlockSet) -> usize {
+        BLOCKS
+            .iter()
+            .zip(&self.block_bytes.bytes)
+            .filter(|(block, _)| blocks.contains(**block))
+            .map(|(_, bytes)| bytes)
+            .sum()
+    }
+}
+
+/// Counters of the error reports rendered by this process, returned by [`render_stats`]
+///
+/// [`render_stats`]: fn.render_stats.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct RenderStats {
+    /// The number of error reports rendered
+    pub reports_rendered: u64,
+    /// The number of rendered error reports that displayed a backtrace
+    pub backtraces_displayed: u64,
+    /// The number of rendered error reports that left something out because of a limit,
+    /// either frames or source snippets skipped once `HookBuilder::render_time_budget` ran
+    /// out, or error sections beyond `HookBuilder::max_error_sections`
+    pub truncations: u64,
+}
+
+/// Returns the counters of the error reports rendered by this process
+///
+/// # Details
+///
+/// The counters are kept with atomics and updated on every complete render of an error
+/// report, whether it is rendered with `Debug`, `format_report` or any other function of
+/// this crate, so they are cheap to keep and always available. Each counter only ever
+/// increases, take the difference of two snapshots to measure a period of time. Reports
+/// rendered with `{:#?}`, panic reports and the partial renders of `ReportOptions::include`
+/// aren't counted.
+///
+/// Capturing a backtrace doesn't mean it is displayed, it can be hidden by the verbosity or
+/// by options like `HookBuilder::skip_backtrace_in_reports`, so `backtraces_displayed`
+/// relative to `reports_rendered` tells how many reports actually showed one.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::anyhow::anyhow;
+///
+/// color_anyhow::install().unwrap();
+/// let before = color_anyhow::render_stats();
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+/// let _ = format!("{:?}", anyhow!("the first upload failed"));
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// let _ = format!("{:?}", anyhow!("the second upload failed"));
+///
+/// let after = color_anyhow::render_stats();
+/// assert_eq!(after.reports_rendered - before.reports_rendered, 2);
+/// assert_eq!(after.backtraces_displayed - before.backtraces_displayed, 1);
+/// assert_eq!(after.truncations, before.truncations);
+/// ```
+///
+/// Renders cut short by the render time budget count as truncations:
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::HookBuilder};
+/// use std::time::Duration;
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "full");
+/// HookBuilder::default()
+///     .render_time_budget(Duration::from_millis(20))
+///     .source_reader(Box::new(|_| {
+///         std::thread::sleep(Duration::from_millis(50));
+///         Ok("fn main() {}\n".repeat(1000))
+///     }))
+///     .install()
+///     .unwrap();
+///
+/// let report = format!("{:?}", anyhow!("the upload failed"));
+/// assert!(report.contains("(skipped: render budget exceeded)"));
+///
+/// let stats = color_anyhow::render_stats();
+/// assert_eq!(stats.reports_rendered, 1);
+/// assert_eq!(stats.backtraces_displayed, 1);
+/// assert_eq!(stats.truncations, 1);
+/// ```
+pub fn render_stats() -> RenderStats {
+    RenderStats {
+        reports_rendered: REPORTS_RENDERED.load(Ordering::Relaxed),
+        backtraces_displayed: BACKTRACES_DISPLAYED.load(Ordering::Relaxed),
+        truncations: TRUNCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// Start counting a complete render of a report, returning the state of the render it is
+/// nested in, if any, for [`finish_report`]
+pub(crate) fn start_report() -> Rendered {
+    CURRENT.with(|current| current.replace(Rendered::NONE))
+}
+
+/// Count a complete render of a report started with [`start_report`], returning what it
+/// displayed and left out
+pub(crate) fn finish_report(outer: Rendered) -> Rendered {
+    let rendered = CURRENT.with(|current| current.replace(outer));
+
+    REPORTS_RENDERED.fetch_add(1, Ordering::Relaxed);
+    if rendered.backtrace {
+        BACKTRACES_DISPLAYED.fetch_add(1, Ordering::Relaxed);
+    }
+    if rendered.truncated {
+        TRUNCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    rendered
+}
+
+/// Record that the report being rendered on this thread displayed a backtrace
+pub(crate) fn backtrace_displayed() {
+    CURRENT.with(|current| {
+        current.set(Rendered {
+            backtrace: true,
+            ..current.get()
+        })
+    });
+}
+
+/// Record that the report being rendered on this thread displayed a `SpanTrace`
+#[cfg(feature = "capture-spantrace")]
+pub(crate) fn span_trace_displayed() {
+    CURRENT.with(|current| {
+        current.set(Rendered {
+            span_trace: true,
+            ..current.get()
+        })
+    });
+}
+
+/// Record that the report being rendered on this thread left something out
+pub(crate) fn truncated() {
+    CURRENT.with(|current| {
+        current.set(Rendered {
+            truncated: true,
+            ..current.get()
+        })
+    });
+}
@@ -0,0 +1,412 @@
+//! Streaming the sections of an error report to a sink as they are attached
+use crate::config::{installed_printer, BlockSet, ReportOptions};
+use crate::format_report;
+use crate::handler::finish_report;
+use crate::Handler;
+use ansi_term::Style;
+use std::cell::Cell;
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+
+thread_local! {
+    /// Whether the report being created is a stand-in for the report of a stream, which is
+    /// created without traces and sections
+    static STAND_IN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Write the chain of errors of `report` to `sink` right away, and stream the sections
+/// attached to it afterwards with [`ReportStream::attach`]
+///
+/// # Details
+///
+/// Sections are written as soon as they're attached, and [`ReportStream::finish`] writes the
+/// traces and the help text, so the sink ends up with the same text as rendering the finished
+/// report in one go with [`format_report`]. Notes, warnings and suggestions are part of the
+/// help text, so they are only written when the stream is finished.
+///
+/// A section can't always be written right away: an error section attached after a custom
+/// section, or an error grouped with one that was already written, changes text that was
+/// written before it. From then on the sections are held back, and `finish` writes the full
+/// report after a note that the sections were attached out of order. Reports rendered with a table
+/// of contents or with the reversed layout are only written by `finish`.
+///
+/// The stream can be shared between threads, e.g. with an `Arc`. When a write to the sink
+/// fails, nothing more is written to it, and every later call of `attach` and `finish`
+/// returns an error of the same kind. Sections are still attached to the report, which
+/// [`ReportStream::into_report`] returns.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder, ReportOptions}, SectionExt, Section};
+/// use std::io::{self, Write};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// /// A sink whose contents can be inspected while the report is streamed to it
+/// #[derive(Clone, Default)]
+/// struct Sink(Arc<Mutex<Vec<u8>>>);
+///
+/// impl Sink {
+///     fn contents(&self) -> String {
+///         String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+///     }
+/// }
+///
+/// impl Write for Sink {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         self.0.lock().unwrap().write(buf)
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+/// HookBuilder::blank()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let sink = Sink::default();
+/// let stream = color_anyhow::stream_report(anyhow!("the host is unreachable"), sink.clone());
+/// let stream = Arc::new(stream);
+/// assert_eq!(sink.contents(), "\n   0: the host is unreachable");
+///
+/// // the probes finish one after another on other threads
+/// let probes: Vec<_> = ["dns", "ping", "tcp"]
+///     .iter()
+///     .enumerate()
+///     .map(|(n, probe)| {
+///         let stream = stream.clone();
+///         std::thread::spawn(move || {
+///             std::thread::sleep(Duration::from_millis(200 * (n as u64 + 1)));
+///             let result = format!("{} failed", probe).header("Probe:");
+///             stream.attach(|report| report.section(result)).unwrap();
+///         })
+///     })
+///     .collect();
+///
+/// // each section shows up on its own, before the next probe finishes
+/// let mut seen = vec![sink.contents()];
+/// while seen.len() < 4 {
+///     let contents = sink.contents();
+///     if seen.last() != Some(&contents) {
+///         seen.push(contents);
+///     }
+///     std::thread::sleep(Duration::from_millis(10));
+/// }
+/// assert!(seen[1].ends_with("\n\nProbe:\n   dns failed"));
+/// assert!(seen[2].ends_with("dns failed\n\nProbe:\n   ping failed"));
+/// assert!(seen[3].ends_with("ping failed\n\nProbe:\n   tcp failed"));
+///
+/// for probe in probes {
+///     probe.join().unwrap();
+/// }
+/// stream.attach(|report| report.suggestion("check the network cable")).unwrap();
+/// stream.finish().unwrap();
+///
+/// let report = Arc::try_unwrap(stream).ok().unwrap().into_report();
+/// assert_eq!(sink.contents(), color_anyhow::format_report(&report, &ReportOptions::new()));
+/// assert!(sink.contents().ends_with("Suggestion: check the network cable"));
+/// ```
+///
+/// [`ReportStream::attach`]: struct.ReportStream.html#method.attach
+/// [`ReportStream::finish`]: struct.ReportStream.html#method.finish
+/// [`ReportStream::into_report`]: struct.ReportStream.html#method.into_report
+/// [`format_report`]: fn.format_report.html
+pub fn stream_report<W: io::Write>(report: anyhow::Error, sink: W) -> ReportStream<W> {
+    let stream = ReportStream {
+        state: Mutex::new(StreamState {
+            report,
+            sink,
+            written: String::new(),
+            held_back: false,
+            finished: false,
+            failed: None,
+        }),
+    };
+    // a failed write is returned by the next call on the stream
+    let _ = stream.lock().write_sections();
+    stream
+}
+
+/// An error report whose sections are written to a sink as they're attached, constructed
+/// with [`stream_report`]
+///
+/// [`stream_report`]: fn.stream_report.html
+#[derive(Debug)]
+pub struct ReportStream<W> {
+    state: Mutex<StreamState<W>>,
+}
+
+#[derive(Debug)]
+struct StreamState<W> {
+    report: anyhow::Error,
+    sink: W,
+    /// Everything written to the sink so far
+    written: String,
+    /// Whether an attached section changed text that was already written
+    held_back: bool,
+    finished: bool,
+    /// The kind of the error of the write that failed, if one did
+    failed: Option<io::ErrorKind>,
+}
+
+impl<W: io::Write> ReportStream<W> {
+    /// Attach sections to the report with the [`Section`] methods called by `attach`, e.g.
+    /// `|report| report.section(..)`, and write them to the sink
+    ///
+    /// # Details
+    ///
+    /// `attach` is called with a stand-in for the report as an `Err`, and must return it as
+    /// one. Everything attached to the stand-in is moved over to the report afterwards, i.e.
+    /// the sections, the help text and the other details set with `Section` methods, but
+    /// changes to the chain of errors, e.g. with `wrap_err`, are not. Sections attached after
+    /// the stream was finished are only attached to the report.
+    ///
+    /// The report never leaves the stream, so when `attach` panics nothing is attached, and
+    /// the stream can still be used once the panic is caught.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, Section};
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let mut sink = vec![];
+    /// let stream = color_anyhow::stream_report(anyhow!("the scan failed"), &mut sink);
+    ///
+    /// let panicked = std::panic::catch_unwind(|| {
+    ///     stream.attach(|report| report.section(vec!["a.txt"][1])).unwrap();
+    /// });
+    /// assert!(panicked.is_err());
+    ///
+    /// stream.attach(|report| report.section("skipped a.txt")).unwrap();
+    /// stream.finish().unwrap();
+    ///
+    /// let report = stream.into_report();
+    /// assert!(format!("{:?}", report).ends_with("   0: the scan failed\n\nskipped a.txt"));
+    /// assert_eq!(
+    ///     String::from_utf8(sink).unwrap(),
+    ///     "\n   0: the scan failed\n\nskipped a.txt"
+    /// );
+    /// ```
+    ///
+    /// [`Section`]: trait.Section.html
+    pub fn attach<F>(&self, attach: F) -> io::Result<()>
+    where
+        F: FnOnce(Result<(), anyhow::Error>) -> Result<(), anyhow::Error>,
+    {
+        let mut attached =
+            attach(Err(stand_in())).expect_err("the report must be returned as an `Err`");
+
+        let mut state = self.lock();
+        let handlers = (
+            attached.handler_mut().downcast_mut::<Handler>(),
+            state.report.handler_mut().downcast_mut::<Handler>(),
+        );
+        if let (Some(attached), Some(report)) = handlers {
+            move_attached(attached, report);
+        }
+
+        if state.finished {
+            return Ok(());
+        }
+        state.write_sections()
+    }
+
+    /// Write the rest of the report, i.e. the traces and the help text, to the sink
+    ///
+    /// # Details
+    ///
+    /// Only the first call writes anything.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{ColorChoice, HookBuilder}, Section};
+    /// use std::io;
+    ///
+    /// std::env::set_var("RUST_LIB_BACKTRACE", "0");
+    /// HookBuilder::blank()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// // error sections are rendered before the other sections
+    /// let mut sink = vec![];
+    /// let stream = color_anyhow::stream_report(anyhow!("the sync failed"), &mut sink);
+    /// stream.attach(|report| report.section("retried 3 times")).unwrap();
+    /// let disk_full = io::Error::new(io::ErrorKind::Other, "disk full");
+    /// stream.attach(|report| report.error(disk_full)).unwrap();
+    /// stream.finish().unwrap();
+    ///
+    /// let written = String::from_utf8(sink).unwrap();
+    /// let (streamed, full) = written.split_once("the full report follows)").unwrap();
+    /// assert!(streamed.ends_with("retried 3 times\n\n(sections were attached out of order, "));
+    /// assert!(full.contains("disk full\n\nretried 3 times"));
+    ///
+    /// // a sink that fails stops the stream, but the report can still be taken out of it
+    /// struct Closed;
+    ///
+    /// impl io::Write for Closed {
+    ///     fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+    ///         Err(io::ErrorKind::BrokenPipe.into())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let stream = color_anyhow::stream_report(anyhow!("the sync failed"), Closed);
+    /// assert_eq!(stream.finish().unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+    /// assert_eq!(stream.into_report().to_string(), "the sync failed");
+    /// ```
+    pub fn finish(&self) -> io::Result<()> {
+        let mut state = self.lock();
+        if state.finished {
+            return Ok(());
+        }
+        state.finished = true;
+        state.check_failed()?;
+
+        let full = state.render(&ReportOptions::new());
+        match full.strip_prefix(state.written.as_str()) {
+            Some(rest) if !state.held_back => {
+                let rest = rest.to_owned();
+                state.write(rest)
+            }
+            _ => {
+                let note = "(sections were attached out of order, the full report follows)";
+                let note = format!("\n\n{}\n", Style::new().dimmed().paint(note));
+                state.write(finish_report(note) + &full)
+            }
+        }
+    }
+
+    /// Returns the report with all the sections attached to it
+    pub fn into_report(self) -> anyhow::Error {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.report
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StreamState<W>> {
+        // the sections written so far stay consistent if writing them panics
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<W: io::Write> StreamState<W> {
+    fn render(&self, options: &ReportOptions) -> String {
+        format_report(&self.report, options)
+    }
+
+    /// Write the sections attached since the last write, if they extend what was written
+    fn write_sections(&mut self) -> io::Result<()> {
+        self.check_failed()?;
+        let printer = installed_printer();
+        if self.held_back || printer.table_of_contents() || printer.reverse_layout() {
+            return Ok(());
+        }
+
+        // the full report starts with a line break, the rendered blocks don't
+        let blocks =
+            self.render(&ReportOptions::new().include(BlockSet::CHAIN | BlockSet::SECTIONS));
+        let rendered = printer.line_ending().apply("\n".to_owned()) + &blocks;
+        match rendered.strip_prefix(self.written.as_str()) {
+            Some(rest) => {
+                let rest = rest.to_owned();
+                self.write(rest)
+            }
+            None => {
+                self.held_back = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn write(&mut self, text: String) -> io::Result<()> {
+        let result = self
+            .sink
+            .write_all(text.as_bytes())
+            .and_then(|()| self.sink.flush());
+        match result {
+            Ok(()) => {
+                self.written.push_str(&text);
+                Ok(())
+            }
+            Err(e) => {
+                self.failed = Some(e.kind());
+                Err(e)
+            }
+        }
+    }
+
+    fn check_failed(&self) -> io::Result<()> {
+        match self.failed {
+            Some(kind) => Err(io::Error::new(
+                kind,
+                "an earlier write of the report stream failed",
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether the report being created is a stand-in for the report of a stream
+pub(crate) fn creating_stand_in() -> bool {
+    STAND_IN.with(Cell::get)
+}
+
+/// A report to attach sections to in place of the report of a stream
+fn stand_in() -> anyhow::Error {
+    STAND_IN.with(|stand_in| stand_in.set(true));
+    let report = anyhow::anyhow!("stand-in for a streamed report");
+    STAND_IN.with(|stand_in| stand_in.set(false));
+    report
+}
+
+/// Move everything attached to `from` over to `to`, the details set on both are taken from
+/// `from`, like they would be if they had been set on `to` last
+fn move_attached(from: &mut Handler, to: &mut Handler) {
+    to.sections.append(&mut from.sections);
+    to.breadcrumbs.append(&mut from.breadcrumbs);
+    to.source_locations.append(&mut from.source_locations);
+    to.inline_hints.append(&mut from.inline_hints);
+
+    if from.backtrace.is_some() && to.backtrace.is_none() {
+        to.backtrace = from.backtrace.take();
+    }
+    if from.chain_label.is_some() {
+        to.chain_label = from.chain_label.take();
+    }
+    if from.severity.is_some() {
+        to.severity = from.severity.take();
+    }
+    if from.localized.is_some() {
+        to.localized = from.localized.take();
+    }
+    if from.category.is_some() {
+        to.category = from.category.take();
+    }
+    if from.theme.is_some() {
+        to.theme = from.theme.take();
+    }
+    #[cfg(feature = "json")]
+    if from.json_source.is_some() {
+        to.json_source = from.json_source.take();
+    }
+}
@@ -0,0 +1,116 @@
+//! Compact rendering of backtraces captured in binaries without symbols
+use std::fmt;
+
+/// The note rendered above the addresses of a backtrace without symbols, by default
+pub(crate) const DEFAULT_NOTE: &str = "symbols unavailable (binary appears stripped); raw addresses follow — symbolicate with the matching debug artifacts";
+
+/// Whether a backtrace whose frames resolved `symbols` is rendered as a list of addresses,
+/// given the fraction of unresolved frames at which it is
+pub(crate) fn is_stripped(symbols: &[(usize, bool)], threshold: f64) -> bool {
+    if symbols.is_empty() {
+        return false;
+    }
+
+    let unresolved = symbols.iter().filter(|(_, resolved)| !resolved).count();
+    unresolved as f64 / symbols.len() as f64 >= threshold
+}
+
+/// The frames of a backtrace without symbols, as the addresses of their instructions
+pub(crate) struct AddressList<'a> {
+    pub(crate) note: &'a str,
+    pub(crate) symbols: &'a [(usize, bool)],
+}
+
+impl fmt::Display for AddressList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", ansi_term::Style::new().dimmed().paint(self.note))?;
+        if let Ok(exe) = std::env::current_exe() {
+            writeln!(f, "executable: {}", exe.display())?;
+        }
+        if let Some(build_id) = build_id() {
+            writeln!(f, "build-id: {}", build_id)?;
+        }
+
+        for (n, (ip, _)) in (1..).zip(self.symbols) {
+            writeln!(f, "{:>2}: {:#018x}", n, ip)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The GNU build-id of the running executable, read from the notes of its ELF headers
+#[cfg(target_os = "linux")]
+fn build_id() -> Option<String> {
+    use std::fs::File;
+    use std::io::{Read, Seek, SeekFrom};
+
+    const PT_NOTE: u32 = 4;
+    const NT_GNU_BUILD_ID: u32 = 3;
+
+    let mut exe = File::open("/proc/self/exe").ok()?;
+    let mut header = [0; 64];
+    exe.read_exact(&mut header).ok()?;
+    // only little endian 64 bit executables are read
+    if &header[..4] != b"\x7fELF" || header[4] != 2 || header[5] != 1 {
+        return None;
+    }
+
+    let u16_at = |bytes: &[u8], at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+    let u32_at = |bytes: &[u8], at: usize| {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    };
+    let u64_at = |bytes: &[u8], at: usize| {
+        let mut word = [0; 8];
+        word.copy_from_slice(&bytes[at..at + 8]);
+        u64::from_le_bytes(word)
+    };
+
+    let phoff = u64_at(&header, 0x20);
+    let phentsize = u16_at(&header, 0x36) as usize;
+    let phnum = u16_at(&header, 0x38) as usize;
+    if phentsize < 0x38 {
+        return None;
+    }
+
+    let mut headers = vec![0; phentsize * phnum];
+    exe.seek(SeekFrom::Start(phoff)).ok()?;
+    exe.read_exact(&mut headers).ok()?;
+
+    for header in headers.chunks(phentsize) {
+        if u32_at(header, 0) != PT_NOTE {
+            continue;
+        }
+
+        let (offset, size) = (u64_at(header, 0x08), u64_at(header, 0x20) as usize);
+        let mut notes = vec![0; size.min(4096)];
+        exe.seek(SeekFrom::Start(offset)).ok()?;
+        exe.read_exact(&mut notes).ok()?;
+
+        let mut at = 0;
+        while at + 12 <= notes.len() {
+            let name_size = u32_at(&notes, at) as usize;
+            let desc_size = u32_at(&notes, at + 4) as usize;
+            let kind = u32_at(&notes, at + 8);
+            let name = at + 12;
+            let desc = name + (name_size + 3) / 4 * 4;
+            let end = desc + (desc_size + 3) / 4 * 4;
+            if desc + desc_size > notes.len() {
+                break;
+            }
+
+            if kind == NT_GNU_BUILD_ID && notes[name..name + name_size] == *b"GNU\0" {
+                let id = notes[desc..desc + desc_size].iter();
+                return Some(id.map(|byte| format!("{:02x}", byte)).collect());
+            }
+            at = end;
+        }
+    }
+
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn build_id() -> Option<String> {
+    None
+}
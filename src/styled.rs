@@ -0,0 +1,251 @@
+//! Rendering of error reports as styled text spans
+use crate::handler::render_redacted;
+use crate::writers::escape_len;
+
+/// A color used by a [`TextStyle`]
+///
+/// The sixteen named colors correspond to the standard terminal palette, the
+/// `Bright` variants are the intense versions used for most of the highlights
+/// in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TextColor {
+    /// ANSI black
+    Black,
+    /// ANSI red
+    Red,
+    /// ANSI green
+    Green,
+    /// ANSI yellow
+    Yellow,
+    /// ANSI blue
+    Blue,
+    /// ANSI purple
+    Purple,
+    /// ANSI cyan
+    Cyan,
+    /// ANSI white
+    White,
+    /// ANSI bright black
+    BrightBlack,
+    /// ANSI bright red
+    BrightRed,
+    /// ANSI bright green
+    BrightGreen,
+    /// ANSI bright yellow
+    BrightYellow,
+    /// ANSI bright blue
+    BrightBlue,
+    /// ANSI bright purple
+    BrightPurple,
+    /// ANSI bright cyan
+    BrightCyan,
+    /// ANSI bright white
+    BrightWhite,
+    /// An entry of the 256 color palette beyond the sixteen named colors
+    Fixed(u8),
+    /// A 24 bit color
+    Rgb(u8, u8, u8),
+}
+
+impl TextColor {
+    /// Convert an entry of the 256 color palette, using the named variants for
+    /// its first sixteen entries
+    pub(crate) fn from_palette(n: u8) -> Self {
+        use TextColor::*;
+
+        const NAMED: [TextColor; 16] = [
+            Black,
+            Red,
+            Green,
+            Yellow,
+            Blue,
+            Purple,
+            Cyan,
+            White,
+            BrightBlack,
+            BrightRed,
+            BrightGreen,
+            BrightYellow,
+            BrightBlue,
+            BrightPurple,
+            BrightCyan,
+            BrightWhite,
+        ];
+
+        NAMED.get(n as usize).copied().unwrap_or(Fixed(n))
+    }
+
+    /// The SGR parameters selecting this color as the foreground or background
+    pub(crate) fn sgr_params(self, background: bool) -> String {
+        use TextColor::*;
+
+        let named = match self {
+            Black => 0,
+            Red => 1,
+            Green => 2,
+            Yellow => 3,
+            Blue => 4,
+            Purple => 5,
+            Cyan => 6,
+            White => 7,
+            BrightBlack => 8,
+            BrightRed => 9,
+            BrightGreen => 10,
+            BrightYellow => 11,
+            BrightBlue => 12,
+            BrightPurple => 13,
+            BrightCyan => 14,
+            BrightWhite => 15,
+            Fixed(n) => return format!("{};5;{}", if background { 48 } else { 38 }, n),
+            Rgb(r, g, b) => {
+                return format!("{};2;{};{};{}", if background { 48 } else { 38 }, r, g, b)
+            }
+        };
+
+        let base = match (named < 8, background) {
+            (true, false) => 30,
+            (true, true) => 40,
+            (false, false) => 90 - 8,
+            (false, true) => 100 - 8,
+        };
+        (base + named).to_string()
+    }
+}
+
+/// How a span of text in a report is meant to be displayed
+///
+/// The default style is plain, uncolored text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct TextStyle {
+    /// The color of the text, if it isn't the default
+    pub foreground: Option<TextColor>,
+    /// The color behind the text, if it isn't the default
+    pub background: Option<TextColor>,
+    /// Whether the text is bold
+    pub bold: bool,
+    /// Whether the text is dimmed
+    pub dimmed: bool,
+    /// Whether the text is italic
+    pub italic: bool,
+    /// Whether the text is underlined
+    pub underline: bool,
+}
+
+impl TextStyle {
+    /// Apply the parameters of an SGR escape sequence
+    fn apply(&mut self, params: &str) {
+        let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = TextStyle::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dimmed = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(TextColor::from_palette(param - 30)),
+                90..=97 => self.foreground = Some(TextColor::from_palette(param - 90 + 8)),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(TextColor::from_palette(param - 40)),
+                100..=107 => self.background = Some(TextColor::from_palette(param - 100 + 8)),
+                49 => self.background = None,
+                38 => self.foreground = extended_color(&mut params),
+                48 => self.background = extended_color(&mut params),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parse the color following a `38` or `48` SGR parameter
+fn extended_color(params: &mut impl Iterator<Item = u8>) -> Option<TextColor> {
+    match params.next() {
+        Some(5) => params.next().map(TextColor::from_palette),
+        Some(2) => {
+            let mut rgb = || params.next().unwrap_or(0);
+            Some(TextColor::Rgb(rgb(), rgb(), rgb()))
+        }
+        _ => None,
+    }
+}
+
+/// Render an error report as a sequence of text spans and the style each is
+/// displayed with
+///
+/// # Details
+///
+/// The report is rendered the same way as its `Debug` output, including the
+/// error chain, sections, backtraces and span traces, with its colors captured
+/// as [`TextStyle`]s instead of ANSI escape codes. This lets reports be shown
+/// in GUIs, TUIs and other frontends that don't interpret escape codes.
+/// Concatenating the text of every span yields the uncolored report, adjacent
+/// spans always have different styles.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::anyhow, Section, TextColor};
+///
+/// color_anyhow::install().unwrap();
+///
+/// let report = Err::<(), _>(anyhow!("disk is full"))
+///     .note("free some space and try again")
+///     .unwrap_err();
+/// let spans = color_anyhow::styled_report(&report);
+///
+/// let (_, style) = spans
+///     .iter()
+///     .find(|(text, _)| text.contains("disk is full"))
+///     .unwrap();
+/// assert_eq!(style.foreground, Some(TextColor::BrightRed));
+///
+/// let (_, style) = spans.iter().find(|(text, _)| text == "Note").unwrap();
+/// assert_eq!(style.foreground, Some(TextColor::BrightCyan));
+///
+/// let text: String = spans.iter().map(|(text, _)| text.as_str()).collect();
+/// assert!(text.contains("free some space and try again"));
+/// assert!(!text.contains('\x1b'));
+/// ```
+pub fn styled_report(error: &anyhow::Error) -> Vec<(String, TextStyle)> {
+    let report = render_redacted(error);
+
+    let mut spans: Vec<(String, TextStyle)> = vec![];
+    let mut style = TextStyle::default();
+    let mut push = |text: &str, style: TextStyle| {
+        if text.is_empty() {
+            return;
+        }
+
+        match spans.last_mut() {
+            Some((last, last_style)) if *last_style == style => last.push_str(text),
+            _ => spans.push((text.to_owned(), style)),
+        }
+    };
+
+    let mut rest = report.trim_start_matches('\n');
+    while let Some(start) = rest.find('\x1b') {
+        push(&rest[..start], style);
+        rest = &rest[start..];
+        let len = escape_len(rest);
+        let escape = &rest[..len];
+        rest = &rest[len..];
+
+        // Only SGR sequences affect how the text looks, drop everything else
+        if let Some(params) = escape
+            .strip_prefix("\x1b[")
+            .and_then(|s| s.strip_suffix('m'))
+        {
+            style.apply(params);
+        }
+    }
+    push(rest, style);
+
+    spans
+}
@@ -0,0 +1,137 @@
+//! Recoloring of rendered reports
+use crate::styled::TextColor;
+use crate::writers::escape_len;
+
+/// A set of replacements for the colors reports are rendered with
+///
+/// # Details
+///
+/// Reports highlight the error messages and the functions of the application's
+/// own frames in [`TextColor::BrightRed`], the functions of dependencies in
+/// [`TextColor::Green`], the labels of help sections in [`TextColor::BrightCyan`]
+/// and source locations in [`TextColor::Purple`].
+/// A `Theme` replaces some of these colors with others, in the foreground and
+/// the background alike, leaving the colors without a replacement as they are.
+/// A theme only changes the colors of a report, so it has no effect on reports
+/// rendered without colors.
+///
+/// A theme installed with `HookBuilder::theme` applies to every report, a theme
+/// attached with [`Section::theme`] to that report only, instead of the
+/// installed one.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{Theme, TextColor};
+///
+/// let theme = Theme::new()
+///     .color(TextColor::BrightRed, TextColor::Rgb(255, 85, 0))
+///     .color(TextColor::BrightCyan, TextColor::BrightBlue);
+/// assert_eq!(theme.replacement(TextColor::BrightRed), Some(TextColor::Rgb(255, 85, 0)));
+/// assert_eq!(theme.replacement(TextColor::Purple), None);
+/// ```
+///
+/// [`TextColor::BrightRed`]: enum.TextColor.html#variant.BrightRed
+/// [`TextColor::BrightCyan`]: enum.TextColor.html#variant.BrightCyan
+/// [`TextColor::Purple`]: enum.TextColor.html#variant.Purple
+/// [`TextColor::Green`]: enum.TextColor.html#variant.Green
+/// [`Section::theme`]: trait.Section.html#tymethod.theme
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Theme {
+    colors: Vec<(TextColor, TextColor)>,
+}
+
+impl Theme {
+    /// Construct a theme that doesn't replace any color
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render the color `from` as `to`, replacing an earlier replacement for `from`
+    pub fn color(mut self, from: TextColor, to: TextColor) -> Self {
+        match self.colors.iter_mut().find(|(color, _)| *color == from) {
+            Some((_, replacement)) => *replacement = to,
+            None => self.colors.push((from, to)),
+        }
+        self
+    }
+
+    /// Returns the color `color` is rendered as, if the theme replaces it
+    pub fn replacement(&self, color: TextColor) -> Option<TextColor> {
+        self.colors
+            .iter()
+            .find(|(from, _)| *from == color)
+            .map(|&(_, to)| to)
+    }
+
+    /// Replace the colors of the SGR escape sequences in a rendered report
+    pub(crate) fn apply(&self, report: &str) -> String {
+        if self.colors.is_empty() {
+            return report.to_owned();
+        }
+
+        let mut out = String::with_capacity(report.len());
+        let mut rest = report;
+        while let Some(start) = rest.find('\x1b') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let len = escape_len(rest);
+            let escape = &rest[..len];
+            rest = &rest[len..];
+
+            match escape
+                .strip_prefix("\x1b[")
+                .and_then(|s| s.strip_suffix('m'))
+            {
+                Some(params) => {
+                    out.push_str("\x1b[");
+                    out.push_str(&self.recolor(params));
+                    out.push('m');
+                }
+                None => out.push_str(escape),
+            }
+        }
+        out.push_str(rest);
+
+        out
+    }
+
+    /// Replace the colors selected by the parameters of an SGR escape sequence
+    fn recolor(&self, params: &str) -> String {
+        let params: Vec<&str> = params.split(';').collect();
+        let number = |n: usize| params.get(n).and_then(|p| p.parse::<u8>().ok());
+
+        let mut out: Vec<String> = Vec::with_capacity(params.len());
+        let mut n = 0;
+        while n < params.len() {
+            let (color, background, len) = match number(n) {
+                Some(p @ 30..=37) => (Some(TextColor::from_palette(p - 30)), false, 1),
+                Some(p @ 90..=97) => (Some(TextColor::from_palette(p - 90 + 8)), false, 1),
+                Some(p @ 40..=47) => (Some(TextColor::from_palette(p - 40)), true, 1),
+                Some(p @ 100..=107) => (Some(TextColor::from_palette(p - 100 + 8)), true, 1),
+                Some(p @ (38 | 48)) => match number(n + 1) {
+                    Some(5) => (number(n + 2).map(TextColor::from_palette), p == 48, 3),
+                    Some(2) => {
+                        let rgb = (number(n + 2), number(n + 3), number(n + 4));
+                        let color = match rgb {
+                            (Some(r), Some(g), Some(b)) => Some(TextColor::Rgb(r, g, b)),
+                            _ => None,
+                        };
+                        (color, p == 48, 5)
+                    }
+                    _ => (None, false, 1),
+                },
+                _ => (None, false, 1),
+            };
+            let len = len.min(params.len() - n);
+
+            match color.and_then(|color| self.replacement(color)) {
+                Some(replacement) => out.push(replacement.sgr_params(background)),
+                None => out.extend(params[n..n + len].iter().map(|&p| p.to_owned())),
+            }
+            n += len;
+        }
+
+        out.join(";")
+    }
+}
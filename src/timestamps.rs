@@ -0,0 +1,178 @@
+//! Wall-clock timestamps for spans and reports
+#[cfg(feature = "capture-spantrace")]
+use {
+    std::fmt,
+    std::time::{SystemTime, UNIX_EPOCH},
+    std::{any::TypeId, marker::PhantomData},
+    tracing_core::{span, Dispatch, Subscriber},
+    tracing_subscriber::{
+        layer::{Context, Layer},
+        registry::LookupSpan,
+    },
+};
+
+/// A subscriber [`Layer`] that records the wall-clock time each span was first
+/// entered so it can be shown on the frames of a `SpanTrace`.
+///
+/// # Details
+///
+/// This layer must be installed alongside `tracing_error::ErrorLayer` and
+/// enabled with [`HookBuilder::spantrace_timestamps`]. Spans entered before the
+/// layer was installed are rendered without a timestamp.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::SpanTimestampLayer;
+/// use tracing_error::ErrorLayer;
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry()
+///     .with(ErrorLayer::default())
+///     .with(SpanTimestampLayer::default())
+///     .init();
+/// ```
+///
+/// [`Layer`]: https://docs.rs/tracing-subscriber/0.2/tracing_subscriber/layer/trait.Layer.html
+/// [`HookBuilder::spantrace_timestamps`]: config/struct.HookBuilder.html#method.spantrace_timestamps
+#[cfg(feature = "capture-spantrace")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capture-spantrace")))]
+pub struct SpanTimestampLayer<S> {
+    get_timestamps: WithTimestamps,
+    _subscriber: PhantomData<fn(S)>,
+}
+
+#[cfg(feature = "capture-spantrace")]
+struct EnteredAt(SystemTime);
+
+// this function "remembers" the type of the subscriber so that we can
+// downcast to something aware of it without knowing that type at the callsite.
+#[cfg(feature = "capture-spantrace")]
+struct WithTimestamps(fn(&Dispatch, &span::Id, f: &mut dyn FnMut(Option<SystemTime>)));
+
+#[cfg(feature = "capture-spantrace")]
+impl<S> Layer<S> for SpanTimestampLayer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if extensions.get_mut::<EnteredAt>().is_none() {
+                extensions.insert(EnteredAt(crate::clock::now()));
+            }
+        }
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        match id {
+            id if id == TypeId::of::<Self>() => Some(self as *const _ as *const ()),
+            id if id == TypeId::of::<WithTimestamps>() => {
+                Some(&self.get_timestamps as *const _ as *const ())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "capture-spantrace")]
+impl<S> SpanTimestampLayer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn get_timestamps(dispatch: &Dispatch, id: &span::Id, f: &mut dyn FnMut(Option<SystemTime>)) {
+        let subscriber = dispatch
+            .downcast_ref::<S>()
+            .expect("subscriber should downcast to expected type; this is a bug!");
+        let mut next = subscriber.span(id);
+        while let Some(span) = next {
+            f(span
+                .extensions()
+                .get::<EnteredAt>()
+                .map(|entered| entered.0));
+            next = span.parent();
+        }
+    }
+}
+
+#[cfg(feature = "capture-spantrace")]
+impl<S> Default for SpanTimestampLayer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn default() -> Self {
+        Self {
+            get_timestamps: WithTimestamps(Self::get_timestamps),
+            _subscriber: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "capture-spantrace")]
+impl<S> fmt::Debug for SpanTimestampLayer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpanTimestampLayer").finish()
+    }
+}
+
+/// Capture the entry timestamps of the current span and all of its parents,
+/// innermost first, in the same order as the frames of a `SpanTrace`.
+#[cfg(feature = "capture-spantrace")]
+pub(crate) fn capture_span_timestamps() -> Option<Vec<Option<SystemTime>>> {
+    tracing_core::dispatcher::get_default(|dispatch| {
+        let current = dispatch.current_span();
+        let id = current.id()?;
+        let getter = dispatch.downcast_ref::<WithTimestamps>()?;
+        let mut timestamps = vec![];
+        (getter.0)(dispatch, id, &mut |timestamp| timestamps.push(timestamp));
+        Some(timestamps)
+    })
+}
+
+/// Displays a `SystemTime` as an RFC 3339 timestamp in UTC with millisecond
+/// precision, or in the format configured with `HookBuilder::date_format`
+#[cfg(feature = "capture-spantrace")]
+pub(crate) struct Timestamp(pub(crate) SystemTime);
+
+#[cfg(feature = "capture-spantrace")]
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(format) = crate::config::installed_printer().date_format() {
+            return format.write(f, self.0);
+        }
+
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs();
+        let (year, month, day) = civil_from_days((secs / 86400) as i64);
+        let secs_of_day = secs % 86400;
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60,
+            since_epoch.subsec_millis()
+        )
+    }
+}
+
+/// Convert days since the unix epoch into a (year, month, day) triple
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
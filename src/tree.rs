@@ -0,0 +1,247 @@
+//! Errors with multiple causes, rendered as a tree
+use crate::{ColorExt, Handler};
+use ansi_term::Color::*;
+use std::fmt::{self, Display, Write};
+
+/// An error that failed because several other errors did, e.g. a task whose
+/// subtasks failed
+///
+/// # Details
+///
+/// A `CauseTree` converts into an `anyhow::Error` like any other error. When
+/// it is part of a report's chain of errors the report renders its children
+/// under a `Causes:` header as an indented tree. Each child is rendered with
+/// its own chain of errors and its own sections, and children which are
+/// themselves `CauseTree`s have their children nested below them.
+///
+/// At most `max_depth` levels of children are rendered, and at most
+/// `max_children` children per level, the rest are summarized in a single
+/// line. The limits of the outermost tree apply to the whole tree.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{anyhow::{anyhow, Context}, config::{BlockSet, ColorChoice, HookBuilder, ReportOptions}, CauseTree, Section};
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+///
+/// let lint = CauseTree::new("linting failed")
+///     .child(anyhow!("src/lib.rs: unused import"))
+///     .child(
+///         Err::<(), _>(anyhow!("permission denied"))
+///             .context("could not read src/main.rs")
+///             .unwrap_err(),
+///     );
+/// let test = Err::<(), _>(anyhow!("2 tests failed"))
+///     .note("run with --nocapture to see their output")
+///     .unwrap_err();
+/// let report = color_anyhow::anyhow::Error::from(
+///     CauseTree::new("the build failed").child(lint.into()).child(test),
+/// );
+///
+/// let options = ReportOptions::new().include(BlockSet::CHAIN);
+/// assert_eq!(
+///     color_anyhow::format_report(&report, &options),
+///     "   0: the build failed\n\
+///      \n\
+///      Causes:\n\
+///      ├─ 0: linting failed\n\
+///      │  ├─ 0: src/lib.rs: unused import\n\
+///      │  └─ 0: could not read src/main.rs\n\
+///      │     1: permission denied\n\
+///      └─ 0: 2 tests failed\n\
+///      \u{20}  Note: run with --nocapture to see their output"
+/// );
+/// ```
+pub struct CauseTree {
+    message: String,
+    children: Vec<anyhow::Error>,
+    max_depth: usize,
+    max_children: usize,
+}
+
+impl CauseTree {
+    /// The default number of levels of children rendered
+    pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+    /// The default number of children rendered per level
+    pub const DEFAULT_MAX_CHILDREN: usize = 16;
+
+    /// Construct a `CauseTree` without children, displayed as `message`
+    pub fn new<D>(message: D) -> Self
+    where
+        D: Display,
+    {
+        Self {
+            message: message.to_string(),
+            children: vec![],
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_children: Self::DEFAULT_MAX_CHILDREN,
+        }
+    }
+
+    /// Add a child report, rendered after the previously added ones
+    pub fn child(mut self, report: anyhow::Error) -> Self {
+        self.children.push(report);
+        self
+    }
+
+    /// Render at most `max_depth` levels of children
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{BlockSet, ColorChoice, HookBuilder, ReportOptions}, CauseTree};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let nested = CauseTree::new("b failed")
+    ///     .child(anyhow!("c failed"))
+    ///     .child(anyhow!("d failed"));
+    /// let report = color_anyhow::anyhow::Error::from(
+    ///     CauseTree::new("a failed").child(nested.into()).max_depth(1),
+    /// );
+    ///
+    /// let options = ReportOptions::new().include(BlockSet::CHAIN);
+    /// assert!(color_anyhow::format_report(&report, &options).ends_with(
+    ///     "Causes:\n\
+    ///      └─ 0: b failed\n\
+    ///      \u{20}  └─ … 2 nested causes hidden"
+    /// ));
+    /// ```
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Render at most `max_children` children per level
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use color_anyhow::{anyhow::anyhow, config::{BlockSet, ColorChoice, HookBuilder, ReportOptions}, CauseTree};
+    ///
+    /// HookBuilder::default()
+    ///     .color_choice(ColorChoice::Never)
+    ///     .install()
+    ///     .unwrap();
+    ///
+    /// let tree = (0..5).fold(CauseTree::new("all shards failed"), |tree, n| {
+    ///     tree.child(anyhow!("shard {} failed", n))
+    /// });
+    /// let report = color_anyhow::anyhow::Error::from(tree.max_children(2));
+    ///
+    /// let options = ReportOptions::new().include(BlockSet::CHAIN);
+    /// assert!(color_anyhow::format_report(&report, &options).ends_with(
+    ///     "Causes:\n\
+    ///      ├─ 0: shard 0 failed\n\
+    ///      ├─ 0: shard 1 failed\n\
+    ///      └─ … and 3 more"
+    /// ));
+    /// ```
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        self.max_children = max_children;
+        self
+    }
+
+    /// The child reports, in the order they were added
+    pub fn children(&self) -> &[anyhow::Error] {
+        &self.children
+    }
+}
+
+impl fmt::Debug for CauseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CauseTree")
+            .field("message", &self.message)
+            .field("children", &self.children.len())
+            .field("max_depth", &self.max_depth)
+            .field("max_children", &self.max_children)
+            .finish()
+    }
+}
+
+impl Display for CauseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CauseTree {}
+
+/// The first `CauseTree` in the chain of `error`
+pub(crate) fn find_tree<'a>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a CauseTree> {
+    anyhow::Chain::new(error).find_map(|error| error.downcast_ref::<CauseTree>())
+}
+
+/// Write the children of `tree` as a tree, one line per chain entry and section line
+pub(crate) fn write_causes<W: Write>(f: &mut W, tree: &CauseTree) -> fmt::Result {
+    write!(f, "Causes:")?;
+    write_children(f, tree, "", 1, (tree.max_depth, tree.max_children))
+}
+
+fn write_children<W: Write>(
+    f: &mut W,
+    tree: &CauseTree,
+    prefix: &str,
+    depth: usize,
+    limits: (usize, usize),
+) -> fmt::Result {
+    let (max_depth, max_children) = limits;
+    if depth > max_depth {
+        return write!(
+            f,
+            "\n{}└─ … {} nested causes hidden",
+            prefix,
+            tree.children.len()
+        );
+    }
+
+    let shown = tree.children.len().min(max_children);
+    let hidden = tree.children.len() - shown;
+    for (i, child) in tree.children[..shown].iter().enumerate() {
+        let last = i + 1 == shown && hidden == 0;
+        let (branch, continuation) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        let continuation = format!("{}{}", prefix, continuation);
+
+        for (n, error) in child.chain().enumerate() {
+            let message = Red.make_intense().paint(error.to_string());
+            if n == 0 {
+                write!(f, "\n{}{}{}: {}", prefix, branch, n, message)?;
+            } else {
+                write!(f, "\n{}{}: {}", continuation, n, message)?;
+            }
+        }
+
+        let sections = child
+            .handler()
+            .downcast_ref::<Handler>()
+            .map(|handler| &handler.sections[..])
+            .unwrap_or_default();
+        for section in sections {
+            for line in section.to_string().lines() {
+                write!(f, "\n{}", format!("{}{}", continuation, line).trim_end())?;
+            }
+        }
+
+        if let Some(nested) = find_tree(child.as_ref()) {
+            write_children(f, nested, &continuation, depth + 1, limits)?;
+        }
+    }
+
+    if hidden > 0 {
+        write!(f, "\n{}└─ … and {} more", prefix, hidden)?;
+    }
+
+    Ok(())
+}
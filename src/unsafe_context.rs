@@ -0,0 +1,111 @@
+//! Skipping the capture of traces in contexts where it could deadlock, such as signal handlers
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+thread_local! {
+    /// The number of guards entered on this thread, and how many of them skip backtraces
+    static ENTERED: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// Enter a context where capturing traces isn't safe, until the returned guard is dropped
+///
+/// # Details
+///
+/// Capturing a `SpanTrace` takes locks of the tracing subscriber, and capturing a backtrace
+/// takes the locks of the symbolizer and allocates. In a signal handler, or any other context
+/// that interrupted a thread which may hold these locks, capturing them can deadlock. While
+/// the guard is alive, reports created on the current thread don't capture a `SpanTrace`,
+/// nor a backtrace with [`CaptureUnsafeContext::skip_backtrace`], and show a dimmed
+/// `trace capture skipped (unsafe context)` note below the chain of errors instead.
+///
+/// Entering the context only touches a thread local counter, so it's cheap, and guards can
+/// be nested, captures resume once the outermost guard is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::anyhow::anyhow;
+///
+/// std::env::set_var("RUST_LIB_BACKTRACE", "1");
+/// color_anyhow::install().unwrap();
+///
+/// let skipped = "trace capture skipped (unsafe context)";
+/// {
+///     let _outer = color_anyhow::enter_capture_unsafe_context().skip_backtrace();
+///     {
+///         let _inner = color_anyhow::enter_capture_unsafe_context();
+///     }
+///
+///     // still inside the outer guard
+///     let report = format!("{:?}", anyhow!("shutting down"));
+///     assert!(report.contains(skipped));
+///     assert!(!report.contains("BACKTRACE"));
+///     assert!(!report.contains("SPANTRACE"));
+/// }
+///
+/// let report = format!("{:?}", anyhow!("shutting down"));
+/// assert!(!report.contains(skipped));
+/// assert!(report.contains("BACKTRACE"));
+/// ```
+///
+/// [`CaptureUnsafeContext::skip_backtrace`]: struct.CaptureUnsafeContext.html#method.skip_backtrace
+pub fn enter_capture_unsafe_context() -> CaptureUnsafeContext {
+    ENTERED.with(|entered| {
+        let (guards, skip_backtrace) = entered.get();
+        entered.set((guards + 1, skip_backtrace));
+    });
+
+    CaptureUnsafeContext {
+        skip_backtrace: false,
+        _not_send: PhantomData,
+    }
+}
+
+/// A guard for a context where capturing traces isn't safe, returned by
+/// [`enter_capture_unsafe_context`]
+///
+/// The guard belongs to the thread that entered the context, so it can't be sent to another
+/// thread.
+///
+/// [`enter_capture_unsafe_context`]: fn.enter_capture_unsafe_context.html
+#[derive(Debug)]
+#[must_use = "trace capture resumes as soon as the guard is dropped"]
+pub struct CaptureUnsafeContext {
+    skip_backtrace: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl CaptureUnsafeContext {
+    /// Skip the capture of backtraces too while the guard is alive
+    pub fn skip_backtrace(mut self) -> Self {
+        if !self.skip_backtrace {
+            self.skip_backtrace = true;
+            ENTERED.with(|entered| {
+                let (guards, skip_backtrace) = entered.get();
+                entered.set((guards, skip_backtrace + 1));
+            });
+        }
+        self
+    }
+}
+
+impl Drop for CaptureUnsafeContext {
+    fn drop(&mut self) {
+        let backtrace = usize::from(self.skip_backtrace);
+        ENTERED.with(|entered| {
+            let (guards, skip_backtrace) = entered.get();
+            entered.set((guards - 1, skip_backtrace - backtrace));
+        });
+    }
+}
+
+/// Whether the current thread is in a context where `SpanTrace`s aren't captured
+#[cfg(feature = "capture-spantrace")]
+pub(crate) fn skips_span_trace() -> bool {
+    ENTERED.with(|entered| entered.get().0 > 0)
+}
+
+/// Whether the current thread is in a context where backtraces aren't captured
+pub(crate) fn skips_backtrace() -> bool {
+    ENTERED.with(|entered| entered.get().1 > 0)
+}
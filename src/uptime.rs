@@ -0,0 +1,80 @@
+//! Tracking how long the process has been running since the hooks were installed
+use once_cell::sync::OnceCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+static INSTALLED_AT: OnceCell<Instant> = OnceCell::new();
+
+/// Record the current time as the time the hooks were installed
+pub(crate) fn record_install() {
+    let _ = INSTALLED_AT.set(crate::clock::instant());
+}
+
+/// Returns the time elapsed since the hooks were installed, or `None` if they
+/// haven't been installed
+///
+/// # Examples
+///
+/// ```rust
+/// assert!(color_anyhow::uptime().is_none());
+///
+/// color_anyhow::install().unwrap();
+///
+/// let uptime = color_anyhow::uptime().unwrap();
+/// assert!(uptime < std::time::Duration::from_secs(60));
+/// ```
+pub fn uptime() -> Option<Duration> {
+    INSTALLED_AT
+        .get()
+        .map(|installed_at| crate::clock::instant().saturating_duration_since(*installed_at))
+}
+
+/// A `Duration` displayed in a compact human readable form
+///
+/// # Details
+///
+/// Durations under a second are shown in milliseconds, as they usually point at
+/// a failure during startup, longer durations in their two or three largest
+/// units, from seconds up to days.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::HumanDuration;
+/// use std::time::Duration;
+///
+/// let human = |secs: u64| HumanDuration(Duration::from_secs(secs)).to_string();
+///
+/// assert_eq!(HumanDuration(Duration::from_micros(300)).to_string(), "0ms");
+/// assert_eq!(HumanDuration(Duration::from_millis(840)).to_string(), "840ms");
+/// assert_eq!(HumanDuration(Duration::from_millis(1999)).to_string(), "1s");
+/// assert_eq!(human(59), "59s");
+/// assert_eq!(human(60), "1m 0s");
+/// assert_eq!(human(12 * 60 + 5), "12m 5s");
+/// assert_eq!(human(3600), "1h 0m");
+/// assert_eq!(human(23 * 3600 + 59 * 60 + 59), "23h 59m");
+/// assert_eq!(human(86400), "1d 0h 0m");
+/// assert_eq!(human(3 * 86400 + 4 * 3600 + 12 * 60 + 30), "3d 4h 12m");
+/// assert_eq!(human(400 * 86400), "400d 0h 0m");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let (days, hours, mins) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+
+        if secs == 0 {
+            write!(f, "{}ms", self.0.subsec_millis())
+        } else if secs < 60 {
+            write!(f, "{}s", secs)
+        } else if secs < 3600 {
+            write!(f, "{}m {}s", mins, secs % 60)
+        } else if secs < 86400 {
+            write!(f, "{}h {}m", hours, mins)
+        } else {
+            write!(f, "{}d {}h {}m", days, hours, mins)
+        }
+    }
+}
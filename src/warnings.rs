@@ -0,0 +1,171 @@
+//! Collection of non-fatal warnings to be reported at exit or attached to error reports
+use crate::config::installed_printer;
+use crate::ColorExt;
+use ansi_term::Color::*;
+use once_cell::sync::Lazy;
+use std::fmt::{self, Display};
+use std::io;
+use std::sync::Mutex;
+
+static GLOBAL: Lazy<WarningsCollector> = Lazy::new(WarningsCollector::default);
+
+/// Collects non-fatal warnings so they can be summarized once, either at exit
+/// or as part of an error report
+///
+/// # Details
+///
+/// Warnings are kept in the order they were recorded and can be recorded from
+/// any thread. Once `capacity` warnings are pending any further warnings are
+/// only counted, so a runaway loop can't exhaust memory.
+///
+/// When [`HookBuilder::attach_collected_warnings`] is enabled the pending
+/// warnings of the [`global`] collector are drained into a `Collected warnings:`
+/// section of every error report constructed.
+///
+/// # Examples
+///
+/// ```rust
+/// use color_anyhow::{config::{ColorChoice, HookBuilder}, WarningsCollector};
+///
+/// HookBuilder::default()
+///     .color_choice(ColorChoice::Never)
+///     .install()
+///     .unwrap();
+/// let collector = WarningsCollector::new(2);
+/// collector.warn("skipped a.txt: not valid utf-8");
+/// collector.warn("--fast is deprecated, use --release");
+/// collector.warn("skipped b.txt: not valid utf-8");
+///
+/// let mut out = vec![];
+/// collector.flush_to(&mut out).unwrap();
+/// let out = String::from_utf8(out).unwrap();
+///
+/// assert!(out.contains("Warning: skipped a.txt: not valid utf-8\n"));
+/// assert!(out.contains("Warning: --fast is deprecated, use --release\n"));
+/// assert!(out.contains("… and 1 more warning"));
+/// assert!(collector.is_empty());
+/// ```
+///
+/// [`HookBuilder::attach_collected_warnings`]: config/struct.HookBuilder.html#method.attach_collected_warnings
+/// [`global`]: #method.global
+#[derive(Debug)]
+pub struct WarningsCollector {
+    capacity: usize,
+    pending: Mutex<CollectedWarnings>,
+}
+
+impl WarningsCollector {
+    /// The capacity of the global collector and of `WarningsCollector::default()`
+    pub const DEFAULT_CAPACITY: usize = 100;
+
+    /// Construct a `WarningsCollector` that keeps at most `capacity` pending warnings
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            pending: Mutex::new(CollectedWarnings::default()),
+        }
+    }
+
+    /// The global collector, whose warnings can be attached to error reports
+    pub fn global() -> &'static WarningsCollector {
+        &GLOBAL
+    }
+
+    /// Record a warning
+    pub fn warn<D: Display>(&self, warning: D) {
+        let mut pending = self.lock();
+        if pending.entries.len() < self.capacity {
+            pending.entries.push(warning.to_string());
+        } else {
+            pending.overflow += 1;
+        }
+    }
+
+    /// Returns the number of pending warnings, including the ones over capacity
+    pub fn len(&self) -> usize {
+        let pending = self.lock();
+        pending.entries.len() + pending.overflow
+    }
+
+    /// Returns true if no warnings are pending
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write all pending warnings to `writer`, styled like the help sections of
+    /// an error report, and clear them
+    pub fn flush_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let warnings = self.drain();
+        if warnings.is_empty() {
+            return Ok(());
+        }
+
+        let mut out = warnings.to_string();
+        if !installed_printer().colors_enabled(atty::Stream::Stderr) {
+            out = crate::writers::strip_ansi(&out);
+        }
+
+        writeln!(writer, "{}", out)
+    }
+
+    /// Write all pending warnings to stderr and clear them
+    pub fn flush_to_stderr(&self) -> io::Result<()> {
+        self.flush_to(io::stderr().lock())
+    }
+
+    /// Take all pending warnings, leaving the collector empty
+    pub(crate) fn drain(&self) -> CollectedWarnings {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CollectedWarnings> {
+        // a panic while holding the lock can't leave the warnings inconsistent
+        self.pending
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for WarningsCollector {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// Warnings drained from a `WarningsCollector`
+#[derive(Debug, Default)]
+pub(crate) struct CollectedWarnings {
+    entries: Vec<String>,
+    overflow: usize,
+}
+
+impl CollectedWarnings {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.overflow == 0
+    }
+}
+
+impl Display for CollectedWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Collected warnings:")?;
+        for warning in &self.entries {
+            write!(
+                f,
+                "\n   {}: {}",
+                Yellow.make_intense().paint("Warning"),
+                warning
+            )?;
+        }
+
+        if self.overflow > 0 {
+            write!(
+                f,
+                "\n   … and {} more warning{}",
+                self.overflow,
+                if self.overflow == 1 { "" } else { "s" }
+            )?;
+        }
+
+        Ok(())
+    }
+}
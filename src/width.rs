@@ -0,0 +1,39 @@
+//! Detecting the width reports are rendered to when it wasn't given explicitly
+use std::env;
+
+/// The width of the terminal `stream` is connected to, or else the width in `COLUMNS`
+///
+/// `COLUMNS` covers outputs that aren't terminals but are displayed with a known width,
+/// such as the logs of CI services. Values that aren't a positive number are ignored.
+pub(crate) fn detect(stream: atty::Stream) -> Option<usize> {
+    terminal_width(stream).or_else(|| {
+        env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.trim().parse().ok())
+            .filter(|&columns| columns > 0)
+    })
+}
+
+#[cfg(unix)]
+fn terminal_width(stream: atty::Stream) -> Option<usize> {
+    let fd = match stream {
+        atty::Stream::Stdout => libc::STDOUT_FILENO,
+        atty::Stream::Stderr => libc::STDERR_FILENO,
+        atty::Stream::Stdin => libc::STDIN_FILENO,
+    };
+
+    // SAFETY: an all zero `winsize` is valid, and `TIOCGWINSZ` only writes a `winsize` to
+    // the pointer it is given, failing for file descriptors that aren't terminals
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) };
+    if result == 0 && size.ws_col > 0 {
+        Some(usize::from(size.ws_col))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn terminal_width(_stream: atty::Stream) -> Option<usize> {
+    None
+}
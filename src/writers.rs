@@ -1,20 +1,46 @@
 use std::fmt::{self, Display};
 #[cfg(feature = "capture-spantrace")]
-use tracing_error::{SpanTrace, SpanTraceStatus};
+use {
+    crate::{config::SpanTraceSelfField, spantrace_renderer::SpanTraceRenderer, ColorExt},
+    ansi_term::Color::*,
+    std::time::SystemTime,
+    tracing_error::{SpanTrace, SpanTraceStatus},
+};
 
+/// A writer that writes `header` before the first text written through it
+///
+/// The header is written at most once per [`ready`](#method.ready), right before the first
+/// non-empty write, and is counted as written as soon as writing it is attempted, so a write
+/// that is retried after the inner writer failed never duplicates it.
 pub(crate) struct HeaderWriter<'a, H, W> {
     pub(crate) inner: W,
     pub(crate) header: &'a H,
     pub(crate) started: bool,
 }
 
-pub(crate) struct ReadyHeaderWriter<'a, 'b, H, W>(&'b mut HeaderWriter<'a, H, W>);
+pub(crate) struct ReadyHeaderWriter<'a, 'b, H, W> {
+    writer: &'b mut HeaderWriter<'a, H, W>,
+    /// The whitespace written before anything else, held back until the header is written,
+    /// if whitespace alone doesn't start the section
+    pending: Option<String>,
+}
 
 impl<'a, H, W> HeaderWriter<'a, H, W> {
     pub(crate) fn ready(&mut self) -> ReadyHeaderWriter<'a, '_, H, W> {
         self.started = false;
 
-        ReadyHeaderWriter(self)
+        ReadyHeaderWriter {
+            writer: self,
+            pending: None,
+        }
+    }
+
+    /// Like `ready`, but the header is only written once something other than whitespace is,
+    /// and nothing is written if only whitespace ever is
+    pub(crate) fn ready_unless_blank(&mut self) -> ReadyHeaderWriter<'a, '_, H, W> {
+        let mut ready = self.ready();
+        ready.pending = Some(String::new());
+        ready
     }
 }
 
@@ -24,17 +50,260 @@ where
     W: fmt::Write,
 {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        if !self.0.started && !s.is_empty() {
-            self.0.inner.write_fmt(format_args!("{}", self.0.header))?;
-            self.0.started = true;
+        if !self.writer.started {
+            if let Some(pending) = &mut self.pending {
+                if s.trim().is_empty() {
+                    pending.push_str(s);
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.writer.started && !s.is_empty() {
+            // a header that fails halfway must not be written again when the write is retried
+            self.writer.started = true;
+            self.writer
+                .inner
+                .write_fmt(format_args!("{}", self.writer.header))?;
+            if let Some(pending) = self.pending.take() {
+                self.writer.inner.write_str(&pending)?;
+            }
+        }
+
+        self.writer.inner.write_str(s)
+    }
+}
+
+/// A writer that replaces `\r\n` line endings with `\n`, even when they're split across writes
+pub(crate) struct NormalizeCrlf<W> {
+    inner: W,
+    /// Whether the last write ended with a `\r`, which is held back until the next one
+    cr: bool,
+}
+
+impl<W: fmt::Write> NormalizeCrlf<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, cr: false }
+    }
+
+    /// Write the `\r` held back at the end of the text, if there is one
+    pub(crate) fn finish(mut self) -> fmt::Result {
+        if self.cr {
+            self.inner.write_char('\r')?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for NormalizeCrlf<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+
+        if std::mem::take(&mut self.cr) && !s.starts_with('\n') {
+            self.inner.write_char('\r')?;
+        }
+
+        let (s, cr) = match s.strip_suffix('\r') {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        if s.contains("\r\n") {
+            self.inner.write_str(&s.replace("\r\n", "\n"))?;
+        } else {
+            self.inner.write_str(s)?;
+        }
+        self.cr = cr;
+        Ok(())
+    }
+}
+
+/// A writer that only counts the bytes written to it
+#[derive(Default)]
+pub(crate) struct ByteCounter(pub(crate) usize);
+
+impl fmt::Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// A writer that counts the bytes written through it to `inner`
+pub(crate) struct CountingWriter<W> {
+    pub(crate) inner: W,
+    pub(crate) written: usize,
+}
+
+impl<W> CountingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: fmt::Write> fmt::Write for CountingWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_str(s)?;
+        self.written += s.len();
+        Ok(())
+    }
+}
+
+/// Remove all ANSI escape sequences from `s`
+pub(crate) fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('\x1b') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = &rest[escape_len(rest)..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Cap `s` at `max` visible characters, replacing the last one with `…` if
+/// anything was cut off
+///
+/// ANSI escape sequences are not counted and never split, colors are reset
+/// after the ellipsis if the text was truncated while any were active.
+pub(crate) fn truncate_visible(s: &str, max: usize) -> std::borrow::Cow<'_, str> {
+    if strip_ansi(s).chars().count() <= max {
+        return s.into();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut count = 0;
+    let mut escaped = false;
+    let mut rest = s;
+
+    // leave room for the ellipsis
+    let keep = max.saturating_sub(1);
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let len = escape_len(rest);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            escaped = true;
+            continue;
+        }
+
+        if count == keep {
+            break;
         }
 
-        self.0.inner.write_str(s)
+        out.push(c);
+        count += 1;
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if max > 0 {
+        out.push('…');
+    }
+
+    if escaped {
+        out.push_str("\x1b[0m");
     }
+
+    out.into()
+}
+
+/// `path` as text, with the bytes that aren't valid UTF-8 escaped as `\xNN`
+///
+/// Paths of object files built on other systems may be in another encoding,
+/// escaping keeps them readable and one column per character, which the
+/// replacement character doesn't in every terminal.
+pub(crate) fn display_path(path: &std::path::Path) -> std::borrow::Cow<'_, str> {
+    if let Some(path) = path.to_str() {
+        return path.into();
+    }
+
+    #[cfg(unix)]
+    {
+        use std::fmt::Write;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut out = String::new();
+        let mut rest = path.as_os_str().as_bytes();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    out.push_str(valid);
+                    return out.into();
+                }
+                Err(e) => {
+                    let (valid, invalid) = rest.split_at(e.valid_up_to());
+                    // the prefix was just checked to be valid
+                    out.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    let len = e.error_len().unwrap_or(invalid.len());
+                    for byte in &invalid[..len] {
+                        let _ = write!(out, "\\x{:02x}", byte);
+                    }
+                    rest = &invalid[len..];
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    path.to_string_lossy()
+}
+
+/// Shorten `path` to at most `max` characters by replacing its leading
+/// components with `...`
+///
+/// The file name is always kept, even if it alone exceeds `max`.
+pub(crate) fn ellipsize_path(path: &str, max: usize) -> std::borrow::Cow<'_, str> {
+    const ELLIPSIS: &str = "...";
+
+    if path.chars().count() <= max {
+        return path.into();
+    }
+
+    let mut tail = None;
+    for (i, c) in path.char_indices().rev() {
+        if c != '/' && c != '\\' {
+            continue;
+        }
+
+        let candidate = &path[i..];
+        if tail.is_some() && ELLIPSIS.len() + candidate.chars().count() > max {
+            break;
+        }
+        tail = Some(candidate);
+    }
+
+    match tail {
+        Some(tail) => format!("{}{}", ELLIPSIS, tail).into(),
+        None => path.into(),
+    }
+}
+
+/// Length in bytes of the ANSI escape sequence at the start of `s`
+pub(crate) fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    if bytes.get(1) != Some(&b'[') {
+        return 1;
+    }
+
+    bytes[2..]
+        .iter()
+        .position(|b| (0x40..=0x7e).contains(b))
+        .map_or(bytes.len(), |end| end + 3)
 }
 
 #[cfg(feature = "capture-spantrace")]
-pub(crate) struct FormattedSpanTrace<'a>(pub(crate) &'a SpanTrace);
+pub(crate) struct FormattedSpanTrace<'a> {
+    pub(crate) span_trace: &'a SpanTrace,
+    /// Entry timestamps for each frame of `span_trace`, innermost first
+    pub(crate) timestamps: Option<&'a [Option<SystemTime>]>,
+    pub(crate) self_field: &'a SpanTraceSelfField,
+    pub(crate) max_path_width: Option<usize>,
+    pub(crate) collapse_same_target: bool,
+}
 
 #[cfg(feature = "capture-spantrace")]
 impl fmt::Display for FormattedSpanTrace<'_> {
@@ -43,9 +312,17 @@ impl fmt::Display for FormattedSpanTrace<'_> {
         use indenter::Format;
         use std::fmt::Write;
 
-        match self.0.status() {
+        match self.span_trace.status() {
             SpanTraceStatus::CAPTURED => {
-                write!(indented(f).with_format(Format::Uniform { indentation: "  " }), "{}", color_spantrace::colorize(self.0))?;
+                let mut f = indented(f).with_format(Format::Uniform { indentation: "  " });
+                let renderer = SpanTraceRenderer {
+                    span_trace: self.span_trace,
+                    timestamps: self.timestamps.unwrap_or_default(),
+                    self_field: self.self_field,
+                    max_path_width: self.max_path_width,
+                    collapse_same_target: self.collapse_same_target,
+                };
+                write!(f, "{}", renderer)?;
             },
             SpanTraceStatus::UNSUPPORTED => write!(f, "Warning: SpanTrace capture is Unsupported.\nEnsure that you've setup an error layer and the versions match")?,
             _ => (),
@@ -54,3 +331,73 @@ impl fmt::Display for FormattedSpanTrace<'_> {
         Ok(())
     }
 }
+
+/// A span of a `SpanTrace`, for annotating the backtrace frames it correlates with
+#[cfg(feature = "capture-spantrace")]
+pub(crate) struct TraceSpan {
+    name: String,
+    fields: String,
+    file: Option<String>,
+    line: Option<u32>,
+}
+
+/// The spans of `span_trace`, innermost first, or none if it wasn't captured
+#[cfg(feature = "capture-spantrace")]
+pub(crate) fn trace_spans(span_trace: &SpanTrace) -> Vec<TraceSpan> {
+    let mut spans = vec![];
+    if span_trace.status() != SpanTraceStatus::CAPTURED {
+        return spans;
+    }
+
+    span_trace.with_spans(|metadata, fields| {
+        spans.push(TraceSpan {
+            name: format!("{}::{}", metadata.target(), metadata.name()),
+            fields: fields.to_owned(),
+            file: metadata.file().map(str::to_owned),
+            line: metadata.line(),
+        });
+        true
+    });
+
+    spans
+}
+
+/// The frames annotated with a span, as the number of the frame and the index of the span
+///
+/// A frame correlates with the span created closest above its line in the same file, and
+/// each span only annotates the innermost of `frames` it correlates with. Frames and spans
+/// without a source location don't correlate with anything.
+#[cfg(feature = "capture-spantrace")]
+pub(crate) fn correlate(
+    frames: &[&crate::config::Frame],
+    spans: &[TraceSpan],
+) -> Vec<(usize, usize)> {
+    let mut used = vec![false; spans.len()];
+    frames
+        .iter()
+        .filter_map(|frame| {
+            let (file, line) = (frame.filename.as_ref()?, frame.lineno?);
+            let (n, _, _) = spans
+                .iter()
+                .enumerate()
+                .filter_map(|(n, span)| Some((n, span.file.as_deref()?, span.line?)))
+                .filter(|&(_, span_file, span_line)| span_line <= line && file.ends_with(span_file))
+                .max_by_key(|&(_, _, span_line)| span_line)?;
+            if std::mem::replace(&mut used[n], true) {
+                return None;
+            }
+            Some((frame.n, n))
+        })
+        .collect()
+}
+
+#[cfg(feature = "capture-spantrace")]
+impl fmt::Display for TraceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in span {}", Red.make_intense().paint(&self.name))?;
+        if !self.fields.is_empty() {
+            write!(f, " with {}", Cyan.make_intense().paint(&self.fields))?;
+        }
+        Ok(())
+    }
+}